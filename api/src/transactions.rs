@@ -1003,7 +1003,11 @@ impl TransactionsApi {
             })?;
         match mempool_status.code {
             MempoolStatusCode::Accepted => Ok(()),
-            MempoolStatusCode::MempoolIsFull | MempoolStatusCode::TooManyTransactions => {
+            MempoolStatusCode::MempoolIsFull
+            | MempoolStatusCode::MempoolIsFullNoEvictableParking
+            | MempoolStatusCode::RateLimited
+            | MempoolStatusCode::TooManyTransactions
+            | MempoolStatusCode::TooManyTransactionsForAccount => {
                 Err(AptosError::new_with_error_code(
                     &mempool_status.message,
                     AptosErrorCode::MempoolIsFull,
@@ -1028,14 +1032,22 @@ impl TransactionsApi {
                     ))
                 }
             },
+            MempoolStatusCode::GasUnitPriceTooHigh | MempoolStatusCode::InsufficientBalance => {
+                Err(AptosError::new_with_error_code(
+                    mempool_status.message,
+                    AptosErrorCode::InvalidInput,
+                ))
+            },
             MempoolStatusCode::InvalidSeqNumber => Err(AptosError::new_with_error_code(
                 mempool_status.message,
                 AptosErrorCode::SequenceNumberTooOld,
             )),
-            MempoolStatusCode::InvalidUpdate => Err(AptosError::new_with_error_code(
-                mempool_status.message,
-                AptosErrorCode::InvalidTransactionUpdate,
-            )),
+            MempoolStatusCode::InvalidUpdate | MempoolStatusCode::ReplacementUnderpriced => {
+                Err(AptosError::new_with_error_code(
+                    mempool_status.message,
+                    AptosErrorCode::InvalidTransactionUpdate,
+                ))
+            },
             MempoolStatusCode::UnknownStatus => Err(AptosError::new_with_error_code(
                 format!("Transaction was rejected with status {}", mempool_status,),
                 AptosErrorCode::InternalError,
@@ -6,6 +6,7 @@ use once_cell::sync::Lazy;
 
 pub mod aptos_framework_sdk_builder;
 pub mod aptos_stdlib;
+pub mod aptos_token_objects_sdk_builder;
 pub mod aptos_token_sdk_builder;
 
 #[cfg(unix)]
@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::MAX_APPLICATION_MESSAGE_SIZE;
+use aptos_types::account_address::AccountAddress;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 pub const DEFAULT_BROADCAST_BUCKETS: &[u64] =
     &[0, 150, 300, 500, 1000, 3000, 5000, 10000, 100000, 1000000];
@@ -25,10 +27,74 @@ pub struct MempoolConfig {
     pub shared_mempool_max_concurrent_inbound_syncs: usize,
     pub shared_mempool_tick_interval_ms: u64,
     pub system_transaction_timeout_secs: u64,
+    // each txn expires from mempool after the system timeout plus a random offset below this, so
+    // that the txns inserted at the same time are not all gc-ed at once. 0 disables
+    pub system_transaction_timeout_jitter_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
     pub broadcast_buckets: Vec<u64>,
     pub eager_expire_threshold_ms: Option<u64>,
     pub eager_expire_time_ms: u64,
+    // fixed seed for the randomized selection paths (e.g. parking lot eviction), so that the
+    // mempool behavior is reproducible in tests. Uses entropy if not set.
+    pub randomization_seed: Option<u64>,
+    // what to do with the transactions in mempool when a new epoch starts
+    pub epoch_change_policy: EpochChangePolicy,
+    // transactions younger than this are exempt from eviction when mempool is full, 0 disables
+    pub min_protected_age_ms: u64,
+    // max distance between the lowest and highest parked sequence numbers of an account, if set
+    pub max_parked_seq_span: Option<u64>,
+    // when full with no parked txn to evict, evict the lowest priority local txn to admit a
+    // higher priority local one
+    pub evict_lower_priority_local_txns: bool,
+    // percentage by which the ranking score of a txn is lowered, when picking the local txn to
+    // evict, per other txn of its sender in mempool, so that accounts hogging mempool lose their
+    // txns before sparse ones with comparable ranking scores. 0 disables
+    pub eviction_account_penalty_pct: u64,
+    // gas price updates staged within this window are applied to the indexes at once, 0 disables
+    pub update_batch_window_ms: u64,
+    // min percentage by which a replacement txn has to raise the ranking score of the txn it
+    // replaces, 0 accepts any increase
+    pub replace_by_fee_min_bump_pct: u64,
+    // txns with a gas unit price above this sanity cap are rejected, e.g. fat-fingered ones
+    pub max_gas_unit_price: Option<u64>,
+    // how the ranking score that orders txns in mempool is derived
+    pub ranking_strategy: RankingStrategyType,
+    // percentage by which the ranking score of a txn is lowered per sequence number it is ahead
+    // of the committed sequence number of its sender, to favor breadth across accounts over the
+    // long runs of a single one. 0 disables
+    pub sequence_number_penalty_pct: u64,
+    // only broadcast the contiguous prefix of the txns of each sender: the txns following a
+    // rejected one are parked, and not broadcast, until the gap is filled
+    pub broadcast_contiguous_prefix_only: bool,
+    // resubmitting a txn identical to one in mempool resets its insertion time and system TTL,
+    // e.g. to keep it alive
+    pub refresh_ttl_on_resubmission: bool,
+    // max number of txns of a single account, if set. Unlike `capacity_per_user`, exceeding it
+    // is reported as TooManyTransactionsForAccount, so it can be told apart from a full mempool
+    pub max_txns_per_account: Option<usize>,
+    // reject txns whose max fee (max gas amount * gas unit price) exceeds the balance of their
+    // sender in storage. Costs a storage read per admitted txn
+    pub check_balance_at_admission: bool,
+    // ignore commit notifications of txns that are already committed, e.g. resent by consensus
+    // on retry, instead of processing them again
+    pub dedup_commit_notifications: bool,
+    // max number of txns admitted per second across all senders, if set. Excess submissions are
+    // rejected as RateLimited before any other check, e.g. under DoS
+    pub max_admissions_per_sec: Option<usize>,
+    // max number of txns admitted per second from a single sender, if set. Excess submissions
+    // are rejected as RateLimited
+    pub per_sender_rate_limit: Option<usize>,
+    // txns of these senders are never evicted from the parking lot when mempool is full, e.g.
+    // the account of a service run by the validator
+    pub priority_senders: Vec<AccountAddress>,
+    // percentage of the ranking score of the txns of a sender inherited by its txns with lower
+    // sequence numbers when pulling blocks, so that a high priority txn parked behind a low
+    // priority one pulls its whole chain in earlier. 0 disables, 100 fully inherits
+    pub priority_inheritance_pct: u64,
+    // file the parking lot is periodically persisted to, if set. The parked txns are imported
+    // back from it on restart, instead of having to be resubmitted
+    pub parking_lot_persistence_path: Option<PathBuf>,
+    pub parking_lot_persistence_interval_secs: u64,
 }
 
 impl Default for MempoolConfig {
@@ -47,10 +113,55 @@ impl Default for MempoolConfig {
             capacity_per_user: 100,
             default_failovers: 3,
             system_transaction_timeout_secs: 600,
+            system_transaction_timeout_jitter_secs: 0,
             system_transaction_gc_interval_ms: 60_000,
             broadcast_buckets: DEFAULT_BROADCAST_BUCKETS.to_vec(),
             eager_expire_threshold_ms: Some(10_000),
             eager_expire_time_ms: 3_000,
+            randomization_seed: None,
+            epoch_change_policy: EpochChangePolicy::Keep,
+            min_protected_age_ms: 0,
+            max_parked_seq_span: None,
+            evict_lower_priority_local_txns: false,
+            eviction_account_penalty_pct: 0,
+            update_batch_window_ms: 0,
+            replace_by_fee_min_bump_pct: 0,
+            max_gas_unit_price: None,
+            ranking_strategy: RankingStrategyType::GasPrice,
+            sequence_number_penalty_pct: 0,
+            broadcast_contiguous_prefix_only: false,
+            refresh_ttl_on_resubmission: false,
+            max_txns_per_account: None,
+            check_balance_at_admission: false,
+            dedup_commit_notifications: true,
+            max_admissions_per_sec: None,
+            per_sender_rate_limit: None,
+            priority_senders: vec![],
+            priority_inheritance_pct: 0,
+            parking_lot_persistence_path: None,
+            parking_lot_persistence_interval_secs: 60,
         }
     }
 }
+
+/// Policy applied to the transactions in mempool at epoch boundaries.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpochChangePolicy {
+    /// Keep all transactions.
+    Keep,
+    /// Drop all transactions that are not ready (i.e. in the parking lot).
+    DropParked,
+    /// Drop all transactions.
+    DropAll,
+}
+
+/// How the ranking score of the transactions in mempool is derived.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingStrategyType {
+    /// Rank by the score computed by the VM validator, i.e. the gas unit price.
+    GasPrice,
+    /// Rank by the max fee a transaction may pay per byte of its raw transaction.
+    FeePerByte,
+}
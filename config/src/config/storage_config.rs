@@ -26,6 +26,16 @@ pub struct RocksdbConfig {
     pub block_cache_size: u64,
     pub block_size: u64,
     pub cache_index_and_filter_blocks: bool,
+    pub cache_index_and_filter_blocks_with_high_priority: bool,
+    pub max_write_buffer_number: i32,
+    pub memtable_memory_budget: Option<u64>,
+    pub bottommost_zstd_compression: bool,
+    pub level0_file_num_compaction_trigger: Option<i32>,
+    pub level0_slowdown_writes_trigger: Option<i32>,
+    pub level0_stop_writes_trigger: Option<i32>,
+    pub target_file_size_base: Option<u64>,
+    pub target_file_size_multiplier: Option<i32>,
+    pub statistics_level: RocksdbStatisticsLevel,
 }
 
 impl Default for RocksdbConfig {
@@ -45,11 +55,49 @@ impl Default for RocksdbConfig {
             block_size: 4 * (1u64 << 10),
             // Whether cache index and filter blocks into block cache.
             cache_index_and_filter_blocks: false,
+            // Whether put index and filter blocks into the high-priority pool of the block cache,
+            // only takes effect when `cache_index_and_filter_blocks` is true.
+            cache_index_and_filter_blocks_with_high_priority: false,
+            // Max number of memtables of a column family, including the one being written to.
+            max_write_buffer_number: 2,
+            // If set, the total memory budget for the memtables of all the shards of a sharded
+            // db, evenly divided across them and their column families. Otherwise the memtables
+            // are sized by RocksDB defaults, for each of the shards.
+            memtable_memory_budget: None,
+            // Whether to compress the bottommost level, which holds most of the (cold) data, with
+            // ZSTD instead of LZ4 like the upper levels. Only applies to the state kv db.
+            bottommost_zstd_compression: false,
+            // If set, the number of level-0 files that triggers a compaction, slows down writes
+            // and stops writes respectively, to tune write amplification. Otherwise RocksDB
+            // defaults apply. Only applies to the state kv db.
+            level0_file_num_compaction_trigger: None,
+            level0_slowdown_writes_trigger: None,
+            level0_stop_writes_trigger: None,
+            // If set, the target size of the SST files of level 1, and the factor it grows by
+            // with every level below, to control the number of SST files. Otherwise RocksDB
+            // defaults apply. Only applies to the state kv db.
+            target_file_size_base: None,
+            target_file_size_multiplier: None,
+            // How much RocksDB statistics are collected, at the cost of some CPU on every
+            // operation. Only applies to the state kv db.
+            statistics_level: RocksdbStatisticsLevel::Disabled,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+/// Statistics collected by a RocksDB instance.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RocksdbStatisticsLevel {
+    /// Collect no statistics.
+    Disabled,
+    /// Collect the tickers and histograms of the foreground operations.
+    Minimal,
+    /// On top of `Minimal`, account the IO of background flushes and compactions.
+    Full,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RocksdbConfigs {
     pub ledger_db_config: RocksdbConfig,
@@ -60,6 +108,21 @@ pub struct RocksdbConfigs {
     pub use_sharded_state_merkle_db: bool,
     pub state_kv_db_config: RocksdbConfig,
     pub index_db_config: RocksdbConfig,
+    // Whether to record the changelog of state values (in version order) for downstream
+    // consumers, e.g. indexers.
+    pub enable_state_kv_changelog: bool,
+    // If set, the WAL of the state kv db lives under this directory instead of next to the data,
+    // e.g. to put it on a faster device. Every shard gets its own sub-directory, mirroring the
+    // layout of the data directory.
+    pub wal_dir: Option<PathBuf>,
+    // If set, the estimated number of keys and size of every state kv db shard are logged at
+    // this interval, e.g. to follow their growth on long-running nodes.
+    pub state_kv_db_stats_dump_interval_secs: Option<u64>,
+    // Number of times opening the state kv db is retried while its lock is held, e.g. briefly by
+    // another process, before giving up. The delay before the first retry is
+    // `state_kv_db_open_retry_delay_ms`, and doubles with every retry.
+    pub state_kv_db_open_retries: usize,
+    pub state_kv_db_open_retry_delay_ms: u64,
 }
 
 impl Default for RocksdbConfigs {
@@ -74,6 +137,11 @@ impl Default for RocksdbConfigs {
                 max_open_files: 1000,
                 ..Default::default()
             },
+            enable_state_kv_changelog: false,
+            wal_dir: None,
+            state_kv_db_stats_dump_interval_secs: None,
+            state_kv_db_open_retries: 0,
+            state_kv_db_open_retry_delay_ms: 100,
         }
     }
 }
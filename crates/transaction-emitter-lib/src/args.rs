@@ -5,7 +5,7 @@ use anyhow::{bail, format_err, Result};
 use aptos::common::types::EncodingType;
 use aptos_config::keys::ConfigKey;
 use aptos_crypto::ed25519::Ed25519PrivateKey;
-use aptos_sdk::types::chain_id::ChainId;
+use aptos_sdk::{move_types::account_address::AccountAddress, types::chain_id::ChainId};
 use clap::{ArgEnum, ArgGroup, Parser};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -112,6 +112,18 @@ pub enum TransactionTypeArg {
     CustomFunctionLargeModuleWorkingSet,
     CreateNewResource,
     NoOp,
+    WalletFlow,
+    TableAndResourceGroup,
+    CoinStoreRegistration,
+    AbortingTransfer,
+    RandomExpirationTransfer,
+    StakingLifecycle,
+    KeyTypeMix,
+    DexSwap,
+    ResourceAccountCreation,
+    ModulePublish,
+    SizeSweep,
+    LargePayload,
 }
 
 impl Default for TransactionTypeArg {
@@ -141,6 +153,12 @@ pub struct EmitArgs {
     #[clap(long, default_value = "30")]
     pub txn_expiration_time_secs: u64,
 
+    /// Delays the expiration of each transaction by a random number of seconds up to this, so
+    /// that a burst of transactions doesn't expire all at once. Applies to the coin-transfer,
+    /// coin-store-registration, aborting-transfer and dex-swap transaction types.
+    #[clap(long, default_value = "0")]
+    pub max_expiration_jitter_secs: u64,
+
     /// Time to run --emit-tx for in seconds.
     #[clap(long, default_value = "60")]
     pub duration: u64,
@@ -163,6 +181,11 @@ pub struct EmitArgs {
     #[clap(long, min_values = 0)]
     pub transaction_phases: Vec<usize>,
 
+    /// Mixes the transaction types of a phase within every batch, picking the type of each
+    /// transaction as of the weights, instead of the type of each whole batch.
+    #[clap(long)]
+    pub mix_within_batch: bool,
+
     #[clap(long)]
     pub gas_price: Option<u64>,
 
@@ -181,6 +204,15 @@ pub struct EmitArgs {
     #[clap(long)]
     pub max_transactions_per_account: Option<usize>,
 
+    /// Delegation pool the staking-lifecycle transaction type delegates to.
+    #[clap(long, parse(try_from_str = AccountAddress::from_hex_literal))]
+    pub delegation_pool_address: Option<AccountAddress>,
+
+    /// Address of the liquidity pool, and of its `amm` module, the dex-swap transaction type
+    /// swaps against.
+    #[clap(long, parse(try_from_str = AccountAddress::from_hex_literal))]
+    pub dex_pool_address: Option<AccountAddress>,
+
     // In cases you want to run txn emitter from multiple machines,
     // and want to make sure that initialization succeeds
     // (account minting and txn-specific initialization), before the
@@ -66,8 +66,17 @@ impl<'t> AccountMinter<'t> {
         let expected_num_seed_accounts = (total_requested_accounts / 50)
             .clamp(1, (total_requested_accounts as f32).sqrt() as usize + 1);
         let num_accounts = total_requested_accounts - accounts.len(); // Only minting extra accounts
+        let extra_coins_per_txn = req
+            .transaction_mix_per_phase
+            .iter()
+            .flatten()
+            .map(|(transaction_type, _)| transaction_type.extra_coins_per_txn())
+            .max()
+            .unwrap_or(0);
         let coins_per_account = (req.expected_max_txns / total_requested_accounts as u64)
-            .checked_mul(SEND_AMOUNT + req.expected_gas_per_txn * req.gas_price)
+            .checked_mul(
+                SEND_AMOUNT + extra_coins_per_txn + req.expected_gas_per_txn * req.gas_price,
+            )
             .unwrap()
             .checked_add(req.max_gas_per_txn * req.gas_price)
             .unwrap(); // extra coins for secure to pay none zero gas price
@@ -281,35 +290,20 @@ impl<'t> AccountMinter<'t> {
         failed_requests: &[AtomicUsize],
     ) -> Result<Vec<LocalAccount>> {
         info!("Creating and funding seeds accounts");
-        let mut i = 0;
-        let mut seed_accounts = vec![];
-        while i < seed_account_num {
-            let batch_size = min(max_submit_batch_size, seed_account_num - i);
-            let mut rng = StdRng::from_rng(self.rng()).unwrap();
-            let mut batch = gen_random_accounts(batch_size, &mut rng);
-            let txn_factory = &self.txn_factory;
-            let create_requests: Vec<_> = batch
-                .iter()
-                .map(|account| {
-                    create_and_fund_account_request(
-                        if let Some(account) = &mut new_source_account {
-                            account
-                        } else {
-                            self.source_account
-                        },
-                        coins_per_seed_account,
-                        account.public_key(),
-                        txn_factory,
-                    )
-                })
-                .collect();
-            txn_executor
-                .execute_transactions_with_counter(&create_requests, failed_requests)
-                .await?;
-
-            i += batch_size;
-            seed_accounts.append(&mut batch);
-        }
+        let mut rng = StdRng::from_rng(self.rng()).unwrap();
+        let seed_accounts = gen_random_accounts(seed_account_num, &mut rng);
+        fund_accounts_parallel(
+            new_source_account
+                .as_mut()
+                .unwrap_or(&mut *self.source_account),
+            &seed_accounts,
+            coins_per_seed_account,
+            max_submit_batch_size,
+            txn_executor,
+            &self.txn_factory,
+            failed_requests,
+        )
+        .await?;
 
         Ok(seed_accounts)
     }
@@ -401,6 +395,45 @@ fn gen_rng_for_reusable_account(count: usize) -> Vec<StdRng> {
     rngs
 }
 
+/// Creates and funds `accounts` with `coins_per_account` each, by transferring coins from
+/// `source_account`. All the requests are signed upfront, so the sequence number of
+/// `source_account` advances once per account, and submitted concurrently in chunks of at most
+/// `max_submit_batch_size`, keeping at most MAX_TXNS_IN_FLIGHT_PER_ACCOUNT of them in flight.
+pub async fn fund_accounts_parallel(
+    source_account: &mut LocalAccount,
+    accounts: &[LocalAccount],
+    coins_per_account: u64,
+    max_submit_batch_size: usize,
+    txn_executor: &dyn TransactionExecutor,
+    txn_factory: &TransactionFactory,
+    failed_requests: &[AtomicUsize],
+) -> Result<()> {
+    let create_requests: Vec<_> = accounts
+        .iter()
+        .map(|account| {
+            create_and_fund_account_request(
+                source_account,
+                coins_per_account,
+                account.public_key(),
+                txn_factory,
+            )
+        })
+        .collect();
+
+    let chunk_size = max_submit_batch_size.clamp(1, MAX_TXNS_IN_FLIGHT_PER_ACCOUNT);
+    let chunk_futures = create_requests
+        .chunks(chunk_size)
+        .map(|chunk| txn_executor.execute_transactions_with_counter(chunk, failed_requests));
+    futures::stream::iter(chunk_futures)
+        .buffered(MAX_TXNS_IN_FLIGHT_PER_ACCOUNT / chunk_size)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+        .with_context(|| format!("Account {} couldn't fund accounts", source_account.address()))?;
+    Ok(())
+}
+
 /// Create `num_new_accounts` by transferring coins from `source_account`. Return Vec of created
 /// accounts
 async fn create_and_fund_new_accounts<R>(
@@ -507,3 +540,66 @@ pub fn create_and_fund_account_request(
 }
 
 const CREATION_PARALLELISM: usize = 500;
+// Mempool accepts at most this many txns of an account at once.
+const MAX_TXNS_IN_FLIGHT_PER_ACCOUNT: usize = 100;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockExecutor;
+    use aptos_sdk::{
+        bcs,
+        move_types::account_address::AccountAddress,
+        types::{chain_id::ChainId, transaction::TransactionPayload},
+    };
+    use std::collections::HashSet;
+
+    #[tokio::test]
+    async fn test_fund_accounts_parallel() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut source_account = LocalAccount::generate(&mut rng);
+        let accounts = gen_random_accounts(500, &mut rng);
+        let executor = MockExecutor::default();
+        let failed_requests = vec![AtomicUsize::new(0)];
+
+        fund_accounts_parallel(
+            &mut source_account,
+            &accounts,
+            1_000,
+            20,
+            &executor,
+            &TransactionFactory::new(ChainId::test()),
+            &failed_requests,
+        )
+        .await
+        .unwrap();
+
+        // One sequence number of the source per funded account, each submitted once.
+        assert_eq!(source_account.sequence_number(), 500);
+        let executed = executor.executed.into_inner().unwrap();
+        let mut sequence_numbers: Vec<_> = executed
+            .iter()
+            .map(SignedTransaction::sequence_number)
+            .collect();
+        sequence_numbers.sort_unstable();
+        assert_eq!(sequence_numbers, (0..500).collect::<Vec<_>>());
+        let receivers: HashSet<_> = executed
+            .iter()
+            .map(|txn| match txn.payload() {
+                TransactionPayload::EntryFunction(entry_function) => {
+                    bcs::from_bytes::<AccountAddress>(&entry_function.args()[0]).unwrap()
+                },
+                _ => panic!("Unexpected payload"),
+            })
+            .collect();
+        assert_eq!(receivers, accounts.iter().map(LocalAccount::address).collect());
+        assert!(accounts
+            .iter()
+            .all(|account| account.sequence_number() == 0));
+
+        // Chunks were submitted concurrently, within the per account limit of mempool.
+        let max_in_flight = executor.max_in_flight.into_inner();
+        assert!(max_in_flight > 20);
+        assert!(max_in_flight <= MAX_TXNS_IN_FLIGHT_PER_ACCOUNT);
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{format_err, Result};
+use aptos_logger::{sample, sample::SampleRate, warn};
+use aptos_transaction_generator_lib::backpressure_generator::BacklogReporter;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use url::Url;
+
+// Number of transactions in the mempool of a node, as exported in its metrics.
+const MEMPOOL_SIZE_METRIC: &str = "aptos_core_mempool_index_size{index=\"system_ttl\"}";
+
+/// Reports the total number of transactions in the mempools of the nodes, read from their
+/// Prometheus metrics endpoints (e.g. http://<node>:9101/metrics). The metrics are polled in the
+/// background by `run`, and the last successful read is reported in between.
+pub struct MempoolMetricsBacklogReporter {
+    client: reqwest::Client,
+    metrics_urls: Vec<Url>,
+    backlog: AtomicUsize,
+}
+
+impl MempoolMetricsBacklogReporter {
+    pub fn new(metrics_urls: Vec<Url>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            metrics_urls,
+            backlog: AtomicUsize::new(0),
+        }
+    }
+
+    /// Polls the metrics of the nodes every `interval`, until `stop` is set.
+    pub async fn run(self: Arc<Self>, interval: Duration, stop: Arc<AtomicBool>) {
+        while !stop.load(Ordering::Relaxed) {
+            match self.query_backlog().await {
+                Ok(backlog) => self.backlog.store(backlog, Ordering::Relaxed),
+                Err(err) => {
+                    sample!(
+                        SampleRate::Duration(Duration::from_secs(60)),
+                        warn!("Failed querying the mempool backlog: {}", err)
+                    );
+                },
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    async fn query_backlog(&self) -> Result<usize> {
+        let mut backlog = 0;
+        for url in &self.metrics_urls {
+            let metrics = self
+                .client
+                .get(url.clone())
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            backlog += parse_mempool_size(&metrics).ok_or_else(|| {
+                format_err!("No {} in the metrics of {}", MEMPOOL_SIZE_METRIC, url)
+            })?;
+        }
+        Ok(backlog)
+    }
+}
+
+impl BacklogReporter for MempoolMetricsBacklogReporter {
+    fn backlog(&self) -> usize {
+        self.backlog.load(Ordering::Relaxed)
+    }
+}
+
+fn parse_mempool_size(metrics: &str) -> Option<usize> {
+    metrics.lines().find_map(|line| {
+        let value: f64 = line
+            .strip_prefix(MEMPOOL_SIZE_METRIC)?
+            .trim()
+            .parse()
+            .ok()?;
+        Some(value as usize)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mempool_size() {
+        let metrics = "\
+# HELP aptos_core_mempool_index_size Size of a core mempool index
+# TYPE aptos_core_mempool_index_size gauge
+aptos_core_mempool_index_size{index=\"parking_lot\"} 7
+aptos_core_mempool_index_size{index=\"system_ttl\"} 1234
+aptos_core_mempool_index_size{index=\"timeline\"} 1200
+";
+        assert_eq!(parse_mempool_size(metrics), Some(1234));
+        assert_eq!(parse_mempool_size("aptos_connections 3\n"), None);
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::emitter::stats::AtomicHistogramAccumulator;
+use anyhow::Result;
+use aptos_sdk::{
+    move_types::account_address::AccountAddress, types::transaction::SignedTransaction,
+};
+use aptos_transaction_generator_lib::TransactionExecutor;
+use async_trait::async_trait;
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::Instant,
+};
+
+// Up to 240s, like the latency histogram of the emitter stats, with a finer precision.
+const LATENCY_HISTOGRAM_CAPACITY: usize = 24_000;
+const LATENCY_HISTOGRAM_STEP_WIDTH_MS: u64 = 10;
+
+#[derive(Debug, Clone, Default)]
+pub struct LatencySummary {
+    pub count: u64,
+    pub p50_latency_ms: u64,
+    pub p90_latency_ms: u64,
+    pub p99_latency_ms: u64,
+}
+
+impl fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} calls, p50: {} ms, p90: {} ms, p99: {} ms",
+            self.count, self.p50_latency_ms, self.p90_latency_ms, self.p99_latency_ms,
+        )
+    }
+}
+
+/// Wraps a TransactionExecutor, recording the wall-clock time of each call executing
+/// transactions, successful or not, with a precision of 10ms.
+pub struct LatencyTrackingExecutor<E> {
+    inner: E,
+    count: AtomicU64,
+    latencies_ms: AtomicHistogramAccumulator,
+}
+
+impl<E: TransactionExecutor> LatencyTrackingExecutor<E> {
+    pub fn new(inner: E) -> Self {
+        Self {
+            inner,
+            count: AtomicU64::new(0),
+            latencies_ms: AtomicHistogramAccumulator::new(
+                LATENCY_HISTOGRAM_CAPACITY,
+                LATENCY_HISTOGRAM_STEP_WIDTH_MS,
+            ),
+        }
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        let latencies_ms = self.latencies_ms.snapshot();
+        LatencySummary {
+            count: self.count.load(Ordering::Relaxed),
+            p50_latency_ms: latencies_ms.percentile(50, 100),
+            p90_latency_ms: latencies_ms.percentile(90, 100),
+            p99_latency_ms: latencies_ms.percentile(99, 100),
+        }
+    }
+
+    fn record(&self, start: Instant) {
+        self.latencies_ms
+            .record_data_point(start.elapsed().as_millis() as u64, 1);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl<E: TransactionExecutor> TransactionExecutor for LatencyTrackingExecutor<E> {
+    async fn get_account_balance(&self, account_address: AccountAddress) -> Result<u64> {
+        self.inner.get_account_balance(account_address).await
+    }
+
+    async fn query_sequence_number(&self, account_address: AccountAddress) -> Result<u64> {
+        self.inner.query_sequence_number(account_address).await
+    }
+
+    async fn execute_transactions(&self, txns: &[SignedTransaction]) -> Result<()> {
+        let start = Instant::now();
+        let result = self.inner.execute_transactions(txns).await;
+        self.record(start);
+        result
+    }
+
+    async fn execute_transactions_with_counter(
+        &self,
+        txns: &[SignedTransaction],
+        failure_counter: &[AtomicUsize],
+    ) -> Result<()> {
+        let start = Instant::now();
+        let result = self
+            .inner
+            .execute_transactions_with_counter(txns, failure_counter)
+            .await;
+        self.record(start);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockExecutor;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_latency_percentiles() {
+        let executor =
+            LatencyTrackingExecutor::new(MockExecutor::with_latency(Duration::from_millis(50)));
+        for _ in 0..10 {
+            executor.execute_transactions(&[]).await.unwrap();
+        }
+        // Not recorded.
+        executor
+            .get_account_balance(AccountAddress::ONE)
+            .await
+            .unwrap();
+
+        let summary = executor.summary();
+        assert_eq!(summary.count, 10);
+        for latency_ms in [
+            summary.p50_latency_ms,
+            summary.p90_latency_ms,
+            summary.p99_latency_ms,
+        ] {
+            assert!((50..250).contains(&latency_ms), "{}", summary);
+        }
+    }
+}
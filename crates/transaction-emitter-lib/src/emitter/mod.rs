@@ -2,12 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod account_minter;
+pub mod backlog_reporter;
+pub mod latency_tracking_executor;
 pub mod stats;
 pub mod submission_worker;
 pub mod transaction_executor;
 
 use crate::emitter::{
     account_minter::AccountMinter,
+    backlog_reporter::MempoolMetricsBacklogReporter,
+    latency_tracking_executor::LatencyTrackingExecutor,
     stats::{DynamicStatsTracking, TxnStats},
     submission_worker::SubmissionWorker,
     transaction_executor::RestApiTransactionExecutor,
@@ -22,7 +26,12 @@ use aptos_sdk::{
     transaction_builder::{aptos_stdlib, TransactionFactory},
     types::{transaction::SignedTransaction, LocalAccount},
 };
-use aptos_transaction_generator_lib::{create_txn_generator_creator, TransactionType};
+use aptos_time_service::TimeService;
+use aptos_transaction_generator_lib::{
+    backpressure_generator::BackpressureTransactionGeneratorCreator,
+    bursty_generator::BurstyTransactionGeneratorCreator, create_txn_generator_creator,
+    TransactionType,
+};
 use futures::future::{try_join_all, FutureExt};
 use once_cell::sync::Lazy;
 use rand::{rngs::StdRng, seq::IteratorRandom, Rng};
@@ -37,12 +46,15 @@ use std::{
     time::{Duration, Instant},
 };
 use tokio::{runtime::Handle, task::JoinHandle, time};
+use url::Url;
 
 // Max is 100k TPS for 3 hours
 const MAX_TXNS: u64 = 1_000_000_000;
 
 const MAX_RETRIES: usize = 8;
 
+const BACKLOG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 // This retry policy is used for important client calls necessary for setting
 // up the test (e.g. account creation) and collecting its results (e.g. checking
 // account sequence numbers). If these fail, the whole test fails. We do not use
@@ -126,6 +138,8 @@ pub struct EmitJobRequest {
     init_gas_price_multiplier: u64,
 
     transaction_mix_per_phase: Vec<Vec<(TransactionType, usize)>>,
+    // pick the transaction type of each transaction, instead of each batch
+    mix_within_batch: bool,
 
     txn_expiration_time_secs: u64,
     max_transactions_per_account: usize,
@@ -138,6 +152,11 @@ pub struct EmitJobRequest {
 
     init_retry_count: usize,
     init_retry_interval: Duration,
+
+    // (burst_size, gap): emit transactions in bursts, separated by quiet periods.
+    bursts: Option<(usize, Duration)>,
+    // (metrics_urls, max_backlog): pause generation while the mempools hold too many transactions.
+    backpressure: Option<(Vec<Url>, usize)>,
 }
 
 impl Default for EmitJobRequest {
@@ -153,6 +172,7 @@ impl Default for EmitJobRequest {
             mint_to_root: false,
             init_gas_price_multiplier: 10,
             transaction_mix_per_phase: vec![vec![(TransactionType::default(), 1)]],
+            mix_within_batch: false,
             txn_expiration_time_secs: 60,
             max_transactions_per_account: 20,
             expected_max_txns: MAX_TXNS,
@@ -161,6 +181,8 @@ impl Default for EmitJobRequest {
             coordination_delay_between_instances: Duration::from_secs(0),
             init_retry_count: MAX_RETRIES,
             init_retry_interval: Duration::from_secs(5),
+            bursts: None,
+            backpressure: None,
         }
     }
 }
@@ -223,6 +245,11 @@ impl EmitJobRequest {
         self
     }
 
+    pub fn mix_within_batch(mut self) -> Self {
+        self.mix_within_batch = true;
+        self
+    }
+
     pub fn get_num_phases(&self) -> usize {
         self.transaction_mix_per_phase.len()
     }
@@ -255,6 +282,20 @@ impl EmitJobRequest {
         self
     }
 
+    /// Emit transactions in bursts of `burst_size` transactions (per worker), separated by quiet
+    /// periods of `gap`, instead of a steady stream.
+    pub fn bursts(mut self, burst_size: usize, gap: Duration) -> Self {
+        self.bursts = Some((burst_size, gap));
+        self
+    }
+
+    /// Pause generation while the mempools of the nodes hold more than `max_backlog` transactions
+    /// in total, as read from their metrics endpoints at `metrics_urls`.
+    pub fn backpressure(mut self, metrics_urls: Vec<Url>, max_backlog: usize) -> Self {
+        self.backpressure = Some((metrics_urls, max_backlog));
+        self
+    }
+
     pub fn calculate_mode_params(&self) -> EmitModeParams {
         let clients_count = self.rest_clients.len();
 
@@ -546,11 +587,11 @@ impl TxnEmitter {
             init_txn_factory.clone(),
             StdRng::from_seed(seed),
         );
-        let txn_executor = RestApiTransactionExecutor {
+        let txn_executor = LatencyTrackingExecutor::new(RestApiTransactionExecutor {
             rest_clients: req.rest_clients.clone(),
             max_retries: req.init_retry_count,
             retry_after: req.init_retry_interval,
-        };
+        });
         let mut all_accounts = account_minter
             .create_accounts(&txn_executor, &req, &mode_params, num_accounts)
             .await?;
@@ -558,16 +599,38 @@ impl TxnEmitter {
         let stats = Arc::new(DynamicStatsTracking::new(stats_tracking_phases));
         let tokio_handle = Handle::current();
 
+        let generator_rng = self.from_rng();
         let mut txn_generator_creator = create_txn_generator_creator(
             &req.transaction_mix_per_phase,
+            req.mix_within_batch,
             num_workers,
             &mut all_accounts,
             &txn_executor,
             &txn_factory,
             &init_txn_factory,
+            generator_rng,
             stats.get_cur_phase_obj(),
         )
         .await;
+        info!("Setup transaction latency: {}", txn_executor.summary());
+        if let Some((burst_size, gap)) = req.bursts {
+            txn_generator_creator = Box::new(BurstyTransactionGeneratorCreator::new(
+                txn_generator_creator,
+                TimeService::real(),
+                burst_size,
+                gap,
+            ));
+        }
+        if let Some((metrics_urls, max_backlog)) = &req.backpressure {
+            let backlog_reporter =
+                Arc::new(MempoolMetricsBacklogReporter::new(metrics_urls.clone()));
+            tokio_handle.spawn(backlog_reporter.clone().run(BACKLOG_POLL_INTERVAL, stop.clone()));
+            txn_generator_creator = Box::new(BackpressureTransactionGeneratorCreator::new(
+                txn_generator_creator,
+                backlog_reporter,
+                *max_backlog,
+            ));
+        }
 
         if !req.coordination_delay_between_instances.is_zero() {
             info!(
@@ -20,6 +20,7 @@ pub struct TxnStats {
     pub latency: u64,
     pub latency_samples: u64,
     pub latency_buckets: AtomicHistogramSnapshot,
+    pub submission_latency_buckets: AtomicHistogramSnapshot,
     pub lasted: Duration,
 }
 
@@ -34,14 +35,17 @@ pub struct TxnStatsRate {
     pub p50_latency: u64,
     pub p90_latency: u64,
     pub p99_latency: u64,
+    pub p50_submission_latency: u64,
+    pub p90_submission_latency: u64,
+    pub p99_submission_latency: u64,
 }
 
 impl fmt::Display for TxnStatsRate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, failed submission: {} tnx/s, latency: {} ms, (p50: {} ms, p90: {} ms, p99: {} ms), latency samples: {}",
-            self.submitted, self.committed, self.expired, self.failed_submission, self.latency, self.p50_latency, self.p90_latency, self.p99_latency, self.latency_samples,
+            "submitted: {} txn/s, committed: {} txn/s, expired: {} txn/s, failed submission: {} tnx/s, latency: {} ms, (p50: {} ms, p90: {} ms, p99: {} ms), latency samples: {}, submission latency (p50: {} ms, p90: {} ms, p99: {} ms)",
+            self.submitted, self.committed, self.expired, self.failed_submission, self.latency, self.p50_latency, self.p90_latency, self.p99_latency, self.latency_samples, self.p50_submission_latency, self.p90_submission_latency, self.p99_submission_latency,
         )
     }
 }
@@ -66,6 +70,9 @@ impl TxnStats {
             p50_latency: self.latency_buckets.percentile(50, 100),
             p90_latency: self.latency_buckets.percentile(90, 100),
             p99_latency: self.latency_buckets.percentile(99, 100),
+            p50_submission_latency: self.submission_latency_buckets.percentile(50, 100),
+            p90_submission_latency: self.submission_latency_buckets.percentile(90, 100),
+            p99_submission_latency: self.submission_latency_buckets.percentile(99, 100),
         }
     }
 }
@@ -92,6 +99,8 @@ impl Sub for &TxnStats {
             latency: self.latency - other.latency,
             latency_samples: self.latency_samples - other.latency_samples,
             latency_buckets: &self.latency_buckets - &other.latency_buckets,
+            submission_latency_buckets: &self.submission_latency_buckets
+                - &other.submission_latency_buckets,
             lasted: self.lasted - other.lasted,
         }
     }
@@ -109,12 +118,14 @@ impl Add for &TxnStats {
             latency: self.latency + other.latency,
             latency_samples: self.latency_samples + other.latency_samples,
             latency_buckets: &self.latency_buckets + &other.latency_buckets,
+            submission_latency_buckets: &self.submission_latency_buckets
+                + &other.submission_latency_buckets,
             lasted: self.lasted + other.lasted,
         }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct StatsAccumulator {
     pub submitted: AtomicU64,
     pub committed: AtomicU64,
@@ -123,6 +134,26 @@ pub struct StatsAccumulator {
     pub latency: AtomicU64,
     pub latency_samples: AtomicU64,
     pub latencies: Arc<AtomicHistogramAccumulator>,
+    // Wall-clock time of each batch submission, from sending it to the node accepting it.
+    pub submission_latencies: Arc<AtomicHistogramAccumulator>,
+}
+
+impl Default for StatsAccumulator {
+    fn default() -> Self {
+        Self {
+            submitted: AtomicU64::default(),
+            committed: AtomicU64::default(),
+            expired: AtomicU64::default(),
+            failed_submission: AtomicU64::default(),
+            latency: AtomicU64::default(),
+            latency_samples: AtomicU64::default(),
+            latencies: Arc::default(),
+            submission_latencies: Arc::new(AtomicHistogramAccumulator::new(
+                DEFAULT_HISTOGRAM_CAPACITY,
+                SUBMISSION_HISTOGRAM_STEP_WIDTH,
+            )),
+        }
+    }
 }
 
 impl StatsAccumulator {
@@ -135,6 +166,7 @@ impl StatsAccumulator {
             latency: self.latency.load(Ordering::Relaxed),
             latency_samples: self.latency_samples.load(Ordering::Relaxed),
             latency_buckets: self.latencies.snapshot(),
+            submission_latency_buckets: self.submission_latencies.snapshot(),
             lasted,
         }
     }
@@ -144,6 +176,9 @@ impl StatsAccumulator {
 const DEFAULT_HISTOGRAM_CAPACITY: usize = 2400;
 // we don't have better precision than ~300 ms anyways.
 const DEFAULT_HISTOGRAM_STEP_WIDTH: u64 = 100;
+// submissions take tens of ms, and can't take longer than the 24s this covers with the
+// default capacity.
+const SUBMISSION_HISTOGRAM_STEP_WIDTH: u64 = 10;
 
 #[derive(Debug)]
 pub struct AtomicHistogramAccumulator {
@@ -401,9 +436,25 @@ mod test {
             latency: 0,
             latency_samples: 0,
             latency_buckets: histogram.snapshot(),
+            submission_latency_buckets: AtomicHistogramSnapshot::default(),
             lasted: Duration::from_secs(10),
         };
         let res = stat.latency_buckets.percentile(9, 10);
         assert_eq!(res, 900);
     }
+
+    #[test]
+    pub fn test_submission_latency_rate() {
+        let stats = StatsAccumulator::default();
+        // 10 submissions, taking 10ms, 20ms, 30ms ... 100ms
+        for i in 1..11 {
+            stats
+                .submission_latencies
+                .record_data_point(i as u64 * 10, 1);
+        }
+        let rate = stats.accumulate(Duration::from_secs(10)).rate();
+        assert_eq!(rate.p50_submission_latency, 50);
+        assert_eq!(rate.p90_submission_latency, 90);
+        assert_eq!(rate.p99_submission_latency, 90);
+    }
 }
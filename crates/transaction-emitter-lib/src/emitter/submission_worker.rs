@@ -98,6 +98,12 @@ impl SubmissionWorker {
             wait_until += wait_duration;
 
             let requests = self.gen_requests();
+            if requests.is_empty() {
+                // Generator is idle (e.g. in between bursts), wait for the next cycle.
+                self.sleep_check_done(wait_until.saturating_duration_since(Instant::now()))
+                    .await;
+                continue;
+            }
 
             let mut account_to_start_and_end_seq_num = HashMap::new();
             for req in requests.iter() {
@@ -297,7 +303,12 @@ pub async fn submit_transactions(
         .submitted
         .fetch_add(txns.len() as u64, Ordering::Relaxed);
 
-    match client.submit_batch_bcs(txns).await {
+    let submission_start = Instant::now();
+    let result = client.submit_batch_bcs(txns).await;
+    stats
+        .submission_latencies
+        .record_data_point(submission_start.elapsed().as_millis() as u64, 1);
+    match result {
         Err(e) => {
             stats
                 .failed_submission
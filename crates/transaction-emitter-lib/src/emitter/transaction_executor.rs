@@ -8,7 +8,7 @@ use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{
     move_types::account_address::AccountAddress, types::transaction::SignedTransaction,
 };
-use aptos_transaction_generator_lib::TransactionExecutor;
+use aptos_transaction_generator_lib::{SubmissionError, TransactionExecutor};
 use async_trait::async_trait;
 use futures::future::join_all;
 use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
@@ -40,6 +40,7 @@ impl RestApiTransactionExecutor {
         failure_counter: &[AtomicUsize],
         run_seed: u64,
     ) -> Result<()> {
+        let mut submitted = false;
         for i in 0..self.max_retries {
             // All transactions from the same sender, need to be submitted to the same client
             // in the same retry round, so that they are not placed in parking lot.
@@ -52,7 +53,7 @@ impl RestApiTransactionExecutor {
             .concat();
             let mut seeded_rng = StdRng::from_seed(*aptos_crypto::HashValue::sha3_256_of(&seed));
             let rest_client = self.random_rest_client_from_rng(&mut seeded_rng);
-            if submit_and_check(
+            match submit_and_check(
                 rest_client,
                 txn,
                 self.retry_after,
@@ -60,18 +61,22 @@ impl RestApiTransactionExecutor {
                 &failure_counter[(i * 2 + 1).min(failure_counter.len() - 1)],
             )
             .await
-            .is_ok()
             {
-                return Ok(());
+                Ok(()) => return Ok(()),
+                Err(err) => submitted |= err.downcast_ref::<SubmissionError>().is_none(),
             };
         }
 
         // if submission timeouts, it might still get committed:
-        self.random_rest_client()
+        match self
+            .random_rest_client()
             .wait_for_signed_transaction_bcs(txn)
-            .await?;
-
-        Ok(())
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if submitted => Err(err.into()),
+            Err(err) => Err(err).context(SubmissionError),
+        }
     }
 }
 
@@ -82,7 +87,8 @@ async fn submit_and_check(
     submit_failure_counter: &AtomicUsize,
     wait_failure_counter: &AtomicUsize,
 ) -> Result<()> {
-    if let Err(err) = rest_client.submit_bcs(txn).await {
+    let submit_result = rest_client.submit_bcs(txn).await;
+    if let Err(err) = &submit_result {
         sample!(
             SampleRate::Duration(Duration::from_secs(60)),
             warn!(
@@ -112,6 +118,9 @@ async fn submit_and_check(
             )
         );
         wait_failure_counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if submit_result.is_err() {
+            return Err(err).context(SubmissionError);
+        }
         Err(err)?;
     }
     Ok(())
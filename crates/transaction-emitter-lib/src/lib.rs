@@ -7,6 +7,8 @@ mod args;
 mod cluster;
 pub mod emitter;
 mod instance;
+#[cfg(test)]
+mod test_utils;
 mod wrappers;
 
 // These are the top level things you should need to run the emitter.
@@ -0,0 +1,67 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Executors shared by the tests of the emitter.
+
+use anyhow::Result;
+use aptos_sdk::{
+    move_types::account_address::AccountAddress, types::transaction::SignedTransaction,
+};
+use aptos_transaction_generator_lib::TransactionExecutor;
+use async_trait::async_trait;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// Executor recording the submitted transactions instead of sending them, each submission
+/// taking `latency`. All the accounts have a zero balance and sequence number.
+#[derive(Default)]
+pub(crate) struct MockExecutor {
+    latency: Duration,
+    pub(crate) executed: Mutex<Vec<SignedTransaction>>,
+    in_flight: AtomicUsize,
+    pub(crate) max_in_flight: AtomicUsize,
+}
+
+impl MockExecutor {
+    pub(crate) fn with_latency(latency: Duration) -> Self {
+        Self {
+            latency,
+            ..Self::default()
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionExecutor for MockExecutor {
+    async fn get_account_balance(&self, _account_address: AccountAddress) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn query_sequence_number(&self, _account_address: AccountAddress) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn execute_transactions(&self, txns: &[SignedTransaction]) -> Result<()> {
+        let in_flight = self.in_flight.fetch_add(txns.len(), Ordering::SeqCst) + txns.len();
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        // Let the other submissions start, even without latency.
+        tokio::task::yield_now().await;
+        tokio::time::sleep(self.latency).await;
+        self.executed.lock().unwrap().extend_from_slice(txns);
+        self.in_flight.fetch_sub(txns.len(), Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn execute_transactions_with_counter(
+        &self,
+        txns: &[SignedTransaction],
+        _failure_counter: &[AtomicUsize],
+    ) -> Result<()> {
+        self.execute_transactions(txns).await
+    }
+}
@@ -11,7 +11,11 @@ use crate::{
 use anyhow::{bail, Context, Result};
 use aptos_logger::{error, info};
 use aptos_sdk::transaction_builder::TransactionFactory;
-use aptos_transaction_generator_lib::{EntryPoints, TransactionType};
+use aptos_transaction_generator_lib::{
+    aborting_transfer_generator::AbortKind, dex_swap_generator::SwapDirection,
+    random_expiration_transfer_generator::MIN_EXPIRATION_SECS, EntryPoints, TransactionType,
+    SEND_AMOUNT,
+};
 use rand::{rngs::StdRng, SeedableRng};
 use std::time::{Duration, Instant};
 
@@ -85,6 +89,12 @@ pub async fn emit_transactions_with_cluster(
         StdRng::from_entropy(),
     );
 
+    let max_gas_fee_per_txn = args
+        .max_gas_per_txn
+        .unwrap_or(aptos_global_constants::MAX_GAS_AMOUNT)
+        * args
+            .gas_price
+            .unwrap_or(aptos_global_constants::GAS_UNIT_PRICE);
     let arg_transaction_types = args
         .transaction_type
         .iter()
@@ -92,6 +102,7 @@ pub async fn emit_transactions_with_cluster(
             TransactionTypeArg::CoinTransfer => TransactionType::CoinTransfer {
                 invalid_transaction_ratio: args.invalid_tx,
                 sender_use_account_pool: false,
+                max_expiration_jitter_secs: args.max_expiration_jitter_secs,
             },
             TransactionTypeArg::AccountGeneration => TransactionType::default_account_generation(),
             TransactionTypeArg::AccountGenerationLargePool => TransactionType::AccountGeneration {
@@ -99,7 +110,9 @@ pub async fn emit_transactions_with_cluster(
                 max_account_working_set: 50_000_000,
                 creation_balance: 200_000_000,
             },
-            TransactionTypeArg::NftMintAndTransfer => TransactionType::NftMintAndTransfer,
+            TransactionTypeArg::NftMintAndTransfer => {
+                TransactionType::default_nft_mint_and_transfer()
+            },
             TransactionTypeArg::PublishPackage => TransactionType::PublishPackage {
                 use_account_pool: false,
             },
@@ -122,6 +135,61 @@ pub async fn emit_transactions_with_cluster(
                 num_modules: 1,
                 use_account_pool: false,
             },
+            // enough for the wallet to send SEND_AMOUNT back at the max gas of the run
+            TransactionTypeArg::WalletFlow => TransactionType::WalletFlow {
+                funding_amount: SEND_AMOUNT + max_gas_fee_per_txn,
+            },
+            TransactionTypeArg::TableAndResourceGroup => TransactionType::TableAndResourceGroup {
+                num_keys_per_txn: 10,
+            },
+            TransactionTypeArg::CoinStoreRegistration => TransactionType::CoinStoreRegistration {
+                transfers_per_receiver: 1,
+                max_expiration_jitter_secs: args.max_expiration_jitter_secs,
+            },
+            TransactionTypeArg::AbortingTransfer => TransactionType::AbortingTransfer {
+                abort_percentage: 10,
+                abort_kind: AbortKind::Mixed,
+                max_expiration_jitter_secs: args.max_expiration_jitter_secs,
+            },
+            TransactionTypeArg::RandomExpirationTransfer => {
+                TransactionType::RandomExpirationTransfer {
+                    max_expiration_secs: args.txn_expiration_time_secs.max(MIN_EXPIRATION_SECS),
+                }
+            },
+            TransactionTypeArg::StakingLifecycle => TransactionType::StakingLifecycle {
+                pool_address: args
+                    .delegation_pool_address
+                    .expect("--delegation-pool-address is required for staking-lifecycle"),
+                // minimum amount of coins the delegation pool accepts to add as stake
+                stake_amount: 1_000_000_000,
+            },
+            TransactionTypeArg::KeyTypeMix => TransactionType::KeyTypeMix {
+                multi_ed25519_percentage: 50,
+            },
+            TransactionTypeArg::DexSwap => TransactionType::DexSwap {
+                pool_address: args
+                    .dex_pool_address
+                    .expect("--dex-pool-address is required for dex-swap"),
+                min_amount: 1,
+                max_amount: 100,
+                direction: SwapDirection::Both,
+                max_expiration_jitter_secs: args.max_expiration_jitter_secs,
+            },
+            TransactionTypeArg::ResourceAccountCreation => {
+                TransactionType::ResourceAccountCreation { seed_prefix: 0 }
+            },
+            TransactionTypeArg::ModulePublish => TransactionType::ModulePublish { mutate: true },
+            TransactionTypeArg::SizeSweep => TransactionType::SizeSweep {
+                min_size: 0,
+                max_size: 60_000,
+                num_buckets: 7,
+                txns_per_bucket: 10_000,
+                num_modules: 1,
+            },
+            TransactionTypeArg::LargePayload => TransactionType::LargePayload {
+                payload_size: 60_000,
+                num_modules: 1,
+            },
         })
         .collect::<Vec<_>>();
 
@@ -177,6 +245,9 @@ pub async fn emit_transactions_with_cluster(
     if reuse_accounts {
         emit_job_request = emit_job_request.reuse_accounts();
     }
+    if args.mix_within_batch {
+        emit_job_request = emit_job_request.mix_within_batch();
+    }
     if let Some(max_transactions_per_account) = args.max_transactions_per_account {
         emit_job_request =
             emit_job_request.max_transactions_per_account(max_transactions_per_account);
@@ -0,0 +1,318 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{
+    gen_expiration_jitter, TransactionGenerator, TransactionGeneratorCreator, SEND_AMOUNT,
+};
+use aptos_sdk::{
+    bcs,
+    move_types::{account_address::AccountAddress, ident_str, language_storage::ModuleId},
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{
+        transaction::{EntryFunction, SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::ops::RangeInclusive;
+
+/// Amount transferred by the transactions that are meant to abort. No account can hold it, so
+/// the transfer always aborts on insufficient balance, and it labels the transaction as such.
+pub const ABORTING_TRANSFER_AMOUNT: u64 = u64::MAX;
+
+/// Function called by the transactions that are meant to fail on a missing function. It doesn't
+/// exist in the `coin` module, and it labels the transaction as such.
+pub const MISSING_FUNCTION_NAME: &str = "aborting_transfer_missing_function";
+
+/// How the transactions that are meant to fail do so.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AbortKind {
+    /// Transfers more coins than any account holds.
+    InsufficientBalance,
+    /// Calls a function that doesn't exist.
+    MissingFunction,
+    /// Either of the above, picked at random for each transaction.
+    Mixed,
+}
+
+/// Generates transfers of every account to itself, `abort_percentage` percent of which fail
+/// during execution, as of `abort_kind`, to exercise the abort handling of the executor. Failed
+/// transactions are still committed, so the sequence numbers of the accounts stay in sync.
+pub struct AbortingTransferGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    abort_percentage: usize,
+    abort_kind: AbortKind,
+    expiration_jitter_secs: RangeInclusive<u64>,
+}
+
+impl AbortingTransferGenerator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        abort_percentage: usize,
+        abort_kind: AbortKind,
+    ) -> Self {
+        assert!(abort_percentage <= 100);
+        Self {
+            rng,
+            txn_factory,
+            abort_percentage,
+            abort_kind,
+            expiration_jitter_secs: 0..=0,
+        }
+    }
+
+    /// Delays the expiration of each generated transaction by a random number of seconds in
+    /// `expiration_jitter_secs`, so that a burst of transactions doesn't expire all at once.
+    pub fn set_expiration_jitter(&mut self, expiration_jitter_secs: RangeInclusive<u64>) {
+        assert!(!expiration_jitter_secs.is_empty());
+        self.expiration_jitter_secs = expiration_jitter_secs;
+    }
+
+    /// Returns whether `txn` was generated to abort.
+    pub fn is_aborting(txn: &SignedTransaction) -> bool {
+        Self::abort_kind_of(txn).is_some()
+    }
+
+    /// Returns how `txn` was generated to abort, if it was, so that the failures it causes can
+    /// be counted as expected.
+    pub fn abort_kind_of(txn: &SignedTransaction) -> Option<AbortKind> {
+        let entry_function = match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => entry_function,
+            _ => return None,
+        };
+        if entry_function.function().as_str() == MISSING_FUNCTION_NAME {
+            return Some(AbortKind::MissingFunction);
+        }
+        let amount = entry_function.args().get(1)?;
+        if bcs::from_bytes::<u64>(amount).ok() == Some(ABORTING_TRANSFER_AMOUNT) {
+            Some(AbortKind::InsufficientBalance)
+        } else {
+            None
+        }
+    }
+
+    fn aborting_payload(&mut self, account: &LocalAccount) -> TransactionPayload {
+        let abort_kind = match self.abort_kind {
+            AbortKind::Mixed if self.rng.gen_bool(0.5) => AbortKind::InsufficientBalance,
+            AbortKind::Mixed => AbortKind::MissingFunction,
+            abort_kind => abort_kind,
+        };
+        match abort_kind {
+            AbortKind::MissingFunction => TransactionPayload::EntryFunction(EntryFunction::new(
+                ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned()),
+                ident_str!(MISSING_FUNCTION_NAME).to_owned(),
+                vec![],
+                vec![],
+            )),
+            _ => aptos_stdlib::aptos_coin_transfer(account.address(), ABORTING_TRANSFER_AMOUNT),
+        }
+    }
+}
+
+impl TransactionGenerator for AbortingTransferGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let num_txns = accounts.len() * transactions_per_account;
+        let num_aborting = num_txns * self.abort_percentage / 100;
+        let mut aborting: Vec<_> = (0..num_txns).map(|i| i < num_aborting).collect();
+        aborting.shuffle(&mut self.rng);
+
+        let mut aborting = aborting.into_iter();
+        let mut requests = Vec::with_capacity(num_txns);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let payload = if aborting.next().unwrap() {
+                    self.aborting_payload(account)
+                } else {
+                    aptos_stdlib::aptos_coin_transfer(account.address(), SEND_AMOUNT)
+                };
+                let expiration_jitter_secs =
+                    gen_expiration_jitter(&mut self.rng, &self.expiration_jitter_secs);
+                let builder = self
+                    .txn_factory
+                    .payload_with_expiration_jitter(payload, expiration_jitter_secs);
+                requests.push(account.sign_with_transaction_builder(builder));
+            }
+        }
+        requests
+    }
+}
+
+pub struct AbortingTransferGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    abort_percentage: usize,
+    abort_kind: AbortKind,
+    expiration_jitter_secs: RangeInclusive<u64>,
+}
+
+impl AbortingTransferGeneratorCreator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        abort_percentage: usize,
+        abort_kind: AbortKind,
+    ) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            abort_percentage,
+            abort_kind,
+            expiration_jitter_secs: 0..=0,
+        }
+    }
+
+    pub fn with_expiration_jitter(mut self, expiration_jitter_secs: RangeInclusive<u64>) -> Self {
+        self.expiration_jitter_secs = expiration_jitter_secs;
+        self
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for AbortingTransferGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        let mut generator = AbortingTransferGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.abort_percentage,
+            self.abort_kind,
+        );
+        generator.set_expiration_jitter(self.expiration_jitter_secs.clone());
+        Box::new(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::chain_id::ChainId;
+
+    #[test]
+    fn test_abort_percentage() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator = AbortingTransferGenerator::new(
+            rng,
+            TransactionFactory::new(ChainId::test()),
+            30,
+            AbortKind::InsufficientBalance,
+        );
+
+        for _ in 0..3 {
+            let txns = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+            assert_eq!(txns.len(), 100);
+            let num_aborting = txns
+                .iter()
+                .filter(|txn| AbortingTransferGenerator::is_aborting(txn))
+                .count();
+            assert_eq!(num_aborting, 30);
+        }
+
+        let mut generator = AbortingTransferGenerator::new(
+            StdRng::seed_from_u64(1),
+            TransactionFactory::new(ChainId::test()),
+            0,
+            AbortKind::Mixed,
+        );
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+        assert!(!txns.iter().any(AbortingTransferGenerator::is_aborting));
+    }
+
+    #[test]
+    fn test_sequence_numbers_contiguous_across_aborts() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator = AbortingTransferGenerator::new(
+            rng,
+            TransactionFactory::new(ChainId::test()),
+            30,
+            AbortKind::InsufficientBalance,
+        );
+
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 20);
+        for (account, txns) in accounts.iter().zip(txns.chunks(20)) {
+            assert!(txns.iter().all(|txn| txn.sender() == account.address()));
+            // Aborting txns are interleaved with successful ones of the same account, and the
+            // later ones still follow them in sequence.
+            let sequence_numbers: Vec<_> = txns.iter().map(|txn| txn.sequence_number()).collect();
+            assert_eq!(sequence_numbers, (0..20).collect::<Vec<_>>());
+            assert_eq!(account.sequence_number(), 20);
+        }
+        let num_accounts_with_mix = txns
+            .chunks(20)
+            .filter(|txns| {
+                txns.iter().any(AbortingTransferGenerator::is_aborting)
+                    && !txns.iter().all(AbortingTransferGenerator::is_aborting)
+            })
+            .count();
+        assert!(num_accounts_with_mix > 0);
+    }
+
+    #[test]
+    fn test_abort_kinds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let count_kinds = |txns: &[SignedTransaction]| {
+            let count = |kind| {
+                txns
+                    .iter()
+                    .filter(|txn| AbortingTransferGenerator::abort_kind_of(txn) == Some(kind))
+                    .count()
+            };
+            (
+                count(AbortKind::InsufficientBalance),
+                count(AbortKind::MissingFunction),
+            )
+        };
+
+        let mut generator = AbortingTransferGenerator::new(
+            StdRng::seed_from_u64(1),
+            TransactionFactory::new(ChainId::test()),
+            40,
+            AbortKind::MissingFunction,
+        );
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+        assert_eq!(count_kinds(&txns), (0, 40));
+
+        // Mixed failures still add up to the abort percentage, with both kinds.
+        let mut generator = AbortingTransferGenerator::new(
+            StdRng::seed_from_u64(1),
+            TransactionFactory::new(ChainId::test()),
+            40,
+            AbortKind::Mixed,
+        );
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+        let (insufficient_balance, missing_function) = count_kinds(&txns);
+        assert_eq!(insufficient_balance + missing_function, 40);
+        assert!(insufficient_balance > 0 && missing_function > 0);
+    }
+
+    #[test]
+    fn test_expiration_jitter() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator = AbortingTransferGenerator::new(
+            rng,
+            TransactionFactory::new(ChainId::test()),
+            30,
+            AbortKind::Mixed,
+        );
+        generator.set_expiration_jitter(10..=100);
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+        let min_expiration = txns.iter().map(|txn| txn.expiration_timestamp_secs()).min();
+        let max_expiration = txns.iter().map(|txn| txn.expiration_timestamp_secs()).max();
+        // Aborting txns are delayed like the others.
+        assert!(max_expiration.unwrap() - min_expiration.unwrap() > 50);
+        assert_eq!(
+            txns.iter()
+                .filter(|txn| AbortingTransferGenerator::is_aborting(txn))
+                .count(),
+            30
+        );
+    }
+}
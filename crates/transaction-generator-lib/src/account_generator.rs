@@ -128,6 +128,7 @@ impl TransactionGenerator for AccountGenerator {
 }
 
 pub struct AccountGeneratorCreator {
+    rng: StdRng,
     txn_factory: TransactionFactory,
     addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
     accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
@@ -138,6 +139,7 @@ pub struct AccountGeneratorCreator {
 
 impl AccountGeneratorCreator {
     pub fn new(
+        rng: StdRng,
         txn_factory: TransactionFactory,
         addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
         accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
@@ -151,6 +153,7 @@ impl AccountGeneratorCreator {
         }
 
         Self {
+            rng,
             txn_factory,
             addresses_pool,
             accounts_pool,
@@ -165,7 +168,7 @@ impl AccountGeneratorCreator {
 impl TransactionGeneratorCreator for AccountGeneratorCreator {
     async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
         Box::new(AccountGenerator::new(
-            StdRng::from_entropy(),
+            StdRng::from_rng(&mut self.rng).unwrap(),
             self.txn_factory.clone(),
             self.addresses_pool.clone(),
             self.accounts_pool.clone(),
@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::types::{transaction::SignedTransaction, LocalAccount};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Reports the number of transactions pending on the nodes transactions are submitted to,
+/// e.g. the size of their mempools.
+pub trait BacklogReporter: Sync + Send {
+    fn backlog(&self) -> usize;
+}
+
+/// Wraps another generator, and stops generating transactions while the reported backlog is
+/// above `max_backlog`. Generation resumes as soon as the backlog drains back to `max_backlog`.
+pub struct BackpressureTransactionGenerator {
+    inner: Box<dyn TransactionGenerator>,
+    backlog_reporter: Arc<dyn BacklogReporter>,
+    max_backlog: usize,
+}
+
+impl BackpressureTransactionGenerator {
+    pub fn new(
+        inner: Box<dyn TransactionGenerator>,
+        backlog_reporter: Arc<dyn BacklogReporter>,
+        max_backlog: usize,
+    ) -> Self {
+        Self {
+            inner,
+            backlog_reporter,
+            max_backlog,
+        }
+    }
+}
+
+impl TransactionGenerator for BackpressureTransactionGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        if self.backlog_reporter.backlog() > self.max_backlog {
+            return Vec::new();
+        }
+        self.inner.generate_transactions(accounts, transactions_per_account)
+    }
+}
+
+pub struct BackpressureTransactionGeneratorCreator {
+    inner: Box<dyn TransactionGeneratorCreator>,
+    backlog_reporter: Arc<dyn BacklogReporter>,
+    max_backlog: usize,
+}
+
+impl BackpressureTransactionGeneratorCreator {
+    pub fn new(
+        inner: Box<dyn TransactionGeneratorCreator>,
+        backlog_reporter: Arc<dyn BacklogReporter>,
+        max_backlog: usize,
+    ) -> Self {
+        Self {
+            inner,
+            backlog_reporter,
+            max_backlog,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for BackpressureTransactionGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(BackpressureTransactionGenerator::new(
+            self.inner.create_transaction_generator().await,
+            self.backlog_reporter.clone(),
+            self.max_backlog,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TransferToSelfGenerator;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockBacklogReporter {
+        backlog: AtomicUsize,
+    }
+
+    impl BacklogReporter for MockBacklogReporter {
+        fn backlog(&self) -> usize {
+            self.backlog.load(Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn test_pauses_while_backlog_is_too_large() {
+        let reporter = Arc::new(MockBacklogReporter {
+            backlog: AtomicUsize::new(0),
+        });
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..2).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator = BackpressureTransactionGenerator::new(
+            Box::new(TransferToSelfGenerator::new(100)),
+            reporter.clone(),
+            100,
+        );
+        let mut generate = || generator.generate_transactions(accounts.iter_mut().collect(), 3);
+
+        assert_eq!(generate().len(), 6);
+
+        reporter.backlog.store(101, Ordering::Relaxed);
+        assert!(generate().is_empty());
+        assert!(generate().is_empty());
+
+        reporter.backlog.store(100, Ordering::Relaxed);
+        assert_eq!(generate().len(), 6);
+    }
+}
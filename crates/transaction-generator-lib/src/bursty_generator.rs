@@ -0,0 +1,142 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::types::{transaction::SignedTransaction, LocalAccount};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use async_trait::async_trait;
+use std::{
+    cmp::max,
+    time::{Duration, Instant},
+};
+
+/// Wraps another generator, and emits its transactions in bursts of (roughly) `burst_size`
+/// transactions, separated by quiet periods of `gap`, during which nothing is generated.
+pub struct BurstyTransactionGenerator {
+    inner: Box<dyn TransactionGenerator>,
+    time_service: TimeService,
+    burst_size: usize,
+    gap: Duration,
+    remaining_in_burst: usize,
+    quiet_until: Option<Instant>,
+}
+
+impl BurstyTransactionGenerator {
+    pub fn new(
+        inner: Box<dyn TransactionGenerator>,
+        time_service: TimeService,
+        burst_size: usize,
+        gap: Duration,
+    ) -> Self {
+        assert!(burst_size > 0, "burst_size needs to be larger than 0");
+        Self {
+            inner,
+            time_service,
+            burst_size,
+            gap,
+            remaining_in_burst: burst_size,
+            quiet_until: None,
+        }
+    }
+}
+
+impl TransactionGenerator for BurstyTransactionGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let now = self.time_service.now();
+        if let Some(quiet_until) = self.quiet_until {
+            if now < quiet_until {
+                return Vec::new();
+            }
+            self.quiet_until = None;
+            self.remaining_in_burst = self.burst_size;
+        }
+
+        // Only use as many accounts as fit into the rest of the burst, but at least one, so the
+        // burst can overshoot by less than `transactions_per_account`.
+        let num_accounts = max(1, self.remaining_in_burst / max(1, transactions_per_account));
+        let txns = self.inner.generate_transactions(
+            accounts.into_iter().take(num_accounts).collect(),
+            transactions_per_account,
+        );
+
+        self.remaining_in_burst = self.remaining_in_burst.saturating_sub(txns.len());
+        if self.remaining_in_burst == 0 {
+            self.quiet_until = Some(now + self.gap);
+        }
+        txns
+    }
+}
+
+pub struct BurstyTransactionGeneratorCreator {
+    inner: Box<dyn TransactionGeneratorCreator>,
+    time_service: TimeService,
+    burst_size: usize,
+    gap: Duration,
+}
+
+impl BurstyTransactionGeneratorCreator {
+    pub fn new(
+        inner: Box<dyn TransactionGeneratorCreator>,
+        time_service: TimeService,
+        burst_size: usize,
+        gap: Duration,
+    ) -> Self {
+        Self {
+            inner,
+            time_service,
+            burst_size,
+            gap,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for BurstyTransactionGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(BurstyTransactionGenerator::new(
+            self.inner.create_transaction_generator().await,
+            self.time_service.clone(),
+            self.burst_size,
+            self.gap,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TransferToSelfGenerator;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_submissions_cluster_into_bursts() {
+        let time_service = TimeService::mock();
+        let mock_time = time_service.clone().into_mock();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..2).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator = BurstyTransactionGenerator::new(
+            Box::new(TransferToSelfGenerator::new(100)),
+            time_service,
+            4,
+            Duration::from_secs(10),
+        );
+
+        // Generate once per second, and record how many transactions we got at each second.
+        let mut counts = Vec::new();
+        for _ in 0..25 {
+            counts.push(generator.generate_transactions(accounts.iter_mut().collect(), 1).len());
+            mock_time.advance_secs(1);
+        }
+
+        // Bursts of 4 transactions (2 per call), each followed by a 10s quiet period.
+        let mut expected = vec![0; 25];
+        for burst_start in [0, 11, 22] {
+            expected[burst_start] = 2;
+            expected[burst_start + 1] = 2;
+        }
+        assert_eq!(counts, expected);
+    }
+}
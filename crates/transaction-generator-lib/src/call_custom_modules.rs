@@ -71,6 +71,7 @@ impl TransactionGenerator for CallCustomModulesGenerator {
 }
 
 pub struct CallCustomModulesCreator {
+    rng: StdRng,
     txn_factory: TransactionFactory,
     packages: Arc<Vec<Package>>,
     entry_point: EntryPoints,
@@ -78,6 +79,7 @@ pub struct CallCustomModulesCreator {
 
 impl CallCustomModulesCreator {
     pub async fn new(
+        mut rng: StdRng,
         txn_factory: TransactionFactory,
         init_txn_factory: TransactionFactory,
         accounts: &mut [LocalAccount],
@@ -85,22 +87,16 @@ impl CallCustomModulesCreator {
         entry_point: EntryPoints,
         num_modules: usize,
     ) -> Self {
-        let mut rng = StdRng::from_entropy();
-        assert!(accounts.len() >= num_modules);
-        let mut requests = Vec::with_capacity(accounts.len());
-        let mut package_handler = PackageHandler::new();
-        let mut packages = Vec::new();
-        for account in accounts.iter_mut().take(num_modules) {
-            let package = package_handler.pick_package(&mut rng, account);
-            let txn = package.publish_transaction(account, &init_txn_factory);
-            requests.push(txn);
-            packages.push(package);
-        }
-        info!("Publishing {} packages", requests.len());
-        txn_executor.execute_transactions(&requests).await.unwrap();
-        info!("Done publishing {} packages", requests.len());
-
+        let packages = publish_packages(
+            &mut rng,
+            &init_txn_factory,
+            accounts,
+            txn_executor,
+            num_modules,
+        )
+        .await;
         Self {
+            rng,
             txn_factory,
             packages: Arc::new(packages),
             entry_point,
@@ -108,11 +104,35 @@ impl CallCustomModulesCreator {
     }
 }
 
+/// Publishes a package from each of the first `num_modules` accounts, and returns them.
+pub(crate) async fn publish_packages(
+    rng: &mut StdRng,
+    init_txn_factory: &TransactionFactory,
+    accounts: &mut [LocalAccount],
+    txn_executor: &dyn TransactionExecutor,
+    num_modules: usize,
+) -> Vec<Package> {
+    assert!(accounts.len() >= num_modules);
+    let mut requests = Vec::with_capacity(accounts.len());
+    let mut package_handler = PackageHandler::new();
+    let mut packages = Vec::new();
+    for account in accounts.iter_mut().take(num_modules) {
+        let package = package_handler.pick_package(rng, account);
+        let txn = package.publish_transaction(account, init_txn_factory);
+        requests.push(txn);
+        packages.push(package);
+    }
+    info!("Publishing {} packages", requests.len());
+    txn_executor.execute_transactions(&requests).await.unwrap();
+    info!("Done publishing {} packages", requests.len());
+    packages
+}
+
 #[async_trait]
 impl TransactionGeneratorCreator for CallCustomModulesCreator {
     async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
         Box::new(CallCustomModulesGenerator::new(
-            StdRng::from_entropy(),
+            StdRng::from_rng(&mut self.rng).unwrap(),
             self.txn_factory.clone(),
             self.packages.clone(),
             self.entry_point,
@@ -0,0 +1,195 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{
+    gen_expiration_jitter, TransactionGenerator, TransactionGeneratorCreator, SEND_AMOUNT,
+};
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::HashMap, ops::RangeInclusive};
+
+struct Receiver {
+    address: AccountAddress,
+    num_transfers: usize,
+}
+
+/// Stresses first-time coin store registration. Every sender transfers to freshly generated
+/// receivers, which have neither an account nor a coin store yet. The first transfer to a
+/// receiver goes through `aptos_account::transfer`, which creates the account and registers its
+/// coin store on the fly. Once registered, the receiver gets plain `coin::transfer`s, until it
+/// received `transfers_per_receiver` transfers and is replaced by a new one.
+pub struct CoinStoreRegistrationGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    transfers_per_receiver: usize,
+    // current receiver of each sender, registered once it received a transfer
+    receivers: HashMap<AccountAddress, Receiver>,
+    expiration_jitter_secs: RangeInclusive<u64>,
+}
+
+impl CoinStoreRegistrationGenerator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        transfers_per_receiver: usize,
+    ) -> Self {
+        assert!(transfers_per_receiver > 0);
+        Self {
+            rng,
+            txn_factory,
+            transfers_per_receiver,
+            receivers: HashMap::new(),
+            expiration_jitter_secs: 0..=0,
+        }
+    }
+
+    /// Delays the expiration of each generated transaction by a random number of seconds in
+    /// `expiration_jitter_secs`, so that a burst of transactions doesn't expire all at once.
+    pub fn set_expiration_jitter(&mut self, expiration_jitter_secs: RangeInclusive<u64>) {
+        assert!(!expiration_jitter_secs.is_empty());
+        self.expiration_jitter_secs = expiration_jitter_secs;
+    }
+
+    /// Returns whether `address` has been sent a registering transfer, and is still in use as
+    /// a receiver.
+    pub fn is_registered(&self, address: &AccountAddress) -> bool {
+        self.receivers
+            .values()
+            .any(|receiver| receiver.address == *address && receiver.num_transfers > 0)
+    }
+
+    fn gen_single_txn(&mut self, from: &mut LocalAccount) -> SignedTransaction {
+        let needs_new_receiver = self
+            .receivers
+            .get(&from.address())
+            .map_or(true, |receiver| receiver.num_transfers >= self.transfers_per_receiver);
+        if needs_new_receiver {
+            self.receivers.insert(from.address(), Receiver {
+                address: LocalAccount::generate(&mut self.rng).address(),
+                num_transfers: 0,
+            });
+        }
+        let receiver = self.receivers.get_mut(&from.address()).unwrap();
+
+        let payload = if receiver.num_transfers == 0 {
+            aptos_stdlib::aptos_account_transfer(receiver.address, SEND_AMOUNT)
+        } else {
+            aptos_stdlib::aptos_coin_transfer(receiver.address, SEND_AMOUNT)
+        };
+        receiver.num_transfers += 1;
+        let expiration_jitter_secs =
+            gen_expiration_jitter(&mut self.rng, &self.expiration_jitter_secs);
+        from.sign_with_transaction_builder(
+            self.txn_factory
+                .payload_with_expiration_jitter(payload, expiration_jitter_secs),
+        )
+    }
+}
+
+impl TransactionGenerator for CoinStoreRegistrationGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                requests.push(self.gen_single_txn(account));
+            }
+        }
+        requests
+    }
+}
+
+pub struct CoinStoreRegistrationGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    transfers_per_receiver: usize,
+    expiration_jitter_secs: RangeInclusive<u64>,
+}
+
+impl CoinStoreRegistrationGeneratorCreator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        transfers_per_receiver: usize,
+    ) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            transfers_per_receiver,
+            expiration_jitter_secs: 0..=0,
+        }
+    }
+
+    pub fn with_expiration_jitter(mut self, expiration_jitter_secs: RangeInclusive<u64>) -> Self {
+        self.expiration_jitter_secs = expiration_jitter_secs;
+        self
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for CoinStoreRegistrationGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        let mut generator = CoinStoreRegistrationGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.transfers_per_receiver,
+        );
+        generator.set_expiration_jitter(self.expiration_jitter_secs.clone());
+        Box::new(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::{
+        bcs,
+        types::{chain_id::ChainId, transaction::TransactionPayload},
+    };
+
+    fn function_and_receiver(txn: &SignedTransaction) -> (String, AccountAddress) {
+        match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => (
+                format!(
+                    "{}::{}",
+                    entry_function.module().name(),
+                    entry_function.function()
+                ),
+                bcs::from_bytes(&entry_function.args()[0]).unwrap(),
+            ),
+            _ => panic!("Unexpected payload"),
+        }
+    }
+
+    #[test]
+    fn test_first_transfer_registers_receiver() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut sender = LocalAccount::generate(&mut rng);
+        let mut generator =
+            CoinStoreRegistrationGenerator::new(rng, TransactionFactory::new(ChainId::test()), 3);
+
+        let txns = generator.generate_transactions(vec![&mut sender], 4);
+        let calls: Vec<_> = txns.iter().map(function_and_receiver).collect();
+        let (first_receiver, second_receiver) = (calls[0].1, calls[3].1);
+        assert_ne!(first_receiver, second_receiver);
+        assert_eq!(calls, vec![
+            ("aptos_account::transfer".to_string(), first_receiver),
+            ("coin::transfer".to_string(), first_receiver),
+            ("coin::transfer".to_string(), first_receiver),
+            ("aptos_account::transfer".to_string(), second_receiver),
+        ]);
+        assert!(!generator.is_registered(&first_receiver));
+        assert!(generator.is_registered(&second_receiver));
+
+        let txns = generator.generate_transactions(vec![&mut sender], 1);
+        let call = function_and_receiver(&txns[0]);
+        assert_eq!(call, ("coin::transfer".to_string(), second_receiver));
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{gen_expiration_jitter, TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::{
+    bcs,
+    move_types::{account_address::AccountAddress, ident_str, language_storage::ModuleId},
+    transaction_builder::TransactionFactory,
+    types::{
+        transaction::{EntryFunction, SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::ops::RangeInclusive;
+
+/// Direction of the swaps against the pool.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SwapDirection {
+    XToY,
+    YToX,
+    /// Each swap picks one of the two directions at random.
+    Both,
+}
+
+/// Swaps against a single liquidity pool, so that every transaction writes the reserves of the
+/// pool and contends with all the others, like the popular pools of an AMM.
+/// Transactions call `<pool_address>::amm::swap(amount_in: u64, min_amount_out: u64,
+/// x_to_y: bool)`, with `amount_in` in `[min_amount, max_amount]` and no slippage protection.
+/// The pool is a resource of `pool_address`, published there beforehand along with the module.
+pub struct DexSwapGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    pool_address: AccountAddress,
+    min_amount: u64,
+    max_amount: u64,
+    direction: SwapDirection,
+    expiration_jitter_secs: RangeInclusive<u64>,
+}
+
+impl DexSwapGenerator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        pool_address: AccountAddress,
+        min_amount: u64,
+        max_amount: u64,
+        direction: SwapDirection,
+    ) -> Self {
+        assert!(min_amount <= max_amount);
+        Self {
+            rng,
+            txn_factory,
+            pool_address,
+            min_amount,
+            max_amount,
+            direction,
+            expiration_jitter_secs: 0..=0,
+        }
+    }
+
+    /// Delays the expiration of each generated transaction by a random number of seconds in
+    /// `expiration_jitter_secs`, so that a burst of transactions doesn't expire all at once.
+    pub fn set_expiration_jitter(&mut self, expiration_jitter_secs: RangeInclusive<u64>) {
+        assert!(!expiration_jitter_secs.is_empty());
+        self.expiration_jitter_secs = expiration_jitter_secs;
+    }
+
+    fn swap_payload(&mut self) -> TransactionPayload {
+        let amount_in = self.rng.gen_range(self.min_amount, self.max_amount + 1);
+        let x_to_y = match self.direction {
+            SwapDirection::XToY => true,
+            SwapDirection::YToX => false,
+            SwapDirection::Both => self.rng.gen(),
+        };
+        TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(self.pool_address, ident_str!("amm").to_owned()),
+            ident_str!("swap").to_owned(),
+            vec![],
+            vec![
+                bcs::to_bytes(&amount_in).unwrap(),
+                bcs::to_bytes(&0u64).unwrap(),
+                bcs::to_bytes(&x_to_y).unwrap(),
+            ],
+        ))
+    }
+}
+
+impl TransactionGenerator for DexSwapGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let payload = self.swap_payload();
+                let expiration_jitter_secs =
+                    gen_expiration_jitter(&mut self.rng, &self.expiration_jitter_secs);
+                let builder = self
+                    .txn_factory
+                    .payload_with_expiration_jitter(payload, expiration_jitter_secs);
+                requests.push(account.sign_with_transaction_builder(builder));
+            }
+        }
+        requests
+    }
+}
+
+pub struct DexSwapGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    pool_address: AccountAddress,
+    min_amount: u64,
+    max_amount: u64,
+    direction: SwapDirection,
+    expiration_jitter_secs: RangeInclusive<u64>,
+}
+
+impl DexSwapGeneratorCreator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        pool_address: AccountAddress,
+        min_amount: u64,
+        max_amount: u64,
+        direction: SwapDirection,
+    ) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            pool_address,
+            min_amount,
+            max_amount,
+            direction,
+            expiration_jitter_secs: 0..=0,
+        }
+    }
+
+    pub fn with_expiration_jitter(mut self, expiration_jitter_secs: RangeInclusive<u64>) -> Self {
+        self.expiration_jitter_secs = expiration_jitter_secs;
+        self
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for DexSwapGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        let mut generator = DexSwapGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.pool_address,
+            self.min_amount,
+            self.max_amount,
+            self.direction,
+        );
+        generator.set_expiration_jitter(self.expiration_jitter_secs.clone());
+        Box::new(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::chain_id::ChainId;
+
+    fn generate(direction: SwapDirection) -> (AccountAddress, Vec<SignedTransaction>) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..5).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let pool_address = LocalAccount::generate(&mut rng).address();
+        let mut generator = DexSwapGenerator::new(
+            rng,
+            TransactionFactory::new(ChainId::test()),
+            pool_address,
+            10,
+            20,
+            direction,
+        );
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 20);
+        assert_eq!(txns.len(), 100);
+        (pool_address, txns)
+    }
+
+    /// Returns the (amount in, x to y) of a swap.
+    fn swap_args(pool_address: AccountAddress, txn: &SignedTransaction) -> (u64, bool) {
+        let entry_function = match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => entry_function,
+            _ => panic!("Unexpected payload"),
+        };
+        // All swaps go through the module of the shared pool.
+        assert_eq!(entry_function.module().address(), &pool_address);
+        assert_eq!(entry_function.module().name().as_str(), "amm");
+        assert_eq!(entry_function.function().as_str(), "swap");
+        let args = entry_function.args();
+        assert_eq!(bcs::from_bytes::<u64>(&args[1]).unwrap(), 0);
+        (
+            bcs::from_bytes(&args[0]).unwrap(),
+            bcs::from_bytes(&args[2]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_swaps_target_shared_pool() {
+        let (pool_address, txns) = generate(SwapDirection::Both);
+        let swaps: Vec<_> = txns
+            .iter()
+            .map(|txn| swap_args(pool_address, txn))
+            .collect();
+        assert!(swaps.iter().all(|(amount, _)| (10..=20).contains(amount)));
+        assert!(swaps.iter().any(|(_, x_to_y)| *x_to_y));
+        assert!(swaps.iter().any(|(_, x_to_y)| !*x_to_y));
+
+        let (pool_address, txns) = generate(SwapDirection::XToY);
+        assert!(txns.iter().all(|txn| swap_args(pool_address, txn).1));
+        let (pool_address, txns) = generate(SwapDirection::YToX);
+        assert!(txns.iter().all(|txn| !swap_args(pool_address, txn).1));
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator, SEND_AMOUNT};
+use aptos::common::types::RotationProofChallenge;
+use aptos_sdk::{
+    bcs,
+    crypto::{
+        ed25519::Ed25519PrivateKey,
+        multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
+        SigningKey, Uniform,
+    },
+    move_types::{account_address::AccountAddress, language_storage::CORE_CODE_ADDRESS},
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+
+/// Number of keys, and signatures required, of the multi-ed25519 key of every account.
+const MULTI_ED25519_NUM_KEYS: usize = 3;
+const MULTI_ED25519_THRESHOLD: u8 = 2;
+
+/// Schemes of the public keys passed to `0x1::account::rotate_authentication_key`.
+const ED25519_SCHEME: u8 = 0;
+const MULTI_ED25519_SCHEME: u8 = 1;
+
+/// Key type a transaction is signed with.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyType {
+    Ed25519,
+    MultiEd25519,
+}
+
+/// Generates transfers of every account to itself, to load the verification of both ed25519
+/// and multi-ed25519 authenticators.
+/// The first time an account is used, it is picked as a multi-ed25519 account with a
+/// `multi_ed25519_percentage` chance. The first transaction of a multi-ed25519 account rotates
+/// its authentication key to a new 2-of-3 multi-ed25519 key, and all its following transactions
+/// are signed with that key. The other accounts keep signing with their ed25519 key.
+/// Rotated accounts can't be used with their ed25519 key anymore, by this or any other generator.
+pub struct KeyTypeMixGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    multi_ed25519_percentage: usize,
+    multi_ed25519_keys: HashMap<AccountAddress, MultiEd25519PrivateKey>,
+    ed25519_accounts: HashSet<AccountAddress>,
+}
+
+impl KeyTypeMixGenerator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        multi_ed25519_percentage: usize,
+    ) -> Self {
+        assert!(multi_ed25519_percentage <= 100);
+        Self {
+            rng,
+            txn_factory,
+            multi_ed25519_percentage,
+            multi_ed25519_keys: HashMap::new(),
+            ed25519_accounts: HashSet::new(),
+        }
+    }
+
+    /// Returns the public multi-ed25519 key of `address`, if its authentication key was rotated
+    /// to it.
+    pub fn multi_ed25519_public_key(
+        &self,
+        address: &AccountAddress,
+    ) -> Option<MultiEd25519PublicKey> {
+        self.multi_ed25519_keys
+            .get(address)
+            .map(MultiEd25519PublicKey::from)
+    }
+
+    /// Generates a multi-ed25519 key for `account` and returns the transaction, signed with its
+    /// ed25519 key, that rotates its authentication key to it.
+    fn rotate_to_multi_ed25519(&mut self, account: &mut LocalAccount) -> SignedTransaction {
+        let keys = (0..MULTI_ED25519_NUM_KEYS)
+            .map(|_| Ed25519PrivateKey::generate(&mut self.rng))
+            .collect();
+        let private_key = MultiEd25519PrivateKey::new(keys, MULTI_ED25519_THRESHOLD)
+            .expect("Multi-ed25519 key is valid");
+        let public_key = MultiEd25519PublicKey::from(&private_key);
+
+        let rotation_proof = RotationProofChallenge {
+            account_address: CORE_CODE_ADDRESS,
+            module_name: "account".to_string(),
+            struct_name: "RotationProofChallenge".to_string(),
+            sequence_number: account.sequence_number(),
+            originator: account.address(),
+            current_auth_key: account.authentication_key().derived_address(),
+            new_public_key: public_key.to_bytes(),
+        };
+        let rotation_msg = bcs::to_bytes(&rotation_proof).expect("Serializing can't fail");
+        let signed_by_current_key = account.private_key().sign_arbitrary_message(&rotation_msg);
+        let signed_by_new_key = private_key.sign_arbitrary_message(&rotation_msg);
+
+        let payload = aptos_stdlib::account_rotate_authentication_key(
+            ED25519_SCHEME,
+            account.public_key().to_bytes().to_vec(),
+            MULTI_ED25519_SCHEME,
+            public_key.to_bytes(),
+            signed_by_current_key.to_bytes().to_vec(),
+            signed_by_new_key.to_bytes(),
+        );
+        self.multi_ed25519_keys
+            .insert(account.address(), private_key);
+        account.sign_with_transaction_builder(self.txn_factory.payload(payload))
+    }
+
+    fn sign_multi_ed25519(&self, account: &mut LocalAccount) -> SignedTransaction {
+        let raw_txn = self
+            .txn_factory
+            .payload(aptos_stdlib::aptos_coin_transfer(account.address(), SEND_AMOUNT))
+            .sender(account.address())
+            .sequence_number(account.sequence_number())
+            .build();
+        *account.sequence_number_mut() += 1;
+
+        let private_key = &self.multi_ed25519_keys[&account.address()];
+        let signature = private_key
+            .sign(&raw_txn)
+            .expect("Signing a txn can't fail");
+        SignedTransaction::new_multisig(
+            raw_txn,
+            MultiEd25519PublicKey::from(private_key),
+            signature,
+        )
+    }
+}
+
+impl TransactionGenerator for KeyTypeMixGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let address = account.address();
+                let request = if self.multi_ed25519_keys.contains_key(&address) {
+                    self.sign_multi_ed25519(account)
+                } else if !self.ed25519_accounts.contains(&address)
+                    && self.rng.gen_range(0, 100) < self.multi_ed25519_percentage
+                {
+                    self.rotate_to_multi_ed25519(account)
+                } else {
+                    self.ed25519_accounts.insert(address);
+                    let payload = aptos_stdlib::aptos_coin_transfer(address, SEND_AMOUNT);
+                    account.sign_with_transaction_builder(self.txn_factory.payload(payload))
+                };
+                requests.push(request);
+            }
+        }
+        requests
+    }
+}
+
+pub struct KeyTypeMixGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    multi_ed25519_percentage: usize,
+}
+
+impl KeyTypeMixGeneratorCreator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        multi_ed25519_percentage: usize,
+    ) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            multi_ed25519_percentage,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for KeyTypeMixGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(KeyTypeMixGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.multi_ed25519_percentage,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::{
+        chain_id::ChainId,
+        transaction::{authenticator::TransactionAuthenticator, TransactionPayload},
+    };
+
+    fn key_type(txn: &SignedTransaction) -> KeyType {
+        match txn.authenticator() {
+            TransactionAuthenticator::Ed25519 { .. } => KeyType::Ed25519,
+            TransactionAuthenticator::MultiEd25519 { .. } => KeyType::MultiEd25519,
+            _ => panic!("Unexpected authenticator"),
+        }
+    }
+
+    fn is_rotation(txn: &SignedTransaction) -> bool {
+        match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => {
+                entry_function.function().as_str() == "rotate_authentication_key"
+            },
+            _ => false,
+        }
+    }
+
+    /// Generates two batches of 10 transactions per account for 100 accounts, and checks that
+    /// every account either rotates to a multi-ed25519 key in its first transaction and signs all
+    /// the following ones with it, or signs all its transactions with its ed25519 key.
+    /// Returns the number of multi-ed25519 accounts.
+    fn generate(multi_ed25519_percentage: usize) -> usize {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..100).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator = KeyTypeMixGenerator::new(
+            rng,
+            TransactionFactory::new(ChainId::test()),
+            multi_ed25519_percentage,
+        );
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+        let second_batch = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+        assert!(txns.iter().all(|txn| txn.signature_is_valid()));
+        assert!(second_batch.iter().all(|txn| txn.signature_is_valid()));
+
+        let mut num_multi_ed25519 = 0;
+        for ((account, first), second) in accounts
+            .iter()
+            .zip(txns.chunks(10))
+            .zip(second_batch.chunks(10))
+        {
+            let account_txns: Vec<_> = first.iter().chain(second).collect();
+            let public_key = generator.multi_ed25519_public_key(&account.address());
+            if is_rotation(account_txns[0]) {
+                num_multi_ed25519 += 1;
+                assert_eq!(key_type(account_txns[0]), KeyType::Ed25519);
+                assert!(public_key.is_some());
+                for txn in &account_txns[1..] {
+                    assert!(!is_rotation(txn));
+                    assert_eq!(key_type(txn), KeyType::MultiEd25519);
+                }
+            } else {
+                assert!(public_key.is_none());
+                for txn in &account_txns {
+                    assert!(!is_rotation(txn));
+                    assert_eq!(key_type(txn), KeyType::Ed25519);
+                }
+            }
+
+            // Whatever the key, the sequence numbers of an account are consecutive.
+            let sequence_numbers: Vec<_> = account_txns
+                .iter()
+                .map(|txn| txn.sequence_number())
+                .collect();
+            assert_eq!(sequence_numbers, (0..20).collect::<Vec<_>>());
+            assert_eq!(account.sequence_number(), 20);
+        }
+        num_multi_ed25519
+    }
+
+    #[test]
+    fn test_key_type_proportions() {
+        assert!((15..=45).contains(&generate(30)));
+        assert_eq!(generate(0), 0);
+        assert_eq!(generate(100), 100);
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    call_custom_modules::publish_packages,
+    publishing::{module_simple::EntryPoints, publish_util::Package},
+    TransactionExecutor,
+};
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_logger::warn;
+use aptos_sdk::{
+    transaction_builder::TransactionFactory,
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::sync::Arc;
+
+/// Max size of a transaction in the default gas schedule. Larger ones are rejected.
+pub const MAX_TRANSACTION_SIZE_IN_BYTES: usize = 64 * 1024;
+
+/// Stresses the network and execution with big transactions: each one calls
+/// `bytes_make_or_change` of the published packages with a `vector<u8>` of `payload_size` bytes.
+pub struct LargePayloadGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    packages: Arc<Vec<Package>>,
+    payload_size: usize,
+}
+
+impl LargePayloadGenerator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        packages: Arc<Vec<Package>>,
+        payload_size: usize,
+    ) -> Self {
+        if payload_size > MAX_TRANSACTION_SIZE_IN_BYTES {
+            warn!(
+                "Payload size {} exceeds the max transaction size {}, txns will be rejected",
+                payload_size, MAX_TRANSACTION_SIZE_IN_BYTES
+            );
+        }
+        Self {
+            rng,
+            txn_factory,
+            packages,
+            payload_size,
+        }
+    }
+}
+
+impl TransactionGenerator for LargePayloadGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let package = self.packages.choose(&mut self.rng).unwrap();
+                requests.push(package.use_specific_transaction(
+                    EntryPoints::BytesMakeOrChange {
+                        data_length: Some(self.payload_size),
+                    },
+                    account,
+                    &self.txn_factory,
+                    Some(&mut self.rng),
+                    None,
+                ));
+            }
+        }
+        requests
+    }
+}
+
+pub struct LargePayloadGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    packages: Arc<Vec<Package>>,
+    payload_size: usize,
+}
+
+impl LargePayloadGeneratorCreator {
+    pub async fn new(
+        mut rng: StdRng,
+        txn_factory: TransactionFactory,
+        init_txn_factory: TransactionFactory,
+        accounts: &mut [LocalAccount],
+        txn_executor: &dyn TransactionExecutor,
+        num_modules: usize,
+        payload_size: usize,
+    ) -> Self {
+        let packages = publish_packages(
+            &mut rng,
+            &init_txn_factory,
+            accounts,
+            txn_executor,
+            num_modules,
+        )
+        .await;
+        Self {
+            rng,
+            txn_factory,
+            packages: Arc::new(packages),
+            payload_size,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for LargePayloadGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(LargePayloadGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.packages.clone(),
+            self.payload_size,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::chain_id::ChainId;
+
+    #[test]
+    fn test_transaction_size_scales_with_payload_size() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut account = LocalAccount::generate(&mut rng);
+        let packages = Arc::new(vec![Package::simple().update(account.address(), 0)]);
+        let mut txn_size = |payload_size| {
+            let mut generator = LargePayloadGenerator::new(
+                StdRng::seed_from_u64(1),
+                TransactionFactory::new(ChainId::test()),
+                packages.clone(),
+                payload_size,
+            );
+            let txns = generator.generate_transactions(vec![&mut account], 1);
+            txns[0].raw_txn_bytes_len()
+        };
+
+        let small = txn_size(1_000);
+        let medium = txn_size(10_000);
+        let large = txn_size(60_000);
+        assert!(large < MAX_TRANSACTION_SIZE_IN_BYTES);
+        // Only the payload grows, up to a few more bytes for its length prefix.
+        assert!((9_000..9_010).contains(&(medium - small)));
+        assert!((59_000..59_010).contains(&(large - small)));
+    }
+}
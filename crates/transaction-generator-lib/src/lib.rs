@@ -3,7 +3,7 @@
 
 #![forbid(unsafe_code)]
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use aptos_infallible::RwLock;
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
@@ -11,40 +11,91 @@ use aptos_sdk::{
     types::{transaction::SignedTransaction, LocalAccount},
 };
 use async_trait::async_trait;
-use std::sync::{atomic::AtomicUsize, Arc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    fmt,
+    ops::RangeInclusive,
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
 
+pub mod aborting_transfer_generator;
 pub mod account_generator;
 pub mod accounts_pool_wrapper;
+pub mod backpressure_generator;
+pub mod bursty_generator;
 pub mod call_custom_modules;
+pub mod coin_store_registration_generator;
+pub mod dex_swap_generator;
+pub mod key_type_mix_generator;
+pub mod large_payload_generator;
+pub mod module_publish_generator;
 pub mod nft_mint_and_transfer;
 pub mod p2p_transaction_generator;
+pub mod phased_generator;
 pub mod publish_modules;
 mod publishing;
+pub mod random_expiration_transfer_generator;
+pub mod resource_account_generator;
+pub mod size_sweep_generator;
+pub mod staking_lifecycle_generator;
+pub mod table_and_resource_group_generator;
+#[cfg(test)]
+mod test_utils;
 pub mod transaction_mix_generator;
+pub mod wallet_flow_generator;
 use self::{
+    aborting_transfer_generator::{AbortKind, AbortingTransferGeneratorCreator},
     account_generator::AccountGeneratorCreator, call_custom_modules::CallCustomModulesCreator,
-    nft_mint_and_transfer::NFTMintAndTransferGeneratorCreator,
+    coin_store_registration_generator::CoinStoreRegistrationGeneratorCreator,
+    dex_swap_generator::{DexSwapGeneratorCreator, SwapDirection},
+    key_type_mix_generator::KeyTypeMixGeneratorCreator,
+    large_payload_generator::LargePayloadGeneratorCreator,
+    module_publish_generator::ModulePublishGeneratorCreator,
+    nft_mint_and_transfer::{AmountDistribution, NFTMintAndTransferGeneratorCreator},
     p2p_transaction_generator::P2PTransactionGeneratorCreator,
     publish_modules::PublishPackageCreator,
-    transaction_mix_generator::PhasedTxnMixGeneratorCreator,
+    random_expiration_transfer_generator::RandomExpirationTransferGeneratorCreator,
+    resource_account_generator::ResourceAccountGeneratorCreator,
+    size_sweep_generator::{size_buckets, SizeSweepGeneratorCreator},
+    staking_lifecycle_generator::StakingLifecycleGeneratorCreator,
+    table_and_resource_group_generator::TableAndResourceGroupGeneratorCreator,
+    transaction_mix_generator::{MixedGeneratorCreator, PhasedTxnMixGeneratorCreator},
+    wallet_flow_generator::WalletFlowGeneratorCreator,
 };
-use crate::accounts_pool_wrapper::AccountsPoolWrapperCreator;
+use crate::{accounts_pool_wrapper::AccountsPoolWrapperCreator, publishing::raw_module_data};
 pub use publishing::module_simple::EntryPoints;
 
 pub const SEND_AMOUNT: u64 = 1;
 
+/// Returns a random number of seconds in `expiration_jitter_secs`, for the generators to delay
+/// the expiration of a transaction by, with `TransactionFactory::payload_with_expiration_jitter`.
+pub(crate) fn gen_expiration_jitter(
+    rng: &mut StdRng,
+    expiration_jitter_secs: &RangeInclusive<u64>,
+) -> u64 {
+    rng.gen_range(
+        *expiration_jitter_secs.start(),
+        expiration_jitter_secs.end() + 1,
+    )
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum TransactionType {
     CoinTransfer {
         invalid_transaction_ratio: usize,
         sender_use_account_pool: bool,
+        max_expiration_jitter_secs: u64,
     },
     AccountGeneration {
         add_created_accounts_to_pool: bool,
         max_account_working_set: usize,
         creation_balance: u64,
     },
-    NftMintAndTransfer,
+    NftMintAndTransfer {
+        num_collections: usize,
+        tokens_per_collection: usize,
+    },
     PublishPackage {
         use_account_pool: bool,
     },
@@ -53,6 +104,55 @@ pub enum TransactionType {
         num_modules: usize,
         use_account_pool: bool,
     },
+    WalletFlow {
+        funding_amount: u64,
+    },
+    TableAndResourceGroup {
+        num_keys_per_txn: usize,
+    },
+    CoinStoreRegistration {
+        transfers_per_receiver: usize,
+        max_expiration_jitter_secs: u64,
+    },
+    AbortingTransfer {
+        abort_percentage: usize,
+        abort_kind: AbortKind,
+        max_expiration_jitter_secs: u64,
+    },
+    RandomExpirationTransfer {
+        max_expiration_secs: u64,
+    },
+    StakingLifecycle {
+        pool_address: AccountAddress,
+        stake_amount: u64,
+    },
+    KeyTypeMix {
+        multi_ed25519_percentage: usize,
+    },
+    DexSwap {
+        pool_address: AccountAddress,
+        min_amount: u64,
+        max_amount: u64,
+        direction: SwapDirection,
+        max_expiration_jitter_secs: u64,
+    },
+    ResourceAccountCreation {
+        seed_prefix: u64,
+    },
+    ModulePublish {
+        mutate: bool,
+    },
+    SizeSweep {
+        min_size: usize,
+        max_size: usize,
+        num_buckets: usize,
+        txns_per_bucket: usize,
+        num_modules: usize,
+    },
+    LargePayload {
+        payload_size: usize,
+        num_modules: usize,
+    },
 }
 
 impl TransactionType {
@@ -60,6 +160,7 @@ impl TransactionType {
         Self::CoinTransfer {
             invalid_transaction_ratio: 0,
             sender_use_account_pool: false,
+            max_expiration_jitter_secs: 0,
         }
     }
 
@@ -71,6 +172,13 @@ impl TransactionType {
         }
     }
 
+    pub fn default_nft_mint_and_transfer() -> Self {
+        Self::NftMintAndTransfer {
+            num_collections: 1,
+            tokens_per_collection: 1,
+        }
+    }
+
     pub fn default_call_custom_module() -> Self {
         Self::CallCustomModules {
             entry_point: EntryPoints::Nop,
@@ -86,6 +194,16 @@ impl TransactionType {
             use_account_pool: false,
         }
     }
+
+    /// Upper bound of the coins a transaction of this type moves out of its sender, on top of
+    /// `SEND_AMOUNT` and gas, for the emitter to fund the accounts with.
+    pub fn extra_coins_per_txn(&self) -> u64 {
+        match self {
+            TransactionType::WalletFlow { funding_amount } => *funding_amount,
+            TransactionType::StakingLifecycle { stake_amount, .. } => *stake_amount,
+            _ => 0,
+        }
+    }
 }
 
 impl Default for TransactionType {
@@ -107,6 +225,18 @@ pub trait TransactionGeneratorCreator: Sync + Send {
     async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator>;
 }
 
+/// Context of the errors of `TransactionExecutor::execute_transactions` when transactions could
+/// not be submitted at all, as opposed to submitted transactions that failed or could not be
+/// waited on.
+#[derive(Debug)]
+pub struct SubmissionError;
+
+impl fmt::Display for SubmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed submitting transactions")
+    }
+}
+
 #[async_trait]
 pub trait TransactionExecutor: Sync + Send {
     async fn get_account_balance(&self, account_address: AccountAddress) -> Result<u64>;
@@ -120,15 +250,54 @@ pub trait TransactionExecutor: Sync + Send {
         txns: &[SignedTransaction],
         failure_counter: &[AtomicUsize],
     ) -> Result<()>;
+
+    /// Executes `txns`, executing them again up to `max_retries` times while they fail with a
+    /// `SubmissionError`, after `backoff` for the first retry and twice as long as the previous
+    /// one for each next retry. The whole batch goes through a single call each time, so that
+    /// the transactions of a sender are submitted together. Submitting the accepted ones again
+    /// is harmless, as they keep the same hash. Other failures are returned right away.
+    async fn execute_transactions_with_retry(
+        &self,
+        txns: &[SignedTransaction],
+        max_retries: usize,
+        mut backoff: Duration,
+    ) -> Result<()> {
+        let mut retry = 0;
+        loop {
+            match self.execute_transactions(txns).await {
+                Err(err)
+                    if retry < max_retries && err.downcast_ref::<SubmissionError>().is_some() =>
+                {
+                    retry += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                },
+                result => {
+                    return result.with_context(|| {
+                        format!(
+                            "Failed executing {} txns after {} retries",
+                            txns.len(),
+                            retry
+                        )
+                    })
+                },
+            }
+        }
+    }
 }
 
+/// Creates the generators of `transaction_mix_per_phase`. Each batch is generated by a single
+/// transaction type of the current phase, picked as of the weights, unless `mix_within_batch`
+/// is set, in which case the transaction types are picked for each transaction.
 pub async fn create_txn_generator_creator(
     transaction_mix_per_phase: &[Vec<(TransactionType, usize)>],
+    mix_within_batch: bool,
     num_workers: usize,
     all_accounts: &mut [LocalAccount],
     txn_executor: &dyn TransactionExecutor,
     txn_factory: &TransactionFactory,
     init_txn_factory: &TransactionFactory,
+    mut rng: StdRng,
     cur_phase: Arc<AtomicUsize>,
 ) -> Box<dyn TransactionGeneratorCreator> {
     let all_addresses = Arc::new(RwLock::new(
@@ -161,13 +330,18 @@ pub async fn create_txn_generator_creator(
                 TransactionType::CoinTransfer {
                     invalid_transaction_ratio,
                     sender_use_account_pool,
+                    max_expiration_jitter_secs,
                 } => wrap_accounts_pool(
-                    Box::new(P2PTransactionGeneratorCreator::new(
-                        txn_factory.clone(),
-                        SEND_AMOUNT,
-                        all_addresses.clone(),
-                        *invalid_transaction_ratio,
-                    )),
+                    Box::new(
+                        P2PTransactionGeneratorCreator::new(
+                            StdRng::from_rng(&mut rng).unwrap(),
+                            txn_factory.clone(),
+                            SEND_AMOUNT,
+                            all_addresses.clone(),
+                            *invalid_transaction_ratio,
+                        )
+                        .with_expiration_jitter(0..=*max_expiration_jitter_secs),
+                    ),
                     *sender_use_account_pool,
                     accounts_pool.clone(),
                 ),
@@ -176,6 +350,7 @@ pub async fn create_txn_generator_creator(
                     max_account_working_set,
                     creation_balance,
                 } => Box::new(AccountGeneratorCreator::new(
+                    StdRng::from_rng(&mut rng).unwrap(),
                     txn_factory.clone(),
                     all_addresses.clone(),
                     accounts_pool.clone(),
@@ -183,18 +358,28 @@ pub async fn create_txn_generator_creator(
                     *max_account_working_set,
                     *creation_balance,
                 )),
-                TransactionType::NftMintAndTransfer => Box::new(
+                TransactionType::NftMintAndTransfer {
+                    num_collections,
+                    tokens_per_collection,
+                } => Box::new(
                     NFTMintAndTransferGeneratorCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
                         txn_factory.clone(),
                         init_txn_factory.clone(),
                         all_accounts.get_mut(0).unwrap(),
                         txn_executor,
                         num_workers,
+                        AmountDistribution::default(),
+                        *num_collections,
+                        *tokens_per_collection,
                     )
                     .await,
                 ),
                 TransactionType::PublishPackage { use_account_pool } => wrap_accounts_pool(
-                    Box::new(PublishPackageCreator::new(txn_factory.clone())),
+                    Box::new(PublishPackageCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
+                        txn_factory.clone(),
+                    )),
                     *use_account_pool,
                     accounts_pool.clone(),
                 ),
@@ -205,6 +390,7 @@ pub async fn create_txn_generator_creator(
                 } => wrap_accounts_pool(
                     Box::new(
                         CallCustomModulesCreator::new(
+                            StdRng::from_rng(&mut rng).unwrap(),
                             txn_factory.clone(),
                             init_txn_factory.clone(),
                             all_accounts,
@@ -217,14 +403,218 @@ pub async fn create_txn_generator_creator(
                     *use_account_pool,
                     accounts_pool.clone(),
                 ),
+                TransactionType::WalletFlow { funding_amount } => {
+                    Box::new(WalletFlowGeneratorCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
+                        txn_factory.clone(),
+                        *funding_amount,
+                    ))
+                },
+                TransactionType::TableAndResourceGroup { num_keys_per_txn } => {
+                    Box::new(TableAndResourceGroupGeneratorCreator::new(
+                        txn_factory.clone(),
+                        *num_keys_per_txn,
+                    ))
+                },
+                TransactionType::CoinStoreRegistration {
+                    transfers_per_receiver,
+                    max_expiration_jitter_secs,
+                } => Box::new(
+                    CoinStoreRegistrationGeneratorCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
+                        txn_factory.clone(),
+                        *transfers_per_receiver,
+                    )
+                    .with_expiration_jitter(0..=*max_expiration_jitter_secs),
+                ),
+                TransactionType::AbortingTransfer {
+                    abort_percentage,
+                    abort_kind,
+                    max_expiration_jitter_secs,
+                } => Box::new(
+                    AbortingTransferGeneratorCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
+                        txn_factory.clone(),
+                        *abort_percentage,
+                        *abort_kind,
+                    )
+                    .with_expiration_jitter(0..=*max_expiration_jitter_secs),
+                ),
+                TransactionType::RandomExpirationTransfer {
+                    max_expiration_secs,
+                } => Box::new(RandomExpirationTransferGeneratorCreator::new(
+                    StdRng::from_rng(&mut rng).unwrap(),
+                    txn_factory.clone(),
+                    *max_expiration_secs,
+                )),
+                TransactionType::StakingLifecycle {
+                    pool_address,
+                    stake_amount,
+                } => Box::new(StakingLifecycleGeneratorCreator::new(
+                    txn_factory.clone(),
+                    *pool_address,
+                    *stake_amount,
+                )),
+                TransactionType::KeyTypeMix {
+                    multi_ed25519_percentage,
+                } => Box::new(KeyTypeMixGeneratorCreator::new(
+                    StdRng::from_rng(&mut rng).unwrap(),
+                    txn_factory.clone(),
+                    *multi_ed25519_percentage,
+                )),
+                TransactionType::DexSwap {
+                    pool_address,
+                    min_amount,
+                    max_amount,
+                    direction,
+                    max_expiration_jitter_secs,
+                } => Box::new(
+                    DexSwapGeneratorCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
+                        txn_factory.clone(),
+                        *pool_address,
+                        *min_amount,
+                        *max_amount,
+                        *direction,
+                    )
+                    .with_expiration_jitter(0..=*max_expiration_jitter_secs),
+                ),
+                TransactionType::ResourceAccountCreation { seed_prefix } => Box::new(
+                    ResourceAccountGeneratorCreator::new(txn_factory.clone(), *seed_prefix),
+                ),
+                TransactionType::ModulePublish { mutate } => {
+                    Box::new(ModulePublishGeneratorCreator::new(
+                        txn_factory.clone(),
+                        raw_module_data::PACKAGE_METADATA_SIMPLE.clone(),
+                        &[raw_module_data::MODULE_SIMPLE.clone()],
+                        *mutate,
+                    ))
+                },
+                TransactionType::SizeSweep {
+                    min_size,
+                    max_size,
+                    num_buckets,
+                    txns_per_bucket,
+                    num_modules,
+                } => Box::new(
+                    SizeSweepGeneratorCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
+                        txn_factory.clone(),
+                        init_txn_factory.clone(),
+                        all_accounts,
+                        txn_executor,
+                        *num_modules,
+                        size_buckets(*min_size, *max_size, *num_buckets),
+                        *txns_per_bucket,
+                    )
+                    .await,
+                ),
+                TransactionType::LargePayload {
+                    payload_size,
+                    num_modules,
+                } => Box::new(
+                    LargePayloadGeneratorCreator::new(
+                        StdRng::from_rng(&mut rng).unwrap(),
+                        txn_factory.clone(),
+                        init_txn_factory.clone(),
+                        all_accounts,
+                        txn_executor,
+                        *num_modules,
+                        *payload_size,
+                    )
+                    .await,
+                ),
             };
             txn_generator_creator_mix.push((txn_generator_creator, *weight));
         }
+        if mix_within_batch {
+            let mixed_generator_creator: Box<dyn TransactionGeneratorCreator> =
+                Box::new(MixedGeneratorCreator::new(
+                    StdRng::from_rng(&mut rng).unwrap(),
+                    txn_generator_creator_mix,
+                ));
+            txn_generator_creator_mix = vec![(mixed_generator_creator, 1)];
+        }
         txn_generator_creator_mix_per_phase.push(txn_generator_creator_mix)
     }
 
     Box::new(PhasedTxnMixGeneratorCreator::new(
+        rng,
         txn_generator_creator_mix_per_phase,
         cur_phase,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockExecutor;
+    use aptos_sdk::types::chain_id::ChainId;
+    use std::sync::Mutex;
+
+    /// Fails the first `num_failures` calls with `error`, counting the calls in `attempts`.
+    fn flaky_executor(
+        attempts: &Mutex<usize>,
+        num_failures: usize,
+        error: fn() -> anyhow::Error,
+    ) -> MockExecutor<impl Fn(&[SignedTransaction]) -> Result<()> + Send + Sync + '_> {
+        MockExecutor::new(move |_txns: &[SignedTransaction]| {
+            let mut attempts = attempts.lock().unwrap();
+            *attempts += 1;
+            if *attempts <= num_failures {
+                Err(error())
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    fn submission_error() -> anyhow::Error {
+        anyhow::anyhow!("connection refused").context(SubmissionError)
+    }
+
+    fn execution_error() -> anyhow::Error {
+        anyhow::anyhow!("transaction aborted")
+    }
+
+    fn transfers(count: usize) -> Vec<SignedTransaction> {
+        let mut account = LocalAccount::generate(&mut StdRng::seed_from_u64(0));
+        let txn_factory = TransactionFactory::new(ChainId::test());
+        (0..count)
+            .map(|_| {
+                account.sign_with_transaction_builder(txn_factory.transfer(account.address(), 1))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_execute_transactions_with_retry() {
+        let txns = transfers(4);
+        let backoff = Duration::from_millis(1);
+
+        // Submission errors are retried, each time with the whole batch in a single call.
+        let attempts = Mutex::new(0);
+        flaky_executor(&attempts, 2, submission_error)
+            .execute_transactions_with_retry(&txns, 2, backoff)
+            .await
+            .unwrap();
+        assert_eq!(attempts.into_inner().unwrap(), 3);
+
+        // Until the retries run out.
+        let attempts = Mutex::new(0);
+        let err = flaky_executor(&attempts, 2, submission_error)
+            .execute_transactions_with_retry(&txns, 1, backoff)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<SubmissionError>().is_some());
+        assert_eq!(attempts.into_inner().unwrap(), 2);
+
+        // Other errors are not retried.
+        let attempts = Mutex::new(0);
+        assert!(flaky_executor(&attempts, 1, execution_error)
+            .execute_transactions_with_retry(&txns, 2, backoff)
+            .await
+            .is_err());
+        assert_eq!(attempts.into_inner().unwrap(), 1);
+    }
+}
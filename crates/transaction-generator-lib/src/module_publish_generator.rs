@@ -0,0 +1,184 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::{
+    bcs,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{account_address::AccountAddress, transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Constant, SignatureToken},
+    CompiledModule,
+};
+
+/// Publishes a package of precompiled modules from every account, through
+/// `code::publish_package_txn`. The modules are rebound to the address of the publisher. With
+/// `mutate`, a u64 constant holding a count of the publishes is appended to the constant pool of
+/// every module, so that every publish is unique and skips no work in the VM. Flipping a byte of
+/// the bytecode instead would make the modules fail to deserialize.
+pub struct ModulePublishGenerator {
+    txn_factory: TransactionFactory,
+    metadata_serialized: Vec<u8>,
+    modules: Vec<CompiledModule>,
+    mutate: bool,
+    num_published: u64,
+}
+
+impl ModulePublishGenerator {
+    pub fn new(
+        txn_factory: TransactionFactory,
+        metadata_serialized: Vec<u8>,
+        modules: Vec<CompiledModule>,
+        mutate: bool,
+    ) -> Self {
+        Self {
+            txn_factory,
+            metadata_serialized,
+            modules,
+            mutate,
+            num_published: 0,
+        }
+    }
+
+    fn publish_payload_code(&mut self, publisher: AccountAddress) -> Vec<Vec<u8>> {
+        self.num_published += 1;
+        self.modules
+            .iter()
+            .map(|module| {
+                let mut module = module.clone();
+                let self_address = module.self_handle().address;
+                module.address_identifiers[self_address.0 as usize] = publisher;
+                if self.mutate {
+                    module.constant_pool.push(Constant {
+                        type_: SignatureToken::U64,
+                        data: bcs::to_bytes(&self.num_published).expect("U64 must serialize"),
+                    });
+                }
+                let mut code = vec![];
+                module.serialize(&mut code).expect("Module must serialize");
+                code
+            })
+            .collect()
+    }
+}
+
+impl TransactionGenerator for ModulePublishGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let code = self.publish_payload_code(account.address());
+                let payload =
+                    aptos_stdlib::code_publish_package_txn(self.metadata_serialized.clone(), code);
+                let builder = self.txn_factory.payload(payload);
+                requests.push(account.sign_with_transaction_builder(builder));
+            }
+        }
+        requests
+    }
+}
+
+pub struct ModulePublishGeneratorCreator {
+    txn_factory: TransactionFactory,
+    metadata_serialized: Vec<u8>,
+    modules: Vec<CompiledModule>,
+    mutate: bool,
+}
+
+impl ModulePublishGeneratorCreator {
+    /// `module_bytes` are the serialized modules of the package described by the BCS serialized
+    /// `PackageMetadata` `metadata_serialized`.
+    pub fn new(
+        txn_factory: TransactionFactory,
+        metadata_serialized: Vec<u8>,
+        module_bytes: &[Vec<u8>],
+        mutate: bool,
+    ) -> Self {
+        let modules = module_bytes
+            .iter()
+            .map(|bytes| CompiledModule::deserialize(bytes).expect("Module must deserialize"))
+            .collect();
+        Self {
+            txn_factory,
+            metadata_serialized,
+            modules,
+            mutate,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for ModulePublishGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(ModulePublishGenerator::new(
+            self.txn_factory.clone(),
+            self.metadata_serialized.clone(),
+            self.modules.clone(),
+            self.mutate,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publishing::raw_module_data::{MODULE_SIMPLE, PACKAGE_METADATA_SIMPLE};
+    use aptos_sdk::types::{chain_id::ChainId, transaction::TransactionPayload};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn published_package(txn: &SignedTransaction) -> (Vec<u8>, Vec<Vec<u8>>) {
+        match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => {
+                assert_eq!(entry_function.module().address(), &AccountAddress::ONE);
+                assert_eq!(entry_function.module().name().as_str(), "code");
+                assert_eq!(entry_function.function().as_str(), "publish_package_txn");
+                (
+                    bcs::from_bytes(&entry_function.args()[0]).unwrap(),
+                    bcs::from_bytes(&entry_function.args()[1]).unwrap(),
+                )
+            },
+            _ => panic!("Unexpected payload"),
+        }
+    }
+
+    #[test]
+    fn test_publishes_module_payload() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..2).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let original = CompiledModule::deserialize(&MODULE_SIMPLE).unwrap();
+
+        for mutate in [false, true] {
+            let mut generator = ModulePublishGenerator::new(
+                TransactionFactory::new(ChainId::test()),
+                PACKAGE_METADATA_SIMPLE.clone(),
+                vec![original.clone()],
+                mutate,
+            );
+
+            let txns = generator.generate_transactions(accounts.iter_mut().collect(), 2);
+            assert_eq!(txns.len(), 4);
+            let mut published_code = vec![];
+            for txn in &txns {
+                let (metadata, code) = published_package(txn);
+                assert_eq!(metadata, *PACKAGE_METADATA_SIMPLE);
+                assert_eq!(code.len(), 1);
+
+                let module = CompiledModule::deserialize(&code[0]).unwrap();
+                assert_eq!(module.self_id().address(), &txn.sender());
+                assert_eq!(module.self_id().name(), original.self_id().name());
+                assert_eq!(module.function_defs, original.function_defs);
+                let num_added_constants = module.constant_pool.len() - original.constant_pool.len();
+                assert_eq!(num_added_constants, mutate as usize);
+                published_code.push(code);
+            }
+            // Without mutation, the publishes of an account are the same.
+            assert_eq!(published_code[0] == published_code[1], !mutate);
+        }
+    }
+}
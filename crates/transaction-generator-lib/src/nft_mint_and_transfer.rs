@@ -17,34 +17,107 @@ use aptos_sdk::{
     },
 };
 use async_trait::async_trait;
-use rand::{rngs::StdRng, thread_rng, SeedableRng};
-use std::collections::HashMap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 const INITIAL_NFT_BALANCE: u64 = 50_000;
+// Retries of the setup txns of the collection, e.g. on transient submission failures.
+const SETUP_MAX_RETRIES: usize = 3;
+const SETUP_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+// Max number of txns of a single account in mempool.
+const MAX_TXNS_PER_ACCOUNT: usize = 100;
+
+/// The collection name and token name of an NFT.
+pub type NftToken = (Vec<u8>, Vec<u8>);
+
+/// Returns the `tokens_per_collection` tokens of each of the `num_collections` collections,
+/// collection by collection.
+pub fn nft_tokens(num_collections: usize, tokens_per_collection: usize) -> Vec<NftToken> {
+    assert!(num_collections > 0 && tokens_per_collection > 0);
+    (0..num_collections)
+        .flat_map(|collection| {
+            (0..tokens_per_collection).map(move |token| {
+                (
+                    format!("collection name {}", collection).into_bytes(),
+                    format!("token name {}", token).into_bytes(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Distribution of the amounts of NFTs transferred by each transaction.
+#[derive(Debug, Copy, Clone)]
+pub enum AmountDistribution {
+    Constant(u64),
+    /// Uniform in `[min, max]`.
+    Uniform { min: u64, max: u64 },
+}
+
+impl Default for AmountDistribution {
+    fn default() -> Self {
+        Self::Constant(1)
+    }
+}
+
+impl AmountDistribution {
+    fn sample(&self, rng: &mut StdRng) -> u64 {
+        match *self {
+            Self::Constant(amount) => amount,
+            Self::Uniform { min, max } => rng.gen_range(min, max + 1),
+        }
+    }
+}
+
+/// Returns the amounts of the `num_transfers` transfers moving the whole INITIAL_NFT_BALANCE of
+/// an account, sampled from `distribution`. Every transfer moves at least 1 NFT, so each sample
+/// is capped to leave enough for the following transfers, and the last one moves what is left.
+pub fn transfer_amounts(
+    distribution: &AmountDistribution,
+    rng: &mut StdRng,
+    num_transfers: usize,
+) -> Vec<u64> {
+    let mut amounts = Vec::with_capacity(num_transfers);
+    let mut remaining = INITIAL_NFT_BALANCE;
+    for i in 1..num_transfers {
+        let max_amount = remaining - (num_transfers - i) as u64;
+        let amount = distribution.sample(rng).clamp(1, max_amount);
+        amounts.push(amount);
+        remaining -= amount;
+    }
+    if num_transfers > 0 {
+        amounts.push(remaining);
+    }
+    amounts
+}
 
 pub struct NFTMintAndTransfer {
+    rng: StdRng,
     txn_factory: TransactionFactory,
     creator_address: AccountAddress,
     distribution_account: LocalAccount,
-    collection_name: Vec<u8>,
-    token_name: Vec<u8>,
-    account_funded: HashMap<AccountAddress, bool>,
+    tokens: Arc<Vec<NftToken>>,
+    amount_distribution: AmountDistribution,
+    // The index of the token each account was funded with, until it sends it back.
+    account_funded: HashMap<AccountAddress, usize>,
 }
 
 impl NFTMintAndTransfer {
     pub async fn new(
+        rng: StdRng,
         txn_factory: TransactionFactory,
         creator_address: AccountAddress,
         distribution_account: LocalAccount,
-        collection_name: Vec<u8>,
-        token_name: Vec<u8>,
+        tokens: Arc<Vec<NftToken>>,
+        amount_distribution: AmountDistribution,
     ) -> Self {
         Self {
+            rng,
             txn_factory,
             distribution_account,
             creator_address,
-            collection_name,
-            token_name,
+            tokens,
+            amount_distribution,
             account_funded: Default::default(),
         }
     }
@@ -58,58 +131,79 @@ impl TransactionGenerator for NFTMintAndTransfer {
     ) -> Vec<SignedTransaction> {
         let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
         for account in accounts {
-            let account_funded = self
-                .account_funded
-                .get(&account.address())
-                .cloned()
-                .unwrap_or(false);
-            for i in 0..transactions_per_account {
-                requests.push(
-                    if account_funded {
-                        create_nft_transfer_request(
-                            account,
-                            &self.distribution_account,
-                            self.creator_address,
-                            &self.collection_name,
-                            &self.token_name,
-                            &self.txn_factory,
-                            if i != transactions_per_account - 1 {
-                                1
-                            } else {
-                                INITIAL_NFT_BALANCE + 1 - transactions_per_account as u64
-                            },
-                        )
-                    } else {
-                        create_nft_transfer_request(
-                            &mut self.distribution_account,
-                            account,
-                            self.creator_address,
-                            &self.collection_name,
-                            &self.token_name,
-                            &self.txn_factory,
-                            if i != transactions_per_account - 1 {
-                                1
-                            } else {
-                                INITIAL_NFT_BALANCE + 1 - transactions_per_account as u64
-                            },
-                        )
-                    },
-                );
+            // A funded account sends back the token it was funded with, others get a random one.
+            let funded_token = self.account_funded.remove(&account.address());
+            let token_index =
+                funded_token.unwrap_or_else(|| self.rng.gen_range(0, self.tokens.len()));
+            let (collection_name, token_name) = &self.tokens[token_index];
+            let amounts = transfer_amounts(
+                &self.amount_distribution,
+                &mut self.rng,
+                transactions_per_account,
+            );
+            for amount in amounts {
+                requests.push(if funded_token.is_some() {
+                    create_nft_transfer_request(
+                        account,
+                        &self.distribution_account,
+                        self.creator_address,
+                        collection_name,
+                        token_name,
+                        &self.txn_factory,
+                        amount,
+                    )
+                } else {
+                    create_nft_transfer_request(
+                        &mut self.distribution_account,
+                        account,
+                        self.creator_address,
+                        collection_name,
+                        token_name,
+                        &self.txn_factory,
+                        amount,
+                    )
+                });
+            }
+            if funded_token.is_none() {
+                self.account_funded.insert(account.address(), token_index);
             }
-            self.account_funded
-                .insert(account.address(), !account_funded);
         }
         requests
     }
 }
 
+/// Returns the requests creating the collections of `tokens`, followed by the ones creating
+/// `tokens`.
+pub fn create_nft_collections_requests(
+    creation_account: &mut LocalAccount,
+    tokens: &[NftToken],
+    txn_factory: &TransactionFactory,
+) -> Vec<SignedTransaction> {
+    let mut collection_names: Vec<_> = tokens.iter().map(|(collection, _)| collection).collect();
+    collection_names.dedup();
+    let mut requests: Vec<_> = collection_names
+        .into_iter()
+        .map(|collection_name| {
+            create_nft_collection_request(creation_account, collection_name, txn_factory)
+        })
+        .collect();
+    for (collection_name, token_name) in tokens {
+        requests.push(create_nft_token_request(
+            creation_account,
+            collection_name,
+            token_name,
+            txn_factory,
+        ));
+    }
+    requests
+}
+
 pub async fn initialize_nft_collection(
     txn_executor: &dyn TransactionExecutor,
     root_account: &mut LocalAccount,
     creator_account: &mut LocalAccount,
     txn_factory: &TransactionFactory,
-    collection_name: &[u8],
-    token_name: &[u8],
+    tokens: &[NftToken],
 ) {
     // Create and mint the owner account first
     let create_account_txn = create_and_fund_account_request(
@@ -120,25 +214,21 @@ pub async fn initialize_nft_collection(
     );
 
     txn_executor
-        .execute_transactions(&[create_account_txn])
-        .await
-        .unwrap();
-
-    let collection_txn =
-        create_nft_collection_request(creator_account, collection_name, txn_factory);
-
-    txn_executor
-        .execute_transactions(&[collection_txn])
+        .execute_transactions_with_retry(
+            &[create_account_txn],
+            SETUP_MAX_RETRIES,
+            SETUP_RETRY_BACKOFF,
+        )
         .await
         .unwrap();
 
-    let token_txn =
-        create_nft_token_request(creator_account, collection_name, token_name, txn_factory);
-
-    txn_executor
-        .execute_transactions(&[token_txn])
-        .await
-        .unwrap();
+    let txns = create_nft_collections_requests(creator_account, tokens, txn_factory);
+    for chunk in txns.chunks(MAX_TXNS_PER_ACCOUNT) {
+        txn_executor
+            .execute_transactions_with_retry(chunk, SETUP_MAX_RETRIES, SETUP_RETRY_BACKOFF)
+            .await
+            .unwrap();
+    }
 
     info!("initialize_nft_collection complete");
 }
@@ -206,66 +296,73 @@ pub fn create_nft_transfer_request(
 }
 
 pub struct NFTMintAndTransferGeneratorCreator {
+    rng: StdRng,
     txn_factory: TransactionFactory,
     creator_address: AccountAddress,
     distribution_accounts: Vec<LocalAccount>,
-    collection_name: Vec<u8>,
-    token_name: Vec<u8>,
+    tokens: Arc<Vec<NftToken>>,
+    amount_distribution: AmountDistribution,
 }
 
 impl NFTMintAndTransferGeneratorCreator {
+    /// The creator account, the distribution accounts and the generators are all derived from
+    /// `rng`, so that a run can be reproduced from its seed.
     pub async fn new(
+        mut rng: StdRng,
         txn_factory: TransactionFactory,
         init_txn_factory: TransactionFactory,
         root_account: &mut LocalAccount,
         txn_executor: &dyn TransactionExecutor,
         num_workers: usize,
+        amount_distribution: AmountDistribution,
+        num_collections: usize,
+        tokens_per_collection: usize,
     ) -> Self {
-        let mut rng = StdRng::from_entropy();
         let mut creator_account = LocalAccount::generate(&mut rng);
         let creator_address = creator_account.address();
-        let collection_name = "collection name".to_owned().into_bytes();
-        let token_name = "token name".to_owned().into_bytes();
+        let tokens = nft_tokens(num_collections, tokens_per_collection);
         initialize_nft_collection(
             txn_executor,
             root_account,
             &mut creator_account,
             &init_txn_factory,
-            &collection_name,
-            &token_name,
+            &tokens,
         )
         .await;
 
         let mut distribution_accounts = Vec::new();
         let mut txns = Vec::new();
 
+        // Every distribution account gets a balance of every token.
         for _ in 0..num_workers {
-            let distribution_account = LocalAccount::generate(&mut thread_rng());
-            txns.push(create_nft_transfer_request(
-                &mut creator_account,
-                &distribution_account,
-                creator_address,
-                &collection_name,
-                &token_name,
-                &init_txn_factory,
-                1_000_000_000,
-            ));
+            let distribution_account = LocalAccount::generate(&mut rng);
+            for (collection_name, token_name) in &tokens {
+                txns.push(create_nft_transfer_request(
+                    &mut creator_account,
+                    &distribution_account,
+                    creator_address,
+                    collection_name,
+                    token_name,
+                    &init_txn_factory,
+                    1_000_000_000,
+                ));
+            }
             distribution_accounts.push(distribution_account);
         }
 
         info!("Creating {} NFTs", txns.len());
-        // per account limit is 100
-        for chunk in txns.chunks(100) {
+        for chunk in txns.chunks(MAX_TXNS_PER_ACCOUNT) {
             txn_executor.execute_transactions(chunk).await.unwrap();
         }
         info!("Done creating {} NFTs", txns.len());
 
         Self {
+            rng,
             txn_factory,
             creator_address,
             distribution_accounts,
-            collection_name,
-            token_name,
+            tokens: Arc::new(tokens),
+            amount_distribution,
         }
     }
 }
@@ -275,11 +372,12 @@ impl TransactionGeneratorCreator for NFTMintAndTransferGeneratorCreator {
     async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
         Box::new(
             NFTMintAndTransfer::new(
+                StdRng::from_rng(&mut self.rng).unwrap(),
                 self.txn_factory.clone(),
                 self.creator_address,
                 self.distribution_accounts.pop().unwrap(),
-                self.collection_name.clone(),
-                self.token_name.clone(),
+                self.tokens.clone(),
+                self.amount_distribution,
             )
             .await,
         )
@@ -298,3 +396,105 @@ fn create_and_fund_account_request(
         aptos_stdlib::aptos_account_transfer(auth_key.derived_address(), amount),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockExecutor;
+    use aptos_sdk::types::{chain_id::ChainId, transaction::TransactionPayload};
+
+    async fn create_creator(seed: u64) -> NFTMintAndTransferGeneratorCreator {
+        let mut root_account = LocalAccount::generate(&mut StdRng::seed_from_u64(100));
+        let txn_factory = TransactionFactory::new(ChainId::test());
+        let executor = MockExecutor::new(|_: &[SignedTransaction]| Ok(()));
+        NFTMintAndTransferGeneratorCreator::new(
+            StdRng::seed_from_u64(seed),
+            txn_factory.clone(),
+            txn_factory,
+            &mut root_account,
+            &executor,
+            3,
+            AmountDistribution::default(),
+            1,
+            1,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_creator_accounts_from_seed() {
+        let addresses = |creator: &NFTMintAndTransferGeneratorCreator| {
+            let mut addresses = vec![creator.creator_address];
+            addresses.extend(
+                creator
+                    .distribution_accounts
+                    .iter()
+                    .map(|account| account.address()),
+            );
+            addresses
+        };
+
+        let creator = create_creator(0).await;
+        assert_eq!(addresses(&creator), addresses(&create_creator(0).await));
+        assert_ne!(addresses(&creator), addresses(&create_creator(1).await));
+    }
+
+    #[test]
+    fn test_create_nft_collections_requests() {
+        let mut creator_account = LocalAccount::generate(&mut StdRng::seed_from_u64(0));
+        let tokens = nft_tokens(3, 4);
+        assert_eq!(tokens.len(), 12);
+        let txns = create_nft_collections_requests(
+            &mut creator_account,
+            &tokens,
+            &TransactionFactory::new(ChainId::test()),
+        );
+
+        let functions: Vec<_> = txns
+            .iter()
+            .map(|txn| match txn.payload() {
+                TransactionPayload::EntryFunction(entry_function) => {
+                    entry_function.function().as_str()
+                },
+                _ => panic!("unexpected payload"),
+            })
+            .collect();
+        let num_collection_txns = functions
+            .iter()
+            .filter(|function| **function == "create_collection_script")
+            .count();
+        let num_token_txns = functions
+            .iter()
+            .filter(|function| **function == "create_token_script")
+            .count();
+        assert_eq!(num_collection_txns, 3);
+        assert_eq!(num_token_txns, 12);
+        assert_eq!(txns.len(), 15);
+        // Collections are created before their tokens.
+        assert!(functions[..3]
+            .iter()
+            .all(|function| *function == "create_collection_script"));
+    }
+
+    #[test]
+    fn test_transfer_amounts() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // The default moves 1 NFT per transfer, and the rest with the last one.
+        let amounts = transfer_amounts(&AmountDistribution::default(), &mut rng, 4);
+        assert_eq!(amounts, vec![1, 1, 1, INITIAL_NFT_BALANCE - 3]);
+
+        for (min, max) in [(1, 10), (100, 20_000), (1, 2 * INITIAL_NFT_BALANCE)] {
+            let distribution = AmountDistribution::Uniform { min, max };
+            for num_transfers in [1, 5, 20] {
+                let amounts = transfer_amounts(&distribution, &mut rng, num_transfers);
+                assert_eq!(amounts.len(), num_transfers);
+                assert!(amounts.iter().all(|amount| *amount >= 1));
+                // Never more than the balance of the sender, which is moved entirely.
+                assert_eq!(amounts.iter().sum::<u64>(), INITIAL_NFT_BALANCE);
+                for amount in &amounts[..num_transfers - 1] {
+                    assert!(*amount <= max);
+                }
+            }
+        }
+    }
+}
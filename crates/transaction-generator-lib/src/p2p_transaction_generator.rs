@@ -1,6 +1,6 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use crate::{gen_expiration_jitter, TransactionGenerator, TransactionGeneratorCreator};
 use aptos_infallible::RwLock;
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
@@ -14,7 +14,7 @@ use rand::{
     rngs::StdRng,
     Rng, RngCore, SeedableRng,
 };
-use std::{cmp::max, sync::Arc};
+use std::{cmp::max, ops::RangeInclusive, sync::Arc};
 
 pub struct P2PTransactionGenerator {
     rng: StdRng,
@@ -22,6 +22,7 @@ pub struct P2PTransactionGenerator {
     txn_factory: TransactionFactory,
     all_addresses: Arc<RwLock<Vec<AccountAddress>>>,
     invalid_transaction_ratio: usize,
+    expiration_jitter_secs: RangeInclusive<u64>,
 }
 
 impl P2PTransactionGenerator {
@@ -38,19 +39,29 @@ impl P2PTransactionGenerator {
             txn_factory,
             all_addresses,
             invalid_transaction_ratio,
+            expiration_jitter_secs: 0..=0,
         }
     }
 
+    /// Delays the expiration of each generated transaction by a random number of seconds in
+    /// `expiration_jitter_secs`, so that a burst of transactions doesn't expire all at once.
+    pub fn set_expiration_jitter(&mut self, expiration_jitter_secs: RangeInclusive<u64>) {
+        assert!(!expiration_jitter_secs.is_empty());
+        self.expiration_jitter_secs = expiration_jitter_secs;
+    }
+
     fn gen_single_txn(
         &self,
         from: &mut LocalAccount,
         to: &AccountAddress,
         num_coins: u64,
         txn_factory: &TransactionFactory,
+        expiration_jitter_secs: u64,
     ) -> SignedTransaction {
-        from.sign_with_transaction_builder(
-            txn_factory.payload(aptos_stdlib::aptos_coin_transfer(*to, num_coins)),
-        )
+        from.sign_with_transaction_builder(txn_factory.payload_with_expiration_jitter(
+            aptos_stdlib::aptos_coin_transfer(*to, num_coins),
+            expiration_jitter_secs,
+        ))
     }
 
     fn generate_invalid_transaction(
@@ -65,26 +76,28 @@ impl P2PTransactionGenerator {
         match Standard.sample(rng) {
             InvalidTransactionType::ChainId => {
                 let txn_factory = &self.txn_factory.clone().with_chain_id(ChainId::new(255));
-                self.gen_single_txn(sender, receiver, self.send_amount, txn_factory)
+                self.gen_single_txn(sender, receiver, self.send_amount, txn_factory, 0)
             },
             InvalidTransactionType::Sender => self.gen_single_txn(
                 &mut invalid_account,
                 receiver,
                 self.send_amount,
                 &self.txn_factory,
+                0,
             ),
             InvalidTransactionType::Receiver => self.gen_single_txn(
                 sender,
                 &invalid_address,
                 self.send_amount,
                 &self.txn_factory,
+                0,
             ),
             InvalidTransactionType::Duplication => {
                 // if this is the first tx, default to generate invalid tx with wrong chain id
                 // otherwise, make a duplication of an exist valid tx
                 if reqs.is_empty() {
                     let txn_factory = &self.txn_factory.clone().with_chain_id(ChainId::new(255));
-                    self.gen_single_txn(sender, receiver, self.send_amount, txn_factory)
+                    self.gen_single_txn(sender, receiver, self.send_amount, txn_factory, 0)
                 } else {
                     let random_index = rng.gen_range(0, reqs.len());
                     reqs[random_index].clone()
@@ -148,7 +161,15 @@ impl TransactionGenerator for P2PTransactionGenerator {
                 let receiver = receivers.get(i).expect("all_addresses can't be empty");
                 let request = if num_valid_tx > 0 {
                     num_valid_tx -= 1;
-                    self.gen_single_txn(sender, receiver, self.send_amount, &self.txn_factory)
+                    let expiration_jitter_secs =
+                        gen_expiration_jitter(&mut self.rng, &self.expiration_jitter_secs);
+                    self.gen_single_txn(
+                        sender,
+                        receiver,
+                        self.send_amount,
+                        &self.txn_factory,
+                        expiration_jitter_secs,
+                    )
                 } else {
                     self.generate_invalid_transaction(
                         &mut self.rng.clone(),
@@ -165,37 +186,91 @@ impl TransactionGenerator for P2PTransactionGenerator {
 }
 
 pub struct P2PTransactionGeneratorCreator {
+    rng: StdRng,
     txn_factory: TransactionFactory,
     amount: u64,
     all_addresses: Arc<RwLock<Vec<AccountAddress>>>,
     invalid_transaction_ratio: usize,
+    expiration_jitter_secs: RangeInclusive<u64>,
 }
 
 impl P2PTransactionGeneratorCreator {
     pub fn new(
+        rng: StdRng,
         txn_factory: TransactionFactory,
         amount: u64,
         all_addresses: Arc<RwLock<Vec<AccountAddress>>>,
         invalid_transaction_ratio: usize,
     ) -> Self {
         Self {
+            rng,
             txn_factory,
             amount,
             all_addresses,
             invalid_transaction_ratio,
+            expiration_jitter_secs: 0..=0,
         }
     }
+
+    pub fn with_expiration_jitter(mut self, expiration_jitter_secs: RangeInclusive<u64>) -> Self {
+        self.expiration_jitter_secs = expiration_jitter_secs;
+        self
+    }
 }
 
 #[async_trait]
 impl TransactionGeneratorCreator for P2PTransactionGeneratorCreator {
     async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
-        Box::new(P2PTransactionGenerator::new(
-            StdRng::from_entropy(),
+        let mut generator = P2PTransactionGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
             self.amount,
             self.txn_factory.clone(),
             self.all_addresses.clone(),
             self.invalid_transaction_ratio,
-        ))
+        );
+        generator.set_expiration_jitter(self.expiration_jitter_secs.clone());
+        Box::new(generator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_infallible::duration_since_epoch;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_expiration_jitter() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let all_addresses = Arc::new(RwLock::new(
+            accounts.iter().map(|account| account.address()).collect(),
+        ));
+        let txn_factory = TransactionFactory::new(ChainId::test());
+        let mut generator = P2PTransactionGenerator::new(rng, 1, txn_factory, all_addresses, 0);
+
+        // Without jitter, a batch expires within a second.
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 5);
+        let expirations: HashSet<_> = txns
+            .iter()
+            .map(|txn| txn.expiration_timestamp_secs())
+            .collect();
+        assert!(expirations.len() <= 2);
+
+        let before_secs = duration_since_epoch().as_secs();
+        generator.set_expiration_jitter(10..=100);
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 5);
+        let after_secs = duration_since_epoch().as_secs();
+        assert_eq!(txns.len(), 50);
+        for txn in &txns {
+            // The default expiration time of the factory is 30 seconds.
+            assert!(txn.expiration_timestamp_secs() >= before_secs + 30 + 10);
+            assert!(txn.expiration_timestamp_secs() <= after_secs + 30 + 100);
+        }
+        let expirations: HashSet<_> = txns
+            .iter()
+            .map(|txn| txn.expiration_timestamp_secs())
+            .collect();
+        assert!(expirations.len() > 10);
     }
 }
@@ -0,0 +1,149 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::types::{transaction::SignedTransaction, LocalAccount};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use async_trait::async_trait;
+use std::time::{Duration, Instant};
+
+/// Generates with one generator per phase of a timeline, e.g. a minute of transfers followed by
+/// a minute of NFT transfers. A phase lasts its duration from the end of the previous one, and
+/// the last one lasts until the end of the run. Each batch is generated by a single generator,
+/// the one of the phase it starts in, so no batch straddles two phases.
+pub struct PhasedGenerator {
+    generators: Vec<(Duration, Box<dyn TransactionGenerator>)>,
+    time_service: TimeService,
+    start: Instant,
+}
+
+impl PhasedGenerator {
+    pub fn new(
+        generators: Vec<(Duration, Box<dyn TransactionGenerator>)>,
+        time_service: TimeService,
+        start: Instant,
+    ) -> Self {
+        assert!(!generators.is_empty());
+        Self {
+            generators,
+            time_service,
+            start,
+        }
+    }
+
+    /// Returns the index of the phase of the current time.
+    pub fn current_phase(&self) -> usize {
+        let elapsed = self.time_service.now().saturating_duration_since(self.start);
+        let mut phase_end = Duration::ZERO;
+        for (phase, (duration, _)) in self.generators.iter().enumerate() {
+            phase_end += *duration;
+            if elapsed < phase_end {
+                return phase;
+            }
+        }
+        self.generators.len() - 1
+    }
+}
+
+impl TransactionGenerator for PhasedGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let phase = self.current_phase();
+        self.generators[phase]
+            .1
+            .generate_transactions(accounts, transactions_per_account)
+    }
+}
+
+pub struct PhasedGeneratorCreator {
+    creators: Vec<(Duration, Box<dyn TransactionGeneratorCreator>)>,
+    time_service: TimeService,
+    // The timeline starts with the first generator, and is shared by all of them.
+    start: Option<Instant>,
+}
+
+impl PhasedGeneratorCreator {
+    pub fn new(
+        creators: Vec<(Duration, Box<dyn TransactionGeneratorCreator>)>,
+        time_service: TimeService,
+    ) -> Self {
+        assert!(!creators.is_empty());
+        Self {
+            creators,
+            time_service,
+            start: None,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for PhasedGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        let mut generators = Vec::with_capacity(self.creators.len());
+        for (duration, creator) in self.creators.iter_mut() {
+            generators.push((*duration, creator.create_transaction_generator().await));
+        }
+        let start = *self.start.get_or_insert_with(|| self.time_service.now());
+        Box::new(PhasedGenerator::new(generators, self.time_service.clone(), start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TransferToSelfGeneratorCreator;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[tokio::test]
+    async fn test_phase_changes_at_boundary() {
+        let time_service = TimeService::mock();
+        let mock_time = time_service.clone().into_mock();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..2).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let creators: Vec<(Duration, Box<dyn TransactionGeneratorCreator>)> = vec![
+            (
+                Duration::from_secs(60),
+                Box::new(TransferToSelfGeneratorCreator::new(100)),
+            ),
+            (
+                Duration::from_secs(60),
+                Box::new(TransferToSelfGeneratorCreator::new(200)),
+            ),
+            (
+                Duration::from_secs(60),
+                Box::new(TransferToSelfGeneratorCreator::new(300)),
+            ),
+        ];
+        let mut creator = PhasedGeneratorCreator::new(creators, time_service);
+        let mut generator = creator.create_transaction_generator().await;
+
+        let mut active_tag = |generator: &mut dyn TransactionGenerator| {
+            let txns = generator.generate_transactions(accounts.iter_mut().collect(), 2);
+            assert_eq!(txns.len(), 4);
+            // The whole batch comes from the same phase.
+            assert!(txns
+                .iter()
+                .all(|txn| txn.gas_unit_price() == txns[0].gas_unit_price()));
+            txns[0].gas_unit_price()
+        };
+
+        assert_eq!(active_tag(generator.as_mut()), 100);
+        mock_time.advance_secs(59);
+        assert_eq!(active_tag(generator.as_mut()), 100);
+        mock_time.advance_secs(1);
+        assert_eq!(active_tag(generator.as_mut()), 200);
+
+        // Generators created later follow the same timeline.
+        let mut late_generator = creator.create_transaction_generator().await;
+        assert_eq!(active_tag(late_generator.as_mut()), 200);
+
+        mock_time.advance_secs(60);
+        assert_eq!(active_tag(generator.as_mut()), 300);
+        // The last phase lasts until the end of the run.
+        mock_time.advance_secs(1_000);
+        assert_eq!(active_tag(generator.as_mut()), 300);
+        assert_eq!(active_tag(late_generator.as_mut()), 300);
+    }
+}
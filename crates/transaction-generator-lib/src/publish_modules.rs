@@ -69,13 +69,15 @@ impl TransactionGenerator for PublishPackageGenerator {
 }
 
 pub struct PublishPackageCreator {
+    rng: StdRng,
     txn_factory: TransactionFactory,
     package_handler: Arc<RwLock<PackageHandler>>,
 }
 
 impl PublishPackageCreator {
-    pub fn new(txn_factory: TransactionFactory) -> Self {
+    pub fn new(rng: StdRng, txn_factory: TransactionFactory) -> Self {
         Self {
+            rng,
             txn_factory,
             package_handler: Arc::new(RwLock::new(PackageHandler::new())),
         }
@@ -86,7 +88,7 @@ impl PublishPackageCreator {
 impl TransactionGeneratorCreator for PublishPackageCreator {
     async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
         Box::new(PublishPackageGenerator::new(
-            StdRng::from_entropy(),
+            StdRng::from_rng(&mut self.rng).unwrap(),
             self.package_handler.clone(),
             self.txn_factory.clone(),
         ))
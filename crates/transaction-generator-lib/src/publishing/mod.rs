@@ -3,4 +3,4 @@
 
 pub mod module_simple;
 pub mod publish_util;
-mod raw_module_data;
+pub mod raw_module_data;
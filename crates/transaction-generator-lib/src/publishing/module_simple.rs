@@ -341,7 +341,7 @@ fn bytes_make_or_change(
     module_id: ModuleId,
     data_len: usize,
 ) -> TransactionPayload {
-    let mut bytes = Vec::<u8>::with_capacity(data_len);
+    let mut bytes = vec![0u8; data_len];
     rng.fill_bytes(&mut bytes);
     get_payload(
         module_id,
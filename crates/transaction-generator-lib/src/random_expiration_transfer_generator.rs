@@ -0,0 +1,125 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator, SEND_AMOUNT};
+use aptos_infallible::duration_since_epoch;
+use aptos_sdk::{
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Minimum number of seconds from now the transactions expire in, so that they don't expire
+/// before being pulled from mempool.
+pub const MIN_EXPIRATION_SECS: u64 = 10;
+
+/// Generates transfers of every account to itself, at the gas unit price of the factory, each
+/// expiring a random number of seconds in `[MIN_EXPIRATION_SECS, max_expiration_secs]` from
+/// now. Gas prices tie, and mempool breaks the ties by the system TTL of the transactions, i.e.
+/// the time they entered mempool, and not by their expiration, so this checks that the order
+/// transactions are pulled in doesn't depend on their expiration.
+pub struct RandomExpirationTransferGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    max_expiration_secs: u64,
+}
+
+impl RandomExpirationTransferGenerator {
+    pub fn new(rng: StdRng, txn_factory: TransactionFactory, max_expiration_secs: u64) -> Self {
+        assert!(
+            max_expiration_secs >= MIN_EXPIRATION_SECS,
+            "Transactions must expire at least {} seconds from now",
+            MIN_EXPIRATION_SECS
+        );
+        Self {
+            rng,
+            txn_factory,
+            max_expiration_secs,
+        }
+    }
+}
+
+impl TransactionGenerator for RandomExpirationTransferGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let now_secs = duration_since_epoch().as_secs();
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let expiration_secs = self
+                    .rng
+                    .gen_range(MIN_EXPIRATION_SECS, self.max_expiration_secs + 1);
+                let builder = self
+                    .txn_factory
+                    .payload(aptos_stdlib::aptos_coin_transfer(account.address(), SEND_AMOUNT))
+                    .expiration_timestamp_secs(now_secs + expiration_secs);
+                requests.push(account.sign_with_transaction_builder(builder));
+            }
+        }
+        requests
+    }
+}
+
+pub struct RandomExpirationTransferGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    max_expiration_secs: u64,
+}
+
+impl RandomExpirationTransferGeneratorCreator {
+    pub fn new(rng: StdRng, txn_factory: TransactionFactory, max_expiration_secs: u64) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            max_expiration_secs,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for RandomExpirationTransferGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(RandomExpirationTransferGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.max_expiration_secs,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::chain_id::ChainId;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_expirations_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator = RandomExpirationTransferGenerator::new(
+            rng,
+            TransactionFactory::new(ChainId::test()),
+            20,
+        );
+
+        let before_secs = duration_since_epoch().as_secs();
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 10);
+        let after_secs = duration_since_epoch().as_secs();
+
+        assert_eq!(txns.len(), 100);
+        for txn in &txns {
+            assert_eq!(txn.gas_unit_price(), txns[0].gas_unit_price());
+            assert!(txn.expiration_timestamp_secs() >= before_secs + MIN_EXPIRATION_SECS);
+            assert!(txn.expiration_timestamp_secs() <= after_secs + 20);
+        }
+        let expirations: HashSet<_> = txns
+            .iter()
+            .map(|txn| txn.expiration_timestamp_secs())
+            .collect();
+        assert!(expirations.len() > 1);
+    }
+}
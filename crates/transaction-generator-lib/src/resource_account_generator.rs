@@ -0,0 +1,133 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{account_address::create_resource_address, transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+
+/// Returns the seed of the resource account created by the transaction of sequence number
+/// `sequence_number` of a sender: `seed_prefix` followed by the sequence number, both little
+/// endian, so that every transaction of a sender creates a distinct resource account.
+pub fn resource_account_seed(seed_prefix: u64, sequence_number: u64) -> Vec<u8> {
+    let mut seed = seed_prefix.to_le_bytes().to_vec();
+    seed.extend_from_slice(&sequence_number.to_le_bytes());
+    seed
+}
+
+/// Returns the address of the resource account created by the transaction of sequence number
+/// `sequence_number` of `origin`.
+pub fn resource_account_address(
+    origin: AccountAddress,
+    seed_prefix: u64,
+    sequence_number: u64,
+) -> AccountAddress {
+    create_resource_address(origin, &resource_account_seed(seed_prefix, sequence_number))
+}
+
+/// Stresses resource account creation. Every transaction creates a new resource account of its
+/// sender through `resource_account::create_resource_account`, with the seed of
+/// `resource_account_seed`, keeping the auth key of the sender.
+pub struct ResourceAccountGenerator {
+    txn_factory: TransactionFactory,
+    seed_prefix: u64,
+}
+
+impl ResourceAccountGenerator {
+    pub fn new(txn_factory: TransactionFactory, seed_prefix: u64) -> Self {
+        Self {
+            txn_factory,
+            seed_prefix,
+        }
+    }
+}
+
+impl TransactionGenerator for ResourceAccountGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let seed = resource_account_seed(self.seed_prefix, account.sequence_number());
+                let payload = aptos_stdlib::resource_account_create_resource_account(seed, vec![]);
+                let builder = self.txn_factory.payload(payload);
+                requests.push(account.sign_with_transaction_builder(builder));
+            }
+        }
+        requests
+    }
+}
+
+pub struct ResourceAccountGeneratorCreator {
+    txn_factory: TransactionFactory,
+    seed_prefix: u64,
+}
+
+impl ResourceAccountGeneratorCreator {
+    pub fn new(txn_factory: TransactionFactory, seed_prefix: u64) -> Self {
+        Self {
+            txn_factory,
+            seed_prefix,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for ResourceAccountGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(ResourceAccountGenerator::new(
+            self.txn_factory.clone(),
+            self.seed_prefix,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::{
+        bcs,
+        types::{chain_id::ChainId, transaction::TransactionPayload},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_creates_distinct_resource_accounts() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..3).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator =
+            ResourceAccountGenerator::new(TransactionFactory::new(ChainId::test()), 7);
+
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 4);
+        assert_eq!(txns.len(), 12);
+        let mut resource_accounts = HashSet::new();
+        for txn in &txns {
+            let entry_function = match txn.payload() {
+                TransactionPayload::EntryFunction(entry_function) => entry_function,
+                _ => panic!("Unexpected payload"),
+            };
+            assert_eq!(entry_function.module().address(), &AccountAddress::ONE);
+            assert_eq!(entry_function.module().name().as_str(), "resource_account");
+            assert_eq!(entry_function.function().as_str(), "create_resource_account");
+
+            let seed: Vec<u8> = bcs::from_bytes(&entry_function.args()[0]).unwrap();
+            assert_eq!(seed, resource_account_seed(7, txn.sequence_number()));
+            let auth_key: Vec<u8> = bcs::from_bytes(&entry_function.args()[1]).unwrap();
+            assert!(auth_key.is_empty());
+            assert!(resource_accounts.insert(create_resource_address(txn.sender(), &seed)));
+        }
+
+        // The next batch creates new resource accounts.
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 1);
+        for txn in txns {
+            let address = resource_account_address(txn.sender(), 7, txn.sequence_number());
+            assert!(resource_accounts.insert(address));
+        }
+    }
+}
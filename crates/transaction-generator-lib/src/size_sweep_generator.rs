@@ -0,0 +1,218 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    call_custom_modules::publish_packages,
+    publishing::{module_simple::EntryPoints, publish_util::Package},
+    TransactionExecutor,
+};
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_logger::info;
+use aptos_sdk::{
+    bcs,
+    transaction_builder::TransactionFactory,
+    types::{
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::sync::Arc;
+
+/// Returns the payload sizes of `num_buckets` buckets evenly spread over
+/// `[min_size, max_size]`.
+pub fn size_buckets(min_size: usize, max_size: usize, num_buckets: usize) -> Vec<usize> {
+    assert!(min_size <= max_size);
+    assert!(num_buckets > 0);
+    if num_buckets == 1 {
+        return vec![min_size];
+    }
+    (0..num_buckets)
+        .map(|i| min_size + (max_size - min_size) * i / (num_buckets - 1))
+        .collect()
+}
+
+/// Sweeps the size of the transactions over the size buckets, to profile throughput against
+/// transaction size. Transactions call `bytes_make_or_change` of the published packages with
+/// as many bytes as the size of the current bucket, which moves to the next bucket (and back
+/// to the first one after the last) every `txns_per_bucket` transactions. Entering a bucket is
+/// logged, and the bucket of a transaction is recovered from its payload by `bucket_of`.
+pub struct SizeSweepGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    packages: Arc<Vec<Package>>,
+    sizes: Vec<usize>,
+    txns_per_bucket: usize,
+    num_generated: usize,
+}
+
+impl SizeSweepGenerator {
+    pub fn new(
+        rng: StdRng,
+        txn_factory: TransactionFactory,
+        packages: Arc<Vec<Package>>,
+        sizes: Vec<usize>,
+        txns_per_bucket: usize,
+    ) -> Self {
+        assert!(!sizes.is_empty());
+        assert!(txns_per_bucket > 0);
+        Self {
+            rng,
+            txn_factory,
+            packages,
+            sizes,
+            txns_per_bucket,
+            num_generated: 0,
+        }
+    }
+
+    /// Returns the bucket of the size of the payload of `txn`, if it is one of the sweep.
+    pub fn bucket_of(&self, txn: &SignedTransaction) -> Option<usize> {
+        let data_len = match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => {
+                bcs::from_bytes::<Vec<u8>>(entry_function.args().first()?)
+                    .ok()?
+                    .len()
+            },
+            _ => return None,
+        };
+        self.sizes.iter().position(|size| *size == data_len)
+    }
+
+    fn next_bucket(&mut self) -> usize {
+        let bucket = self.num_generated / self.txns_per_bucket % self.sizes.len();
+        if self.num_generated % self.txns_per_bucket == 0 {
+            info!(
+                "Size sweep entering bucket {} of {} bytes",
+                bucket, self.sizes[bucket]
+            );
+        }
+        self.num_generated += 1;
+        bucket
+    }
+}
+
+impl TransactionGenerator for SizeSweepGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let bucket = self.next_bucket();
+                let entry_point = EntryPoints::BytesMakeOrChange {
+                    data_length: Some(self.sizes[bucket]),
+                };
+                let package = self.packages.choose(&mut self.rng).unwrap();
+                requests.push(package.use_specific_transaction(
+                    entry_point,
+                    account,
+                    &self.txn_factory,
+                    Some(&mut self.rng),
+                    None,
+                ));
+            }
+        }
+        requests
+    }
+}
+
+pub struct SizeSweepGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    packages: Arc<Vec<Package>>,
+    sizes: Vec<usize>,
+    txns_per_bucket: usize,
+}
+
+impl SizeSweepGeneratorCreator {
+    pub async fn new(
+        mut rng: StdRng,
+        txn_factory: TransactionFactory,
+        init_txn_factory: TransactionFactory,
+        accounts: &mut [LocalAccount],
+        txn_executor: &dyn TransactionExecutor,
+        num_modules: usize,
+        sizes: Vec<usize>,
+        txns_per_bucket: usize,
+    ) -> Self {
+        let packages = publish_packages(
+            &mut rng,
+            &init_txn_factory,
+            accounts,
+            txn_executor,
+            num_modules,
+        )
+        .await;
+        Self {
+            rng,
+            txn_factory,
+            packages: Arc::new(packages),
+            sizes,
+            txns_per_bucket,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for SizeSweepGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(SizeSweepGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.packages.clone(),
+            self.sizes.clone(),
+            self.txns_per_bucket,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::chain_id::ChainId;
+
+    #[test]
+    fn test_size_buckets() {
+        assert_eq!(size_buckets(0, 1000, 5), vec![0, 250, 500, 750, 1000]);
+        assert_eq!(size_buckets(10, 20, 1), vec![10]);
+    }
+
+    #[test]
+    fn test_sweeps_buckets_in_order() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..2).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let package = Package::simple().update(accounts[0].address(), 0);
+        let sizes = size_buckets(100, 400, 4);
+        let mut generator = SizeSweepGenerator::new(
+            rng,
+            TransactionFactory::new(ChainId::test()),
+            Arc::new(vec![package]),
+            sizes.clone(),
+            3,
+        );
+
+        // 2 batches of 2 accounts with 2 txns each: buckets of 3 txns, across batches.
+        let mut buckets = vec![];
+        for _ in 0..2 {
+            let txns = generator.generate_transactions(accounts.iter_mut().collect(), 2);
+            assert_eq!(txns.len(), 4);
+            buckets.extend(txns.iter().map(|txn| generator.bucket_of(txn).unwrap()));
+        }
+        assert_eq!(buckets, vec![0, 0, 0, 1, 1, 1, 2, 2]);
+
+        // After the last bucket, the sweep starts over.
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 2);
+        let buckets: Vec<_> = txns
+            .iter()
+            .map(|txn| generator.bucket_of(txn).unwrap())
+            .collect();
+        assert_eq!(buckets, vec![2, 3, 3, 3]);
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 1);
+        assert_eq!(generator.bucket_of(&txns[0]), Some(0));
+        assert_eq!(generator.bucket_of(&txns[1]), Some(0));
+    }
+}
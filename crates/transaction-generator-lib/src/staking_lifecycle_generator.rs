@@ -0,0 +1,184 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// Stage of the staking lifecycle the next transaction of an account executes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StakingStage {
+    Delegate,
+    Unlock,
+    Withdraw,
+}
+
+impl StakingStage {
+    fn next(self) -> Self {
+        match self {
+            StakingStage::Delegate => StakingStage::Unlock,
+            StakingStage::Unlock => StakingStage::Withdraw,
+            StakingStage::Withdraw => StakingStage::Delegate,
+        }
+    }
+}
+
+/// Cycles every account through the staking lifecycle of the delegation pool at
+/// `pool_address`: each transaction of an account delegates `stake_amount` to the pool, unlocks
+/// it, or withdraws it, in that order, and then starts over. Withdrawals only succeed once the
+/// lockup of the pool expired, before that they withdraw nothing, but still touch the same
+/// resources of the pool.
+pub struct StakingLifecycleGenerator {
+    txn_factory: TransactionFactory,
+    pool_address: AccountAddress,
+    stake_amount: u64,
+    // stage of the next transaction of each account
+    stages: HashMap<AccountAddress, StakingStage>,
+}
+
+impl StakingLifecycleGenerator {
+    pub fn new(
+        txn_factory: TransactionFactory,
+        pool_address: AccountAddress,
+        stake_amount: u64,
+    ) -> Self {
+        Self {
+            txn_factory,
+            pool_address,
+            stake_amount,
+            stages: HashMap::new(),
+        }
+    }
+
+    /// Returns the stage the next transaction of `address` executes.
+    pub fn stage(&self, address: &AccountAddress) -> StakingStage {
+        self.stages
+            .get(address)
+            .copied()
+            .unwrap_or(StakingStage::Delegate)
+    }
+
+    fn gen_single_txn(&mut self, account: &mut LocalAccount) -> SignedTransaction {
+        let stage = self.stage(&account.address());
+        let payload = match stage {
+            StakingStage::Delegate => {
+                aptos_stdlib::delegation_pool_add_stake(self.pool_address, self.stake_amount)
+            },
+            StakingStage::Unlock => {
+                aptos_stdlib::delegation_pool_unlock(self.pool_address, self.stake_amount)
+            },
+            StakingStage::Withdraw => {
+                aptos_stdlib::delegation_pool_withdraw(self.pool_address, self.stake_amount)
+            },
+        };
+        self.stages.insert(account.address(), stage.next());
+        account.sign_with_transaction_builder(self.txn_factory.payload(payload))
+    }
+}
+
+impl TransactionGenerator for StakingLifecycleGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                requests.push(self.gen_single_txn(account));
+            }
+        }
+        requests
+    }
+}
+
+pub struct StakingLifecycleGeneratorCreator {
+    txn_factory: TransactionFactory,
+    pool_address: AccountAddress,
+    stake_amount: u64,
+}
+
+impl StakingLifecycleGeneratorCreator {
+    pub fn new(
+        txn_factory: TransactionFactory,
+        pool_address: AccountAddress,
+        stake_amount: u64,
+    ) -> Self {
+        Self {
+            txn_factory,
+            pool_address,
+            stake_amount,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for StakingLifecycleGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(StakingLifecycleGenerator::new(
+            self.txn_factory.clone(),
+            self.pool_address,
+            self.stake_amount,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::{chain_id::ChainId, transaction::TransactionPayload};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn function(txn: &SignedTransaction) -> String {
+        match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => format!(
+                "{}::{}",
+                entry_function.module().name(),
+                entry_function.function()
+            ),
+            _ => panic!("Unexpected payload"),
+        }
+    }
+
+    #[test]
+    fn test_accounts_cycle_through_staking_lifecycle() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut accounts: Vec<_> = (0..3).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let pool_address = LocalAccount::generate(&mut rng).address();
+        let mut generator = StakingLifecycleGenerator::new(
+            TransactionFactory::new(ChainId::test()),
+            pool_address,
+            1_000_000_000,
+        );
+
+        let expected = [
+            ("delegation_pool::add_stake", StakingStage::Unlock),
+            ("delegation_pool::unlock", StakingStage::Withdraw),
+            ("delegation_pool::withdraw", StakingStage::Delegate),
+            ("delegation_pool::add_stake", StakingStage::Unlock),
+        ];
+        for (expected_function, expected_stage) in expected {
+            let txns = generator.generate_transactions(accounts.iter_mut().collect(), 1);
+            assert_eq!(txns.len(), 3);
+            for (txn, account) in txns.iter().zip(&accounts) {
+                assert_eq!(txn.sender(), account.address());
+                assert_eq!(function(txn), expected_function);
+                assert_eq!(generator.stage(&account.address()), expected_stage);
+            }
+        }
+
+        // Several transactions of an account in one call walk through consecutive stages.
+        let txns = generator.generate_transactions(vec![&mut accounts[0]], 2);
+        let functions: Vec<_> = txns.iter().map(function).collect();
+        assert_eq!(functions, vec![
+            "delegation_pool::unlock",
+            "delegation_pool::withdraw"
+        ]);
+        assert_eq!(generator.stage(&accounts[0].address()), StakingStage::Delegate);
+        assert_eq!(generator.stage(&accounts[1].address()), StakingStage::Unlock);
+    }
+}
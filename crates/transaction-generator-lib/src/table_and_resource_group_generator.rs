@@ -0,0 +1,233 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_cached_packages::aptos_token_objects_sdk_builder as aptos_token_objects_stdlib;
+use aptos_sdk::{
+    bcs,
+    move_types::account_address::AccountAddress,
+    transaction_builder::{aptos_stdlib::aptos_token_stdlib, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+const COLLECTION_NAME: &[u8] = b"table_and_resource_group";
+
+/// Exercises the table and resource group storage paths, by minting tokens with
+/// `num_keys_per_txn` properties each. Token v1 (`0x3::token`) keeps token data in tables,
+/// while token objects (`0x4::aptos_token`) are stored in resource groups. Every account
+/// first creates a collection of each kind, and then alternates between the two.
+pub struct TableAndResourceGroupGenerator {
+    txn_factory: TransactionFactory,
+    num_keys_per_txn: usize,
+    // number of tokens minted by each account, present once its collections are created
+    minted: HashMap<AccountAddress, u64>,
+}
+
+impl TableAndResourceGroupGenerator {
+    pub fn new(txn_factory: TransactionFactory, num_keys_per_txn: usize) -> Self {
+        Self {
+            txn_factory,
+            num_keys_per_txn,
+            minted: HashMap::new(),
+        }
+    }
+
+    fn create_collections(&self, account: &mut LocalAccount) -> Vec<SignedTransaction> {
+        vec![
+            account.sign_with_transaction_builder(self.txn_factory.payload(
+                aptos_token_stdlib::token_create_collection_script(
+                    COLLECTION_NAME.to_vec(),
+                    b"description".to_vec(),
+                    b"uri".to_vec(),
+                    u64::MAX,
+                    vec![false, false, false],
+                ),
+            )),
+            account.sign_with_transaction_builder(self.txn_factory.payload(
+                aptos_token_objects_stdlib::aptos_token_create_collection(
+                    b"description".to_vec(),
+                    u64::MAX,
+                    COLLECTION_NAME.to_vec(),
+                    b"uri".to_vec(),
+                    false,
+                    false,
+                    false,
+                    false,
+                    false,
+                    true,
+                    false,
+                    false,
+                    false,
+                    0,
+                    1,
+                ),
+            )),
+        ]
+    }
+
+    fn mint(&self, account: &mut LocalAccount, index: u64) -> SignedTransaction {
+        let token_name = format!("token_{}", index).into_bytes();
+        let keys: Vec<_> = (0..self.num_keys_per_txn)
+            .map(|i| format!("key_{}", i).into_bytes())
+            .collect();
+        let values: Vec<_> = (0..self.num_keys_per_txn as u64)
+            .map(|i| bcs::to_bytes(&i).unwrap())
+            .collect();
+        let types = vec![b"u64".to_vec(); self.num_keys_per_txn];
+
+        let payload = if index % 2 == 0 {
+            aptos_token_stdlib::token_create_token_script(
+                COLLECTION_NAME.to_vec(),
+                token_name,
+                b"description".to_vec(),
+                1,
+                u64::MAX,
+                b"uri".to_vec(),
+                account.address(),
+                1,
+                0,
+                vec![false, false, false, false, true],
+                keys,
+                values,
+                types,
+            )
+        } else {
+            aptos_token_objects_stdlib::aptos_token_mint(
+                COLLECTION_NAME.to_vec(),
+                b"description".to_vec(),
+                token_name,
+                b"uri".to_vec(),
+                keys,
+                types,
+                values,
+            )
+        };
+        account.sign_with_transaction_builder(self.txn_factory.payload(payload))
+    }
+}
+
+impl TransactionGenerator for TableAndResourceGroupGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            let address = account.address();
+            if !self.minted.contains_key(&address) {
+                requests.extend(self.create_collections(account));
+                self.minted.insert(address, 0);
+                continue;
+            }
+            for _ in 0..transactions_per_account {
+                let index = self.minted[&address];
+                requests.push(self.mint(account, index));
+                self.minted.insert(address, index + 1);
+            }
+        }
+        requests
+    }
+}
+
+pub struct TableAndResourceGroupGeneratorCreator {
+    txn_factory: TransactionFactory,
+    num_keys_per_txn: usize,
+}
+
+impl TableAndResourceGroupGeneratorCreator {
+    pub fn new(txn_factory: TransactionFactory, num_keys_per_txn: usize) -> Self {
+        Self {
+            txn_factory,
+            num_keys_per_txn,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for TableAndResourceGroupGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(TableAndResourceGroupGenerator::new(
+            self.txn_factory.clone(),
+            self.num_keys_per_txn,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_cached_packages::{
+        aptos_token_objects_sdk_builder::EntryFunctionCall as TokenObjectsCall,
+        aptos_token_sdk_builder::EntryFunctionCall as TokenCall,
+    };
+    use aptos_sdk::types::{chain_id::ChainId, transaction::TransactionPayload};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn module_and_function(txn: &SignedTransaction) -> (AccountAddress, String) {
+        match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => (
+                *entry_function.module().address(),
+                format!(
+                    "{}::{}",
+                    entry_function.module().name(),
+                    entry_function.function()
+                ),
+            ),
+            _ => panic!("Unexpected payload"),
+        }
+    }
+
+    #[test]
+    fn test_mints_into_tables_and_resource_groups() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut account = LocalAccount::generate(&mut rng);
+        let mut generator =
+            TableAndResourceGroupGenerator::new(TransactionFactory::new(ChainId::test()), 7);
+
+        let txns = generator.generate_transactions(vec![&mut account], 4);
+        let functions: Vec<_> = txns.iter().map(module_and_function).collect();
+        let address = |literal| AccountAddress::from_hex_literal(literal).unwrap();
+        assert_eq!(functions, vec![
+            (address("0x3"), "token::create_collection_script".to_string()),
+            (address("0x4"), "aptos_token::create_collection".to_string()),
+        ]);
+
+        let txns = generator.generate_transactions(vec![&mut account], 4);
+        assert_eq!(txns.len(), 4);
+        for (i, txn) in txns.iter().enumerate() {
+            if i % 2 == 0 {
+                // Token v1 data lives in tables.
+                match TokenCall::decode(txn.payload()) {
+                    Some(TokenCall::TokenCreateTokenScript {
+                        property_keys,
+                        property_values,
+                        property_types,
+                        ..
+                    }) => {
+                        assert_eq!(property_keys.len(), 7);
+                        assert_eq!(property_values.len(), 7);
+                        assert_eq!(property_types.len(), 7);
+                    },
+                    call => panic!("Unexpected call: {:?}", call),
+                }
+            } else {
+                // Token objects live in resource groups.
+                match TokenObjectsCall::decode(txn.payload()) {
+                    Some(TokenObjectsCall::AptosTokenMint {
+                        property_keys,
+                        property_values,
+                        property_types,
+                        ..
+                    }) => {
+                        assert_eq!(property_keys.len(), 7);
+                        assert_eq!(property_values.len(), 7);
+                        assert_eq!(property_types.len(), 7);
+                    },
+                    call => panic!("Unexpected call: {:?}", call),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generators and executors shared by the tests of the transaction generators.
+
+use crate::{TransactionExecutor, TransactionGenerator, TransactionGeneratorCreator};
+use anyhow::Result;
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{chain_id::ChainId, transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use std::sync::atomic::AtomicUsize;
+
+/// Generates transfers of each account to itself, with `gas_unit_price` as gas unit price, which
+/// can tag the transactions with the generator they come from.
+pub(crate) struct TransferToSelfGenerator {
+    txn_factory: TransactionFactory,
+}
+
+impl TransferToSelfGenerator {
+    pub(crate) fn new(gas_unit_price: u64) -> Self {
+        Self {
+            txn_factory: TransactionFactory::new(ChainId::test())
+                .with_gas_unit_price(gas_unit_price),
+        }
+    }
+}
+
+impl TransactionGenerator for TransferToSelfGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut txns = Vec::new();
+        for account in accounts {
+            for _ in 0..transactions_per_account {
+                let payload = aptos_stdlib::aptos_coin_transfer(account.address(), 1);
+                txns.push(account.sign_with_transaction_builder(self.txn_factory.payload(payload)));
+            }
+        }
+        txns
+    }
+}
+
+/// Creates `TransferToSelfGenerator`s with `gas_unit_price` as gas unit price.
+pub(crate) struct TransferToSelfGeneratorCreator {
+    gas_unit_price: u64,
+}
+
+impl TransferToSelfGeneratorCreator {
+    pub(crate) fn new(gas_unit_price: u64) -> Self {
+        Self { gas_unit_price }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for TransferToSelfGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(TransferToSelfGenerator::new(self.gas_unit_price))
+    }
+}
+
+/// Executor handing the submitted transactions to `execute` instead of sending them, e.g. to
+/// record or fail them. All the accounts have a zero balance and sequence number.
+pub(crate) struct MockExecutor<F> {
+    execute: F,
+}
+
+impl<F> MockExecutor<F>
+where
+    F: Fn(&[SignedTransaction]) -> Result<()> + Send + Sync,
+{
+    pub(crate) fn new(execute: F) -> Self {
+        Self { execute }
+    }
+}
+
+#[async_trait]
+impl<F> TransactionExecutor for MockExecutor<F>
+where
+    F: Fn(&[SignedTransaction]) -> Result<()> + Send + Sync,
+{
+    async fn get_account_balance(&self, _account_address: AccountAddress) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn query_sequence_number(&self, _account_address: AccountAddress) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn execute_transactions(&self, txns: &[SignedTransaction]) -> Result<()> {
+        (self.execute)(txns)
+    }
+
+    async fn execute_transactions_with_counter(
+        &self,
+        txns: &[SignedTransaction],
+        _failure_counter: &[AtomicUsize],
+    ) -> Result<()> {
+        self.execute_transactions(txns).await
+    }
+}
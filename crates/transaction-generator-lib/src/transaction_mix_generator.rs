@@ -63,17 +63,110 @@ impl TransactionGenerator for PhasedTxnMixGenerator {
     }
 }
 
+/// Mixes the transactions of several generators within a batch: every transaction of every
+/// account is assigned to a generator picked at random, with a probability proportional to its
+/// weight. Each picked generator is then asked once for all the transactions of the account
+/// assigned to it, so that generators with setup transactions or state per account see the same
+/// requests as when used alone, and may return more or fewer transactions than requested.
+pub struct MixedGenerator {
+    rng: StdRng,
+    txn_mix: Vec<(Box<dyn TransactionGenerator>, usize)>,
+    total_weight: usize,
+}
+
+impl MixedGenerator {
+    pub fn new(rng: StdRng, txn_mix: Vec<(Box<dyn TransactionGenerator>, usize)>) -> Self {
+        let total_weight = txn_mix.iter().map(|(_, weight)| weight).sum();
+        assert!(total_weight > 0, "Total weight of the mix must be positive");
+        Self {
+            rng,
+            txn_mix,
+            total_weight,
+        }
+    }
+
+    fn pick_generator(&mut self) -> usize {
+        let mut picked = self.rng.gen_range(0, self.total_weight);
+        for (index, (_, weight)) in self.txn_mix.iter().enumerate() {
+            if picked < *weight {
+                return index;
+            }
+            picked -= *weight;
+        }
+        unreachable!("Picked a weight past the total weight of the mix");
+    }
+}
+
+impl TransactionGenerator for MixedGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut requests = Vec::with_capacity(accounts.len() * transactions_per_account);
+        for account in accounts {
+            let mut counts = vec![0; self.txn_mix.len()];
+            for _ in 0..transactions_per_account {
+                counts[self.pick_generator()] += 1;
+            }
+            for ((gen, _), count) in self.txn_mix.iter_mut().zip(counts) {
+                if count > 0 {
+                    requests.append(&mut gen.generate_transactions(vec![&mut *account], count));
+                }
+            }
+        }
+        requests
+    }
+}
+
+pub struct MixedGeneratorCreator {
+    rng: StdRng,
+    txn_mix_creators: Vec<(Box<dyn TransactionGeneratorCreator>, usize)>,
+}
+
+impl MixedGeneratorCreator {
+    pub fn new(
+        rng: StdRng,
+        txn_mix_creators: Vec<(Box<dyn TransactionGeneratorCreator>, usize)>,
+    ) -> Self {
+        Self {
+            rng,
+            txn_mix_creators,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for MixedGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        let mut txn_mix = Vec::<(Box<dyn TransactionGenerator>, usize)>::new();
+        for (generator_creator, weight) in self.txn_mix_creators.iter_mut() {
+            txn_mix.push((
+                generator_creator.create_transaction_generator().await,
+                *weight,
+            ));
+        }
+        Box::new(MixedGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            txn_mix,
+        ))
+    }
+}
+
 pub struct PhasedTxnMixGeneratorCreator {
+    rng: StdRng,
     txn_mix_per_phase_creators: Vec<Vec<(Box<dyn TransactionGeneratorCreator>, usize)>>,
     phase: Arc<AtomicUsize>,
 }
 
 impl PhasedTxnMixGeneratorCreator {
     pub fn new(
+        rng: StdRng,
         txn_mix_per_phase_creators: Vec<Vec<(Box<dyn TransactionGeneratorCreator>, usize)>>,
         phase: Arc<AtomicUsize>,
     ) -> Self {
         Self {
+            rng,
             txn_mix_per_phase_creators,
             phase,
         }
@@ -96,9 +189,100 @@ impl TransactionGeneratorCreator for PhasedTxnMixGeneratorCreator {
         }
 
         Box::new(PhasedTxnMixGenerator::new(
-            StdRng::from_entropy(),
+            StdRng::from_rng(&mut self.rng).unwrap(),
             txn_mix_per_phase,
             self.phase.clone(),
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TransferToSelfGenerator;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_mixed_generator_ratios() {
+        let weights = [(100, 70), (200, 20), (300, 10)];
+        let txn_mix = weights
+            .iter()
+            .map(|(tag, weight)| {
+                let gen: Box<dyn TransactionGenerator> =
+                    Box::new(TransferToSelfGenerator::new(*tag));
+                (gen, *weight)
+            })
+            .collect();
+        let mut generator = MixedGenerator::new(StdRng::seed_from_u64(0), txn_mix);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 1_000);
+        // Same layout as a single generator: the transactions of each account, in order.
+        assert_eq!(txns.len(), 10_000);
+        for (i, txn) in txns.iter().enumerate() {
+            assert_eq!(txn.sender(), accounts[i / 1_000].address());
+            assert_eq!(txn.sequence_number(), (i % 1_000) as u64);
+        }
+        for (tag, weight) in weights {
+            let count = txns
+                .iter()
+                .filter(|txn| txn.gas_unit_price() == tag)
+                .count();
+            let ratio = count as f64 / txns.len() as f64;
+            assert!(
+                (ratio - weight as f64 / 100.0).abs() < 0.02,
+                "Ratio {} of generator {} too far from its weight {}",
+                ratio,
+                tag,
+                weight
+            );
+        }
+    }
+
+    /// Records the number of transactions requested for each account, and returns none.
+    struct RecordingGenerator {
+        requests: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl TransactionGenerator for RecordingGenerator {
+        fn generate_transactions(
+            &mut self,
+            accounts: Vec<&mut LocalAccount>,
+            transactions_per_account: usize,
+        ) -> Vec<SignedTransaction> {
+            assert_eq!(accounts.len(), 1);
+            self.requests.lock().unwrap().push(transactions_per_account);
+            vec![]
+        }
+    }
+
+    #[test]
+    fn test_mixed_generator_requests_per_account() {
+        let requests: Vec<Arc<Mutex<Vec<usize>>>> = (0..2).map(|_| Default::default()).collect();
+        let txn_mix = requests
+            .iter()
+            .map(|requests| {
+                let gen: Box<dyn TransactionGenerator> = Box::new(RecordingGenerator {
+                    requests: requests.clone(),
+                });
+                (gen, 1)
+            })
+            .collect();
+        let mut generator = MixedGenerator::new(StdRng::seed_from_u64(0), txn_mix);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut accounts: Vec<_> = (0..10).map(|_| LocalAccount::generate(&mut rng)).collect();
+
+        // Generators are asked at most once per account, for all the transactions they got.
+        let txns = generator.generate_transactions(accounts.iter_mut().collect(), 100);
+        assert!(txns.is_empty());
+        let requests: Vec<_> = requests
+            .iter()
+            .map(|requests| requests.lock().unwrap().clone())
+            .collect();
+        assert!(requests.iter().all(|requests| requests.len() <= 10));
+        let total: usize = requests.iter().flatten().sum();
+        assert_eq!(total, 1_000);
+        assert!(requests.iter().flatten().any(|count| *count > 1));
+    }
+}
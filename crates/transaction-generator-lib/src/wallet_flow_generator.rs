@@ -0,0 +1,192 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+use crate::{TransactionGenerator, TransactionGeneratorCreator, SEND_AMOUNT};
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::HashMap;
+
+/// Position of a wallet in the scripted flow. Wallets move forward by one stage
+/// on every `generate_transactions` call of the account that owns them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WalletStage {
+    /// Wallet account was created on chain by its owner.
+    Created,
+    /// Wallet received funds from its owner.
+    Funded,
+    /// Wallet sent a transfer back to its owner.
+    Transferred,
+    /// Wallet is done with the script, and doesn't submit anything. The next call
+    /// for the owner starts a new wallet.
+    Idle,
+}
+
+struct Wallet {
+    account: LocalAccount,
+    stage: WalletStage,
+}
+
+/// Emulates wallet flows: each account passed in owns a wallet which goes through
+/// create, fund, transfer and idle stages, at most one transaction per call.
+pub struct WalletFlowGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    funding_amount: u64,
+    wallets: HashMap<AccountAddress, Wallet>,
+}
+
+impl WalletFlowGenerator {
+    pub fn new(rng: StdRng, txn_factory: TransactionFactory, funding_amount: u64) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            funding_amount,
+            wallets: HashMap::new(),
+        }
+    }
+
+    /// Returns the stage of the wallet owned by `owner`, if there is one in progress.
+    pub fn wallet_stage(&self, owner: &AccountAddress) -> Option<WalletStage> {
+        self.wallets.get(owner).map(|wallet| wallet.stage)
+    }
+
+    fn advance(&mut self, owner: &mut LocalAccount) -> Option<SignedTransaction> {
+        let owner_address = owner.address();
+        match self.wallets.get(&owner_address).map(|wallet| wallet.stage) {
+            None | Some(WalletStage::Idle) => {
+                let account = LocalAccount::generate(&mut self.rng);
+                let txn = owner.sign_with_transaction_builder(self.txn_factory.payload(
+                    aptos_stdlib::aptos_account_create_account(account.address()),
+                ));
+                self.wallets.insert(owner_address, Wallet {
+                    account,
+                    stage: WalletStage::Created,
+                });
+                Some(txn)
+            },
+            Some(WalletStage::Created) => {
+                let wallet = self.wallets.get_mut(&owner_address).unwrap();
+                wallet.stage = WalletStage::Funded;
+                Some(
+                    owner.sign_with_transaction_builder(self.txn_factory.payload(
+                        aptos_stdlib::aptos_coin_transfer(
+                            wallet.account.address(),
+                            self.funding_amount,
+                        ),
+                    )),
+                )
+            },
+            Some(WalletStage::Funded) => {
+                let wallet = self.wallets.get_mut(&owner_address).unwrap();
+                wallet.stage = WalletStage::Transferred;
+                Some(
+                    wallet
+                        .account
+                        .sign_with_transaction_builder(self.txn_factory.payload(
+                            aptos_stdlib::aptos_coin_transfer(owner_address, SEND_AMOUNT),
+                        )),
+                )
+            },
+            Some(WalletStage::Transferred) => {
+                self.wallets.get_mut(&owner_address).unwrap().stage = WalletStage::Idle;
+                None
+            },
+        }
+    }
+}
+
+impl TransactionGenerator for WalletFlowGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        _transactions_per_account: usize,
+    ) -> Vec<SignedTransaction> {
+        accounts
+            .into_iter()
+            .filter_map(|account| self.advance(account))
+            .collect()
+    }
+}
+
+pub struct WalletFlowGeneratorCreator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    funding_amount: u64,
+}
+
+impl WalletFlowGeneratorCreator {
+    pub fn new(rng: StdRng, txn_factory: TransactionFactory, funding_amount: u64) -> Self {
+        Self {
+            rng,
+            txn_factory,
+            funding_amount,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionGeneratorCreator for WalletFlowGeneratorCreator {
+    async fn create_transaction_generator(&mut self) -> Box<dyn TransactionGenerator> {
+        Box::new(WalletFlowGenerator::new(
+            StdRng::from_rng(&mut self.rng).unwrap(),
+            self.txn_factory.clone(),
+            self.funding_amount,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_sdk::types::{chain_id::ChainId, transaction::TransactionPayload};
+
+    fn entry_function_name(txn: &SignedTransaction) -> String {
+        match txn.payload() {
+            TransactionPayload::EntryFunction(entry_function) => format!(
+                "{}::{}",
+                entry_function.module().name(),
+                entry_function.function()
+            ),
+            _ => panic!("Unexpected payload"),
+        }
+    }
+
+    #[test]
+    fn test_wallets_progress_through_script() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut owners: Vec<_> = (0..2).map(|_| LocalAccount::generate(&mut rng)).collect();
+        let mut generator =
+            WalletFlowGenerator::new(rng, TransactionFactory::new(ChainId::test()), 100);
+
+        let expected = [
+            (WalletStage::Created, Some("aptos_account::create_account")),
+            (WalletStage::Funded, Some("coin::transfer")),
+            (WalletStage::Transferred, Some("coin::transfer")),
+            (WalletStage::Idle, None),
+            (WalletStage::Created, Some("aptos_account::create_account")),
+        ];
+        for (stage, function) in expected {
+            let txns = generator.generate_transactions(owners.iter_mut().collect(), 1);
+            for owner in &owners {
+                assert_eq!(generator.wallet_stage(&owner.address()), Some(stage));
+            }
+            assert_eq!(
+                txns.len(),
+                if function.is_some() { owners.len() } else { 0 }
+            );
+            for txn in &txns {
+                assert_eq!(Some(entry_function_name(txn).as_str()), function);
+            }
+            if stage == WalletStage::Transferred {
+                // Transfers back to the owner are signed by the wallets.
+                assert!(txns
+                    .iter()
+                    .all(|txn| owners.iter().all(|owner| owner.address() != txn.sender())));
+            }
+        }
+    }
+}
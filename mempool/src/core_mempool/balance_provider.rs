@@ -0,0 +1,33 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_storage_interface::{state_view::LatestDbStateCheckpointView, DbReader};
+use aptos_types::account_address::AccountAddress;
+use aptos_vm_validator::vm_validator::get_account_balance;
+use std::sync::Arc;
+
+/// Reports the balance of accounts, which Mempool checks the max fee of transactions against
+/// at admission.
+pub trait BalanceProvider: Send + Sync {
+    /// Returns the balance of `address`, or None if it's unknown, in which case the transaction
+    /// is admitted.
+    fn balance(&self, address: &AccountAddress) -> Option<u64>;
+}
+
+/// Reads the balance from the latest state checkpoint in storage.
+pub(crate) struct DbBalanceProvider {
+    db: Arc<dyn DbReader>,
+}
+
+impl DbBalanceProvider {
+    pub(crate) fn new(db: Arc<dyn DbReader>) -> Self {
+        Self { db }
+    }
+}
+
+impl BalanceProvider for DbBalanceProvider {
+    fn balance(&self, address: &AccountAddress) -> Option<u64> {
+        let state_view = self.db.latest_state_checkpoint_view().ok()?;
+        get_account_balance(&state_view, *address).ok().flatten()
+    }
+}
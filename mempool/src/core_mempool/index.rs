@@ -11,7 +11,11 @@ use crate::{
 };
 use aptos_logger::prelude::*;
 use aptos_types::account_address::AccountAddress;
-use rand::seq::SliceRandom;
+use rand::{
+    rngs::StdRng,
+    seq::{IteratorRandom, SliceRandom},
+    SeedableRng,
+};
 use std::{
     cmp::Ordering,
     collections::{btree_set::Iter, BTreeMap, BTreeSet, HashMap},
@@ -30,14 +34,16 @@ pub type AccountTransactions = BTreeMap<u64, MempoolTransaction>;
 /// Instead we use `OrderedQueueKey` - logical reference to the transaction in the main store.
 pub struct PriorityIndex {
     data: BTreeSet<OrderedQueueKey>,
+    sequence_number_penalty_pct: u64,
 }
 
 pub type PriorityQueueIter<'a> = Rev<Iter<'a, OrderedQueueKey>>;
 
 impl PriorityIndex {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(sequence_number_penalty_pct: u64) -> Self {
         Self {
             data: BTreeSet::new(),
+            sequence_number_penalty_pct,
         }
     }
 
@@ -53,9 +59,41 @@ impl PriorityIndex {
         self.data.contains(&self.make_key(txn))
     }
 
+    /// Sets the sequence number of the sender of `txn` to `account_sequence_number`, re-keying
+    /// `txn` if it is in the index, as its score depends on it.
+    pub(crate) fn update_account_sequence_number(
+        &mut self,
+        txn: &mut MempoolTransaction,
+        account_sequence_number: u64,
+    ) {
+        let rekey = self.sequence_number_penalty_pct > 0 && self.data.remove(&self.make_key(txn));
+        txn.sequence_info.account_sequence_number = account_sequence_number;
+        if rekey {
+            self.data.insert(self.make_key(txn));
+        }
+    }
+
+    /// Returns the score `txn` is ordered by: its ranking score, lowered by
+    /// `sequence_number_penalty_pct` percent for every sequence number it is ahead of the
+    /// sequence number of its sender, i.e. divided by `1 + penalty_pct / 100 * distance`, so
+    /// that the far-out txns of an account submitting long runs yield to the fresh txns of other
+    /// accounts.
+    pub(crate) fn ranking_score(&self, txn: &MempoolTransaction) -> u64 {
+        if self.sequence_number_penalty_pct == 0 {
+            return txn.ranking_score;
+        }
+        let distance = txn
+            .sequence_info
+            .transaction_sequence_number
+            .saturating_sub(txn.sequence_info.account_sequence_number)
+            as u128;
+        (txn.ranking_score as u128 * 100
+            / (100 + self.sequence_number_penalty_pct as u128 * distance)) as u64
+    }
+
     fn make_key(&self, txn: &MempoolTransaction) -> OrderedQueueKey {
         OrderedQueueKey {
-            gas_ranking_score: txn.ranking_score,
+            gas_ranking_score: self.ranking_score(txn),
             expiration_time: txn.expiration_time,
             address: txn.get_sender(),
             sequence_number: txn.sequence_info,
@@ -229,6 +267,14 @@ impl TimelineIndex {
         batch
     }
 
+    /// Returns the number of transactions in the timeline since <timeline_id>.
+    #[cfg(test)]
+    pub(crate) fn count_since(&self, timeline_id: u64) -> usize {
+        self.timeline
+            .range((Bound::Excluded(timeline_id), Bound::Unbounded))
+            .count()
+    }
+
     /// Read transactions from the timeline from `start_id` (exclusive) to `end_id` (inclusive).
     pub(crate) fn timeline_range(&self, start_id: u64, end_id: u64) -> Vec<(AccountAddress, u64)> {
         self.timeline
@@ -259,6 +305,12 @@ impl TimelineIndex {
     pub(crate) fn size(&self) -> usize {
         self.timeline.len()
     }
+
+    /// Returns the id of the last transaction inserted into the timeline, or 0 if none was.
+    #[cfg(test)]
+    pub(crate) fn current_id(&self) -> u64 {
+        self.timeline_id - 1
+    }
 }
 
 pub struct MultiBucketTimelineIndex {
@@ -325,6 +377,18 @@ impl MultiBucketTimelineIndex {
         returned.iter().rev().cloned().collect()
     }
 
+    /// Returns the number of transactions in all timelines since <timeline_id>.
+    #[cfg(test)]
+    pub(crate) fn count_since(&self, timeline_id: &MultiBucketTimelineIndexIds) -> usize {
+        assert!(timeline_id.id_per_bucket.len() == self.bucket_mins.len());
+
+        self.timelines
+            .iter()
+            .zip(timeline_id.id_per_bucket.iter())
+            .map(|(timeline, &timeline_id)| timeline.count_since(timeline_id))
+            .sum()
+    }
+
     /// Read transactions from the timeline from `start_id` (exclusive) to `end_id` (inclusive).
     pub(crate) fn timeline_range(
         &self,
@@ -340,6 +404,17 @@ impl MultiBucketTimelineIndex {
         all_txns
     }
 
+    /// Returns the id of the last transaction inserted into each timeline, i.e. a cursor past all
+    /// the transactions of the timelines so far.
+    #[cfg(test)]
+    pub(crate) fn current_timeline_index(&self) -> MultiBucketTimelineIndexIds {
+        self.timelines
+            .iter()
+            .map(TimelineIndex::current_id)
+            .collect::<Vec<_>>()
+            .into()
+    }
+
     #[inline]
     fn get_timeline(&mut self, ranking_score: u64) -> &mut TimelineIndex {
         let index = self
@@ -393,14 +468,17 @@ pub struct ParkingLotIndex {
     data: Vec<(AccountAddress, BTreeSet<u64>)>,
     account_indices: HashMap<AccountAddress, usize>,
     size: usize,
+    // source of randomness for `get_poppable`, seeded for reproducible selection if configured
+    rng: StdRng,
 }
 
 impl ParkingLotIndex {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(seed: Option<u64>) -> Self {
         Self {
             data: vec![],
             account_indices: HashMap::new(),
             size: 0,
+            rng: seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
         }
     }
 
@@ -464,13 +542,40 @@ impl ParkingLotIndex {
     }
 
     /// Returns a random "non-ready" transaction (with highest sequence number for that account).
-    pub(crate) fn get_poppable(&self) -> Option<TxnPointer> {
-        let mut rng = rand::thread_rng();
+    pub(crate) fn get_poppable(&mut self) -> Option<TxnPointer> {
         self.data
-            .choose(&mut rng)
+            .choose(&mut self.rng)
             .and_then(|(sender, txns)| txns.iter().rev().next().map(|seq_num| (*sender, *seq_num)))
     }
 
+    /// Returns a random "non-ready" transaction (with highest sequence number for that account)
+    /// among the ones satisfying `predicate`.
+    pub(crate) fn get_poppable_if<F>(&mut self, predicate: F) -> Option<TxnPointer>
+    where
+        F: Fn(&TxnPointer) -> bool,
+    {
+        self.data
+            .iter()
+            .filter_map(|(sender, txns)| txns.iter().rev().next().map(|seq_num| (*sender, *seq_num)))
+            .filter(|pointer| predicate(pointer))
+            .choose(&mut self.rng)
+    }
+
+    /// Returns the (sender, sequence number) of every parked transaction.
+    #[cfg(test)]
+    pub(crate) fn get_all(&self) -> Vec<TxnPointer> {
+        self.data
+            .iter()
+            .flat_map(|(sender, txns)| txns.iter().map(move |seq_num| (*sender, *seq_num)))
+            .collect()
+    }
+
+    /// Returns the lowest and highest parked sequence numbers of `account`, if it has any.
+    pub(crate) fn get_seq_num_range(&self, account: &AccountAddress) -> Option<(u64, u64)> {
+        let txns = &self.data[*self.account_indices.get(account)?].1;
+        Some((*txns.iter().next()?, *txns.iter().next_back()?))
+    }
+
     pub(crate) fn size(&self) -> usize {
         self.size
     }
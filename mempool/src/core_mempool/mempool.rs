@@ -6,8 +6,10 @@
 //! agreed upon.
 use crate::{
     core_mempool::{
-        index::TxnPointer,
-        transaction::{MempoolTransaction, TimelineState},
+        balance_provider::BalanceProvider,
+        index::{OrderedQueueKey, TxnPointer},
+        ranking_strategy::{create_ranking_strategy, RankingStrategy},
+        transaction::{EvictionEvent, MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
     },
     counters,
@@ -15,38 +17,247 @@ use crate::{
     logging::{LogEntry, LogSchema, TxnsLog},
     shared_mempool::types::MultiBucketTimelineIndexIds,
 };
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{EpochChangePolicy, NodeConfig};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
+use aptos_rate_limiter::rate_limit::Bucket;
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     transaction::SignedTransaction,
 };
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::{
-    collections::HashSet,
-    time::{Duration, SystemTime},
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
+#[cfg(test)]
+use std::ops::Range;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Time after which the rate limiter of an idle sender is dropped, the refill interval of the
+/// rate limiters.
+const SENDER_RATE_LIMITER_IDLE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Position in the paginated listing of all transactions in Mempool, see `list_transactions`.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ListCursor {
+    sender: AccountAddress,
+    sequence_number: u64,
+}
+
+/// State of a transaction in Mempool, see `get_status_by_hash`.
+#[cfg(test)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransactionPoolStatus {
+    /// Whether the transaction is ready for consensus, as opposed to parked.
+    pub ready: bool,
+    /// Time since the transaction was inserted into Mempool.
+    pub age: Duration,
+    /// Score the transaction is ranked by, e.g. its gas unit price.
+    pub ranking_score: u64,
+    /// Zero-based position in the priority queue of ready transactions, if ready.
+    pub rank: Option<usize>,
+    /// If parked, the first range of sequence numbers of its sender missing before it.
+    pub blocking_gap: Option<Range<u64>>,
+}
 
 pub struct Mempool {
     // Stores the metadata of all transactions in mempool (of all states).
     transactions: TransactionStore,
 
     pub system_transaction_timeout: Duration,
+    // Upper bound (exclusive) of the random offset added to the system timeout of each txn.
+    system_transaction_timeout_jitter: Duration,
+    // Source of the system timeout offsets, seeded if `randomization_seed` is configured.
+    rng: StdRng,
+
+    // Latest epoch seen by mempool, and the policy applied to the transactions when it changes.
+    epoch: u64,
+    epoch_change_policy: EpochChangePolicy,
+
+    // Txns with a higher gas unit price are rejected.
+    max_gas_unit_price: Option<u64>,
+
+    // Derives the ranking score stored with each txn.
+    ranking_strategy: Box<dyn RankingStrategy>,
+
+    // Txns whose max fee exceeds the balance of their sender are rejected, if set.
+    balance_provider: Option<Arc<dyn BalanceProvider>>,
+
+    // Commits of txns that are already committed are ignored, if set.
+    dedup_commit_notifications: bool,
+
+    // Limits the rate txns are admitted at by `add_txn`, if set.
+    admission_rate_limiter: Option<Bucket>,
+    // Max rate txns of a single sender are admitted at, if set, and the rate limiter of each
+    // sender with the last time it was used. Idle ones are dropped by `gc`.
+    per_sender_rate_limit: Option<usize>,
+    sender_rate_limiters: HashMap<AccountAddress, (Bucket, Instant)>,
+
+    // Percentage of the ranking score of later txns of a sender inherited by its earlier ones
+    // when pulling blocks, 0 disables.
+    priority_inheritance_pct: u64,
 }
 
 impl Mempool {
     pub fn new(config: &NodeConfig) -> Self {
+        Self::load(config, vec![])
+    }
+
+    /// Creates a Mempool holding the transactions of a snapshot taken with `dump`, e.g. before
+    /// a restart. The transactions are re-inserted as if they were freshly added, except that
+    /// they keep their insertion time: they go through the capacity checks again, and the ones
+    /// that already expired, either by system TTL or by their own expiration time, are dropped.
+    pub fn load(config: &NodeConfig, txns: Vec<MempoolTransaction>) -> Self {
+        let mut mempool = Self::with_eviction_sender(config, None);
+
+        // The latest known sequence number of each account, txns may have been inserted at
+        // different points in time.
+        let mut account_sequence_numbers = HashMap::new();
+        for txn in &txns {
+            let sequence_number = account_sequence_numbers.entry(txn.get_sender()).or_insert(0);
+            *sequence_number = (*sequence_number).max(txn.sequence_info.account_sequence_number);
+        }
+
+        let now = aptos_infallible::duration_since_epoch();
+        for mut txn in txns {
+            let account_sequence_number = account_sequence_numbers[&txn.get_sender()];
+            if txn.expiration_time <= now
+                || Duration::from_secs(txn.txn.expiration_timestamp_secs()) <= now
+                || txn.sequence_info.transaction_sequence_number < account_sequence_number
+            {
+                continue;
+            }
+            txn.sequence_info.account_sequence_number = account_sequence_number;
+            // Timeline ids are local to the Mempool the txns were dumped from.
+            if let TimelineState::Ready(_) = txn.timeline_state {
+                txn.timeline_state = TimelineState::NotReady;
+            }
+            txn.was_parked = false;
+            mempool.transactions.insert(txn);
+        }
+        mempool
+    }
+
+    /// Creates an empty Mempool, which sends an `EvictionEvent` to `eviction_sender` (if set) for
+    /// every transaction evicted under capacity pressure, expired or replaced.
+    pub fn with_eviction_sender(
+        config: &NodeConfig,
+        eviction_sender: Option<UnboundedSender<EvictionEvent>>,
+    ) -> Self {
         Mempool {
-            transactions: TransactionStore::new(&config.mempool),
+            transactions: TransactionStore::new(&config.mempool, eviction_sender),
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
+            system_transaction_timeout_jitter: Duration::from_secs(
+                config.mempool.system_transaction_timeout_jitter_secs,
+            ),
+            rng: config
+                .mempool
+                .randomization_seed
+                .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            epoch: 0,
+            epoch_change_policy: config.mempool.epoch_change_policy,
+            max_gas_unit_price: config.mempool.max_gas_unit_price,
+            ranking_strategy: create_ranking_strategy(config.mempool.ranking_strategy),
+            balance_provider: None,
+            dedup_commit_notifications: config.mempool.dedup_commit_notifications,
+            admission_rate_limiter: config.mempool.max_admissions_per_sec.map(|rate| {
+                Bucket::new(
+                    "mempool_admission".to_string(),
+                    String::new(),
+                    String::new(),
+                    rate,
+                    rate,
+                    rate,
+                    None,
+                )
+            }),
+            per_sender_rate_limit: config.mempool.per_sender_rate_limit,
+            sender_rate_limiters: HashMap::new(),
+            priority_inheritance_pct: config.mempool.priority_inheritance_pct,
         }
     }
 
+    /// Checks the max fee of every transaction added from now on against the balance of its
+    /// sender reported by `balance_provider`.
+    pub(crate) fn set_balance_provider(&mut self, balance_provider: Arc<dyn BalanceProvider>) {
+        self.balance_provider = Some(balance_provider);
+    }
+
+    /// Returns a snapshot of all transactions in Mempool, both ready and parked, which can be
+    /// loaded back with `load`.
+    #[cfg(test)]
+    pub(crate) fn dump(&self) -> Vec<MempoolTransaction> {
+        self.transactions.dump()
+    }
+
     /// This function will be called once the transaction has been stored.
+    /// With `dedup_commit_notifications`, the commit of a transaction that is already committed
+    /// is ignored.
     pub(crate) fn commit_transaction(&mut self, sender: &AccountAddress, sequence_number: u64) {
+        if self.is_duplicate_commit(sender, sequence_number) {
+            return;
+        }
+        self.log_commit(sender, sequence_number);
+        self.transactions
+            .commit_transaction(sender, sequence_number);
+    }
+
+    /// Removes the `committed` transactions, e.g. the ones of a block, under a single lock of
+    /// Mempool. Same as calling `commit_transaction` for each of them, but the transactions of
+    /// each sender are removed, and the following ones unblocked, at once. With `is_rejected`,
+    /// same as calling `reject_transaction` for each of them instead.
+    pub(crate) fn remove_transactions(
+        &mut self,
+        committed: &[(AccountAddress, u64)],
+        is_rejected: bool,
+    ) {
+        if is_rejected {
+            for (sender, sequence_number) in committed {
+                if let Some(hash) = self
+                    .transactions
+                    .get_committed_hash(sender, *sequence_number)
+                {
+                    self.reject_transaction(sender, *sequence_number, &hash);
+                }
+            }
+            return;
+        }
+
+        let mut latest_sequence_numbers = HashMap::new();
+        for (sender, sequence_number) in committed {
+            if self.is_duplicate_commit(sender, *sequence_number) {
+                continue;
+            }
+            self.log_commit(sender, *sequence_number);
+            let latest = latest_sequence_numbers.entry(*sender).or_insert(*sequence_number);
+            *latest = (*latest).max(*sequence_number);
+        }
+        for (sender, sequence_number) in latest_sequence_numbers {
+            self.transactions.commit_transaction(&sender, sequence_number);
+        }
+    }
+
+    fn is_duplicate_commit(&self, sender: &AccountAddress, sequence_number: u64) -> bool {
+        if !self.dedup_commit_notifications
+            || !self.transactions.is_committed(sender, sequence_number)
+        {
+            return false;
+        }
+        trace!(
+            LogSchema::new(LogEntry::RemoveTxn).txns(TxnsLog::new_txn(*sender, sequence_number)),
+            "Ignoring duplicate commit notification"
+        );
+        true
+    }
+
+    fn log_commit(&self, sender: &AccountAddress, sequence_number: u64) {
         trace!(
             LogSchema::new(LogEntry::RemoveTxn).txns(TxnsLog::new_txn(*sender, sequence_number)),
             is_rejected = false
@@ -60,9 +271,6 @@ impl Mempool {
                 ranking_score,
             );
         }
-
-        self.transactions
-            .commit_transaction(sender, sequence_number);
     }
 
     pub(crate) fn reject_transaction(
@@ -109,14 +317,108 @@ impl Mempool {
         self.transactions.get_by_hash(hash)
     }
 
+    /// Returns the state of the transaction with `hash`, e.g. for a status endpoint, or None if
+    /// it is not in Mempool.
+    #[cfg(test)]
+    pub(crate) fn get_status_by_hash(&self, hash: HashValue) -> Option<TransactionPoolStatus> {
+        let txn = self.transactions.get_mempool_txn_by_hash(hash)?;
+        let sender = txn.get_sender();
+        let sequence_number = txn.sequence_info.transaction_sequence_number;
+        let ready = self.transactions.is_ready(txn);
+        let (rank, blocking_gap) = if ready {
+            (self.estimate_position(&sender, sequence_number), None)
+        } else {
+            (None, self.transactions.blocking_gap(&sender, sequence_number))
+        };
+        Some(TransactionPoolStatus {
+            ready,
+            age: SystemTime::now()
+                .duration_since(txn.insertion_time)
+                .unwrap_or_default(),
+            ranking_score: txn.ranking_score,
+            rank,
+            blocking_gap,
+        })
+    }
+
+    /// Returns whether the transaction of `sender` with `sequence_number` is in Mempool, either
+    /// ready or in the parking lot.
+    #[cfg(test)]
+    pub(crate) fn contains(&self, sender: &AccountAddress, sequence_number: u64) -> bool {
+        self.transactions.contains(sender, sequence_number)
+    }
+
+    /// Returns the sequence number `sender` should use for its next transaction, one past its
+    /// highest contiguous ready transaction in Mempool.
+    #[cfg(test)]
+    pub(crate) fn next_sequence_number(&self, sender: &AccountAddress) -> Option<u64> {
+        self.transactions.next_sequence_number(sender)
+    }
+
+    /// Returns the zero-based position of the transaction of `sender` with `sequence_number` in
+    /// the priority queue of ready transactions, or None if it is parked or not in Mempool.
+    /// Multiplied by the observed block size, this roughly estimates when it will be included.
+    #[cfg(test)]
+    pub(crate) fn estimate_position(
+        &self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<usize> {
+        self.transactions.iter_queue().position(|key| {
+            key.address == *sender
+                && key.sequence_number.transaction_sequence_number == sequence_number
+        })
+    }
+
+    /// Returns all transactions of `address` in Mempool, both ready and parked, ordered by
+    /// sequence number.
+    #[cfg(test)]
+    pub(crate) fn get_transactions_by_sender(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<SignedTransaction> {
+        self.transactions.get_by_sender(address)
+    }
+
     /// Used to add a transaction to the Mempool.
     /// Performs basic validation: checks account's sequence number.
+    /// Rejects the transaction as RateLimited if admissions exceed `max_admissions_per_sec`.
     pub(crate) fn add_txn(
         &mut self,
         txn: SignedTransaction,
         ranking_score: u64,
         db_sequence_number: u64,
         timeline_state: TimelineState,
+    ) -> MempoolStatus {
+        if let Some(rate_limiter) = &mut self.admission_rate_limiter {
+            if rate_limiter.acquire_all_tokens(1).is_err() {
+                return MempoolStatus::new(MempoolStatusCode::RateLimited).with_message(
+                    "Mempool admits transactions faster than its configured rate".to_string(),
+                );
+            }
+        }
+        if !self.acquire_sender_rate_limit(txn.sender()) {
+            return MempoolStatus::new(MempoolStatusCode::RateLimited).with_message(format!(
+                "Sender {} submits transactions faster than the configured rate",
+                txn.sender(),
+            ));
+        }
+        self.add_txn_inserted_at(
+            txn,
+            ranking_score,
+            db_sequence_number,
+            timeline_state,
+            SystemTime::now(),
+        )
+    }
+
+    fn add_txn_inserted_at(
+        &mut self,
+        txn: SignedTransaction,
+        ranking_score: u64,
+        db_sequence_number: u64,
+        timeline_state: TimelineState,
+        insertion_time: SystemTime,
     ) -> MempoolStatus {
         trace!(
             LogSchema::new(LogEntry::AddTxn)
@@ -133,18 +435,45 @@ impl Mempool {
             ));
         }
 
-        let now = SystemTime::now();
-        let expiration_time =
-            aptos_infallible::duration_since_epoch_at(&now) + self.system_transaction_timeout;
+        if let Some(max_gas_unit_price) = self.max_gas_unit_price {
+            if txn.gas_unit_price() > max_gas_unit_price {
+                return MempoolStatus::new(MempoolStatusCode::GasUnitPriceTooHigh).with_message(
+                    format!(
+                        "transaction gas unit price is {}, max gas unit price is {}",
+                        txn.gas_unit_price(),
+                        max_gas_unit_price,
+                    ),
+                );
+            }
+        }
 
-        let txn_info = MempoolTransaction::new(
+        if let Some(balance_provider) = &self.balance_provider {
+            if let Some(balance) = balance_provider.balance(&txn.sender()) {
+                let max_fee = txn.gas_unit_price() as u128 * txn.max_gas_amount() as u128;
+                if max_fee > balance as u128 {
+                    return MempoolStatus::new(MempoolStatusCode::InsufficientBalance)
+                        .with_message(format!(
+                            "transaction max fee is {}, balance of sender is {}",
+                            max_fee, balance,
+                        ));
+                }
+            }
+        }
+
+        let expiration_time = aptos_infallible::duration_since_epoch_at(&insertion_time)
+            + self.system_transaction_timeout
+            + self.system_transaction_timeout_offset();
+
+        let mut txn_info = MempoolTransaction::new(
             txn,
             expiration_time,
             ranking_score,
             timeline_state,
             db_sequence_number,
-            now,
+            insertion_time,
         );
+        let ranking_score = self.ranking_strategy.ranking_score(&txn_info);
+        txn_info.ranking_score = ranking_score;
 
         let status = self.transactions.insert(txn_info);
         counters::core_mempool_txn_ranking_score(
@@ -156,18 +485,178 @@ impl Mempool {
         status
     }
 
-    /// Fetches next block of transactions for consensus.
-    /// `batch_size` - size of requested block.
-    /// `seen_txns` - transactions that were sent to Consensus but were not committed yet,
-    ///  mempool should filter out such transactions.
+    /// Returns whether `sender` can submit one more transaction under `per_sender_rate_limit`.
+    fn acquire_sender_rate_limit(&mut self, sender: AccountAddress) -> bool {
+        let rate = match self.per_sender_rate_limit {
+            Some(rate) => rate,
+            None => return true,
+        };
+        let (rate_limiter, last_used) = self
+            .sender_rate_limiters
+            .entry(sender)
+            .or_insert_with(|| {
+                let bucket = Bucket::new(
+                    "mempool_sender_admission".to_string(),
+                    String::new(),
+                    sender.to_string(),
+                    rate,
+                    rate,
+                    rate,
+                    None,
+                );
+                (bucket, Instant::now())
+            });
+        *last_used = Instant::now();
+        rate_limiter.acquire_all_tokens(1).is_ok()
+    }
+
+    /// Returns a random offset in [0, `system_transaction_timeout_jitter`) to add to the system
+    /// timeout of a new transaction, spreading the gc of the transactions inserted together.
+    fn system_transaction_timeout_offset(&mut self) -> Duration {
+        let jitter_ms = self.system_transaction_timeout_jitter.as_millis() as u64;
+        if jitter_ms == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(self.rng.gen_range(0, jitter_ms))
+    }
+
+    /// Returns the transactions in the parking lot, with the time they were inserted at, so they
+    /// can be moved to another Mempool with `import_parking_lot`.
+    pub(crate) fn export_parking_lot(&self) -> Vec<(SignedTransaction, SystemTime)> {
+        self.transactions.get_parked()
+    }
+
+    /// Returns the (sender, sequence number) of the transactions in the parking lot, i.e. the
+    /// ones waiting on a missing predecessor.
+    #[cfg(test)]
+    pub(crate) fn get_parking_lot_transactions(&self) -> Vec<TxnPointer> {
+        self.transactions.get_parking_lot_transactions()
+    }
+
+    /// Returns the (sender, sequence number) of the ready transactions and of the parked ones,
+    /// in a single pass over Mempool.
+    #[cfg(test)]
+    pub(crate) fn classify_all(&self) -> (Vec<TxnPointer>, Vec<TxnPointer>) {
+        self.transactions.classify_all()
+    }
+
+    /// Inserts transactions exported with `export_parking_lot`, keeping their insertion time.
+    /// They are ranked by gas price and go through the usual checks of `add_txn`, so they are
+    /// only ready if they are sequential with the transactions of their sender in Mempool.
+    pub(crate) fn import_parking_lot(
+        &mut self,
+        txns: Vec<(SignedTransaction, SystemTime)>,
+    ) -> Vec<MempoolStatus> {
+        txns.into_iter()
+            .map(|(txn, insertion_time)| {
+                let db_sequence_number = self
+                    .transactions
+                    .get_sequence_number(&txn.sender())
+                    .copied()
+                    .unwrap_or(0);
+                let ranking_score = txn.gas_unit_price();
+                self.add_txn_inserted_at(
+                    txn,
+                    ranking_score,
+                    db_sequence_number,
+                    TimelineState::NotReady,
+                    insertion_time,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the (sender, sequence number, time in Mempool) of all transactions that have been
+    /// in Mempool for longer than `age`, oldest first.
+    #[cfg(test)]
+    pub(crate) fn transactions_older_than(
+        &self,
+        age: Duration,
+    ) -> Vec<(AccountAddress, u64, Duration)> {
+        self.transactions.transactions_older_than(age)
+    }
+
+    /// Returns how long the oldest transaction, by insertion time, has been in Mempool at `now`,
+    /// or None if Mempool is empty.
+    pub(crate) fn oldest_transaction_age(&self, now: SystemTime) -> Option<Duration> {
+        self.transactions
+            .oldest_insertion_time()
+            .map(|insertion_time| now.duration_since(insertion_time).unwrap_or_default())
+    }
+
+    /// Returns the number of ready transactions per gas price bucket. `bucket_boundaries` must be
+    /// sorted, and delimit `bucket_boundaries.len() + 1` buckets: bucket `i` counts transactions
+    /// with `bucket_boundaries[i - 1] <= gas price < bucket_boundaries[i]`, the first and the last
+    /// bucket being unbounded below and above respectively.
+    #[cfg(test)]
+    pub(crate) fn gas_price_histogram(&self, bucket_boundaries: &[u64]) -> Vec<usize> {
+        self.transactions.gas_price_histogram(bucket_boundaries)
+    }
+
+    /// Returns a page of at most `limit` transactions (both ready and parked) starting at
+    /// `cursor`, together with the cursor of the next page if there is any.
+    /// Transactions are listed in a stable order, i.e. by sender and sequence number.
+    #[cfg(test)]
+    pub(crate) fn list_transactions(
+        &self,
+        cursor: Option<ListCursor>,
+        limit: usize,
+    ) -> (Vec<SignedTransaction>, Option<ListCursor>) {
+        let (txns, has_more) = self.transactions.list_transactions(
+            cursor.map(|cursor| (cursor.sender, cursor.sequence_number)),
+            limit,
+        );
+        let next_cursor = if has_more {
+            txns.last().map(|txn| ListCursor {
+                sender: txn.sender(),
+                sequence_number: txn.sequence_number(),
+            })
+        } else {
+            None
+        };
+        (txns, next_cursor)
+    }
+
+    /// Applies the gas price updates staged within `update_batch_window_ms`, and returns the
+    /// number of transactions updated.
+    pub(crate) fn apply_pending_updates(&mut self) -> usize {
+        self.transactions.apply_pending_updates()
+    }
+
+    /// Returns the transaction `get_batch` would pick first if the transactions in `exclude` were
+    /// already pulled, i.e. the highest priority ready transaction that isn't excluded.
+    #[cfg(test)]
+    pub(crate) fn peek_next(&self, exclude: &HashSet<HashValue>) -> Option<SignedTransaction> {
+        let excluded: HashSet<TxnPointer> = exclude
+            .iter()
+            .filter_map(|hash| self.transactions.get_by_hash(*hash))
+            .map(|txn| (txn.sender(), txn.sequence_number()))
+            .collect();
+        self.transactions
+            .iter_queue()
+            .find(|txn| {
+                let tx_seq = txn.sequence_number.transaction_sequence_number;
+                !excluded.contains(&TxnPointer::from(*txn))
+                    && (self.transactions.get_sequence_number(&txn.address) == Some(&tx_seq)
+                        || (tx_seq > 0 && excluded.contains(&(txn.address, tx_seq - 1))))
+            })
+            .and_then(|txn| {
+                self.transactions
+                    .get(&txn.address, txn.sequence_number.transaction_sequence_number)
+            })
+    }
+
+    /// Returns up to `max_txns` transactions in the order they are pulled in, skipping the `seen`
+    /// ones and the ones of `exclude_senders`, together with the number of transactions walked
+    /// to select them. Selected transactions are added to `seen`.
     #[allow(clippy::explicit_counter_loop)]
-    pub(crate) fn get_batch(
+    fn select_batch(
         &self,
         max_txns: u64,
-        max_bytes: u64,
-        return_non_full: bool,
-        mut seen: HashSet<TxnPointer>,
-    ) -> Vec<SignedTransaction> {
+        seen: &mut HashSet<TxnPointer>,
+        exclude_senders: &HashSet<AccountAddress>,
+        tie_break_seed: Option<u64>,
+    ) -> (Vec<TxnPointer>, usize) {
         let mut result = vec![];
         // Helper DS. Helps to mitigate scenarios where account submits several transactions
         // with increasing gas price (e.g. user submits transactions with sequence number 1, 2
@@ -176,13 +665,11 @@ impl Mempool {
         // but can't be executed before first txn. Once observed, such txn will be saved in
         // `skipped` DS and rechecked once it's ancestor becomes available
         let mut skipped = HashSet::new();
-        let mut total_bytes = 0;
-        let seen_size = seen.len();
         let mut txn_walked = 0usize;
         // iterate over the queue of transactions based on gas price
-        'main: for txn in self.transactions.iter_queue() {
+        'main: for txn in self.inheriting_queue(tie_break_seed) {
             txn_walked += 1;
-            if seen.contains(&TxnPointer::from(txn)) {
+            if seen.contains(&TxnPointer::from(txn)) || exclude_senders.contains(&txn.address) {
                 continue;
             }
             let tx_seq = txn.sequence_number.transaction_sequence_number;
@@ -213,6 +700,239 @@ impl Mempool {
                 skipped.insert(TxnPointer::from(txn));
             }
         }
+        (result, txn_walked)
+    }
+
+    /// Returns the priority queue. If `tie_break_seed` is set, the transactions with the same
+    /// ranking score and expiration time are ordered by a hash of the seed and their hash,
+    /// instead of by sender.
+    fn ordered_queue(
+        &self,
+        tie_break_seed: Option<u64>,
+    ) -> Box<dyn Iterator<Item = &OrderedQueueKey> + '_> {
+        let mut queue = self.transactions.iter_queue().peekable();
+        let seed = match tie_break_seed {
+            Some(seed) => seed,
+            None => return Box::new(queue),
+        };
+        let tie_break_key = move |key: &OrderedQueueKey| {
+            let txn_hash = self
+                .transactions
+                .get_committed_hash(&key.address, key.sequence_number.transaction_sequence_number)
+                .unwrap_or_default();
+            HashValue::sha3_256_of(&[&seed.to_le_bytes()[..], txn_hash.as_ref()].concat())
+        };
+        let mut ties = vec![].into_iter();
+        Box::new(std::iter::from_fn(move || {
+            if let Some(key) = ties.next() {
+                return Some(key);
+            }
+            let first = queue.next()?;
+            let mut group = vec![first];
+            while let Some(key) = queue.next_if(|key| {
+                key.gas_ranking_score == first.gas_ranking_score
+                    && key.expiration_time == first.expiration_time
+            }) {
+                group.push(key);
+            }
+            group.sort_by_cached_key(|key| tie_break_key(key));
+            ties = group.into_iter();
+            ties.next()
+        }))
+    }
+
+    /// Returns the priority queue, ordered by the effective ranking scores of the transactions
+    /// with `priority_inheritance_pct`: the effective score of a transaction is the highest of
+    /// its own and `priority_inheritance_pct` of the score of each later transaction of its
+    /// sender. Transactions with the same effective score keep their order in the queue.
+    fn inheriting_queue(
+        &self,
+        tie_break_seed: Option<u64>,
+    ) -> Box<dyn Iterator<Item = &OrderedQueueKey> + '_> {
+        if self.priority_inheritance_pct == 0 {
+            return self.ordered_queue(tie_break_seed);
+        }
+        let mut queue: Vec<_> = self.ordered_queue(tie_break_seed).collect();
+        let mut txns_by_sender: HashMap<_, Vec<_>> = HashMap::new();
+        for key in &queue {
+            txns_by_sender.entry(key.address).or_default().push((
+                key.sequence_number.transaction_sequence_number,
+                key.gas_ranking_score,
+            ));
+        }
+        let mut effective_scores = HashMap::new();
+        for (address, mut txns) in txns_by_sender {
+            txns.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+            let mut inherited = 0;
+            for (sequence_number, score) in txns {
+                effective_scores.insert((address, sequence_number), score.max(inherited));
+                let inheritable =
+                    score as u128 * self.priority_inheritance_pct.min(100) as u128 / 100;
+                inherited = inherited.max(inheritable as u64);
+            }
+        }
+        queue.sort_by_key(|key| {
+            Reverse(
+                effective_scores[&(
+                    key.address,
+                    key.sequence_number.transaction_sequence_number,
+                )],
+            )
+        });
+        Box::new(queue.into_iter())
+    }
+
+    /// Returns the transactions `get_batch` would return with `return_non_full`, without any
+    /// side effects (no metrics or logs), e.g. to preview the next block.
+    #[cfg(test)]
+    pub(crate) fn peek_batch(
+        &self,
+        max_txns: u64,
+        max_bytes: u64,
+        mut exclude: HashSet<TxnPointer>,
+    ) -> Vec<SignedTransaction> {
+        let (result, _) = self.select_batch(max_txns, &mut exclude, &HashSet::new(), None);
+        let mut total_bytes = 0;
+        result
+            .into_iter()
+            .filter_map(|(address, seq)| self.transactions.get(&address, seq))
+            .take_while(|txn| {
+                total_bytes += txn.raw_txn_bytes_len();
+                total_bytes <= max_bytes as usize
+            })
+            .collect()
+    }
+
+    /// Fetches next block of transactions for consensus.
+    /// `batch_size` - size of requested block.
+    /// `seen_txns` - transactions that were sent to Consensus but were not committed yet,
+    ///  mempool should filter out such transactions.
+    /// `tie_break_seed` - if set, transactions with the same ranking score and expiration time
+    ///  are ordered by a hash of the seed and their hash instead of by sender, so that the
+    ///  order can be reproduced.
+    ///
+    /// The batch is pulled from a consistent snapshot of Mempool: it only borrows Mempool, which
+    /// callers share behind a lock held for the whole call, so transactions inserted or promoted
+    /// concurrently are only reflected in the next call.
+    pub(crate) fn get_batch(
+        &self,
+        max_txns: u64,
+        max_bytes: u64,
+        return_non_full: bool,
+        seen: HashSet<TxnPointer>,
+        tie_break_seed: Option<u64>,
+    ) -> Vec<SignedTransaction> {
+        self.get_batch_impl(
+            max_txns,
+            max_bytes,
+            return_non_full,
+            seen,
+            &HashSet::new(),
+            tie_break_seed,
+        )
+    }
+
+    /// Same as `get_batch`, but also leaves out all transactions of `exclude_senders`, e.g.
+    /// known spammers. Their transactions stay in Mempool for later blocks.
+    #[cfg(test)]
+    pub(crate) fn get_batch_excluding_senders(
+        &self,
+        max_txns: u64,
+        max_bytes: u64,
+        return_non_full: bool,
+        seen: HashSet<TxnPointer>,
+        exclude_senders: &HashSet<AccountAddress>,
+    ) -> Vec<SignedTransaction> {
+        self.get_batch_impl(
+            max_txns,
+            max_bytes,
+            return_non_full,
+            seen,
+            exclude_senders,
+            None,
+        )
+    }
+
+    /// Debug only: same as `get_batch` (returning non full batches), followed by the parked
+    /// transactions not in `seen`, by sender and sequence number, within the same limits.
+    /// Parked transactions can't be executed until their predecessors are, so the result must
+    /// never be proposed to consensus.
+    #[cfg(test)]
+    pub(crate) fn get_batch_including_parking(
+        &self,
+        max_txns: u64,
+        max_bytes: u64,
+        seen: HashSet<TxnPointer>,
+    ) -> Vec<SignedTransaction> {
+        let mut batch = self.get_batch(max_txns, max_bytes, true, seen.clone(), None);
+        let mut total_bytes: usize = batch.iter().map(|txn| txn.raw_txn_bytes_len()).sum();
+        let mut parked = self.transactions.get_parking_lot_transactions();
+        parked.sort_unstable();
+        for (address, sequence_number) in parked {
+            if batch.len() as u64 >= max_txns {
+                break;
+            }
+            if seen.contains(&(address, sequence_number)) {
+                continue;
+            }
+            if let Some(txn) = self.transactions.get(&address, sequence_number) {
+                total_bytes += txn.raw_txn_bytes_len();
+                if total_bytes > max_bytes as usize {
+                    break;
+                }
+                batch.push(txn);
+            }
+        }
+        batch
+    }
+
+    /// Same as `get_batch`, but bounds the batch by the total declared gas of its transactions
+    /// instead of by bytes, e.g. for gas-limited blocks: transactions are taken in priority order
+    /// until the sum of their max gas amounts would exceed `max_total_gas_units`.
+    #[cfg(test)]
+    pub(crate) fn get_batch_by_gas(
+        &self,
+        max_txns: u64,
+        max_total_gas_units: u64,
+        mut seen: HashSet<TxnPointer>,
+    ) -> Vec<SignedTransaction> {
+        let (result, _) = self.select_batch(max_txns, &mut seen, &HashSet::new(), None);
+        let mut total_gas_units = 0u64;
+        let mut block = Vec::with_capacity(result.len());
+        for (address, seq) in result {
+            if let Some(txn) = self.transactions.get(&address, seq) {
+                total_gas_units = total_gas_units.saturating_add(txn.max_gas_amount());
+                if total_gas_units > max_total_gas_units {
+                    break;
+                }
+                block.push(txn);
+            }
+        }
+
+        counters::mempool_service_transactions(counters::GET_BLOCK_LABEL, block.len());
+        for transaction in &block {
+            self.log_latency(
+                transaction.sender(),
+                transaction.sequence_number(),
+                counters::CONSENSUS_PULLED_LABEL,
+            );
+        }
+        block
+    }
+
+    fn get_batch_impl(
+        &self,
+        max_txns: u64,
+        max_bytes: u64,
+        return_non_full: bool,
+        mut seen: HashSet<TxnPointer>,
+        exclude_senders: &HashSet<AccountAddress>,
+        tie_break_seed: Option<u64>,
+    ) -> Vec<SignedTransaction> {
+        let mut total_bytes = 0;
+        let seen_size = seen.len();
+        let (result, txn_walked) =
+            self.select_batch(max_txns, &mut seen, exclude_senders, tie_break_seed);
         let result_size = result.len();
         let mut block = Vec::with_capacity(result_size);
         let mut full_bytes = false;
@@ -283,14 +1003,41 @@ impl Mempool {
     /// Periodic core mempool garbage collection.
     /// Removes all expired transactions and clears expired entries in metrics
     /// cache and sequence number cache.
-    pub(crate) fn gc(&mut self) {
+    /// Returns the committed hashes of the removed transactions. Transactions parked because
+    /// an earlier one expired stay in Mempool, and aren't part of it.
+    pub(crate) fn gc(&mut self) -> Vec<HashValue> {
+        // The bucket of a sender idle for a refill interval is full again, so it can be dropped
+        // without letting the sender through faster.
+        self.sender_rate_limiters
+            .retain(|_, (_, last_used)| last_used.elapsed() < SENDER_RATE_LIMITER_IDLE_TIMEOUT);
         let now = aptos_infallible::duration_since_epoch();
-        self.transactions.gc_by_system_ttl(now);
+        let removed = self.transactions.gc_by_system_ttl(now);
+        counters::core_mempool_oldest_txn_age(
+            self.oldest_transaction_age(SystemTime::now())
+                .unwrap_or_default(),
+        );
+        removed
     }
 
     /// Garbage collection based on client-specified expiration time.
-    pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) {
-        self.transactions.gc_by_expiration_time(block_time);
+    /// Returns the committed hashes of the removed transactions, see `gc`.
+    pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) -> Vec<HashValue> {
+        self.transactions.gc_by_expiration_time(block_time)
+    }
+
+    /// Applies the configured `EpochChangePolicy` if `new_epoch` is newer than the latest
+    /// epoch seen by mempool.
+    pub(crate) fn on_epoch_change(&mut self, new_epoch: u64) {
+        if new_epoch <= self.epoch {
+            return;
+        }
+        self.epoch = new_epoch;
+
+        match self.epoch_change_policy {
+            EpochChangePolicy::Keep => {},
+            EpochChangePolicy::DropParked => self.transactions.remove_parked_transactions(),
+            EpochChangePolicy::DropAll => self.transactions.remove_all_transactions(),
+        }
     }
 
     /// Returns block of transactions and new last_timeline_id.
@@ -302,6 +1049,38 @@ impl Mempool {
         self.transactions.read_timeline(timeline_id, count)
     }
 
+    /// Same as `read_timeline`, but also returns the number of ready transactions in the
+    /// timeline since `timeline_id` (including the returned ones), e.g. to pace broadcasts.
+    #[cfg(test)]
+    pub(crate) fn read_timeline_with_total(
+        &self,
+        timeline_id: &MultiBucketTimelineIndexIds,
+        count: usize,
+    ) -> (Vec<SignedTransaction>, MultiBucketTimelineIndexIds, usize) {
+        self.transactions
+            .read_timeline_with_total(timeline_id, count)
+    }
+
+    /// Same as `read_timeline`, but returns the transactions BCS-encoded into a single buffer,
+    /// e.g. to gossip them with less overhead per transaction.
+    #[cfg(test)]
+    pub(crate) fn read_timeline_compact(
+        &self,
+        timeline_id: &MultiBucketTimelineIndexIds,
+        count: usize,
+    ) -> (Vec<u8>, MultiBucketTimelineIndexIds) {
+        let (txns, last_timeline_id) = self.read_timeline(timeline_id, count);
+        let bytes = bcs::to_bytes(&txns).expect("Transactions must serialize");
+        (bytes, last_timeline_id)
+    }
+
+    /// Returns the timeline cursor past all the transactions inserted into the timeline so far,
+    /// e.g. to compare with the cursors of the peers and skip the ranges already sent to them.
+    #[cfg(test)]
+    pub(crate) fn current_timeline_index(&self) -> MultiBucketTimelineIndexIds {
+        self.transactions.current_timeline_index()
+    }
+
     /// Read transactions from timeline from `start_id` (exclusive) to `end_id` (inclusive).
     pub(crate) fn timeline_range(
         &self,
@@ -323,4 +1102,9 @@ impl Mempool {
     pub fn get_transaction_store(&self) -> &TransactionStore {
         &self.transactions
     }
+
+    #[cfg(test)]
+    pub fn get_transaction_store_mut(&mut self) -> &mut TransactionStore {
+        &mut self.transactions
+    }
 }
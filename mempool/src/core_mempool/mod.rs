@@ -2,14 +2,18 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod balance_provider;
 mod index;
 mod mempool;
+mod ranking_strategy;
 mod transaction;
 mod transaction_store;
 
 pub use self::{
+    balance_provider::BalanceProvider,
     index::TxnPointer,
     mempool::Mempool as CoreMempool,
-    transaction::{MempoolTransaction, TimelineState},
+    transaction::{EvictionEvent, EvictionReason, MempoolTransaction, TimelineState},
     transaction_store::TXN_INDEX_ESTIMATED_BYTES,
 };
+pub(crate) use self::balance_provider::DbBalanceProvider;
@@ -0,0 +1,40 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::core_mempool::transaction::MempoolTransaction;
+use aptos_config::config::RankingStrategyType;
+
+/// Derives the ranking score that orders transactions in mempool, higher scores first.
+pub(crate) trait RankingStrategy: Send + Sync {
+    fn ranking_score(&self, txn: &MempoolTransaction) -> u64;
+}
+
+pub(crate) fn create_ranking_strategy(
+    strategy_type: RankingStrategyType,
+) -> Box<dyn RankingStrategy> {
+    match strategy_type {
+        RankingStrategyType::GasPrice => Box::new(GasPriceRanking),
+        RankingStrategyType::FeePerByte => Box::new(FeePerByteRanking),
+    }
+}
+
+/// Keeps the score computed by the VM validator, which is the gas unit price of the txn.
+pub(crate) struct GasPriceRanking;
+
+impl RankingStrategy for GasPriceRanking {
+    fn ranking_score(&self, txn: &MempoolTransaction) -> u64 {
+        txn.ranking_score
+    }
+}
+
+/// Ranks by the max fee the txn may pay (gas unit price * max gas amount), per byte of the raw
+/// transaction, so that large txns have to pay proportionally more to be prioritized.
+pub(crate) struct FeePerByteRanking;
+
+impl RankingStrategy for FeePerByteRanking {
+    fn ranking_score(&self, txn: &MempoolTransaction) -> u64 {
+        let max_fee = txn.txn.gas_unit_price() as u128 * txn.txn.max_gas_amount() as u128;
+        let num_bytes = txn.txn.raw_txn_bytes_len().max(1) as u128;
+        u64::try_from(max_fee / num_bytes).unwrap_or(u64::MAX)
+    }
+}
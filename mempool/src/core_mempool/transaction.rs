@@ -4,7 +4,10 @@
 
 use crate::core_mempool::TXN_INDEX_ESTIMATED_BYTES;
 use aptos_crypto::HashValue;
-use aptos_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{authenticator::TransactionAuthenticator, SignedTransaction},
+};
 use serde::{Deserialize, Serialize};
 use std::{
     mem::size_of,
@@ -62,7 +65,28 @@ impl MempoolTransaction {
     }
 
     pub(crate) fn get_estimated_bytes(&self) -> usize {
-        self.txn.raw_txn_bytes_len() + TXN_FIXED_ESTIMATED_BYTES + TXN_INDEX_ESTIMATED_BYTES
+        self.txn.raw_txn_bytes_len()
+            + self.secondary_signers_bytes()
+            + TXN_FIXED_ESTIMATED_BYTES
+            + TXN_INDEX_ESTIMATED_BYTES
+    }
+
+    /// Serialized size of the secondary signers (addresses and authenticators) of a multi-agent
+    /// txn, which are not part of the raw txn. 0 for any other txn.
+    fn secondary_signers_bytes(&self) -> usize {
+        match self.txn.authenticator_ref() {
+            TransactionAuthenticator::MultiAgent {
+                secondary_signer_addresses,
+                secondary_signers,
+                ..
+            } => {
+                bcs::serialized_size(secondary_signer_addresses)
+                    .expect("Unable to serialize secondary signer addresses")
+                    + bcs::serialized_size(secondary_signers)
+                        .expect("Unable to serialize secondary signers")
+            },
+            _ => 0,
+        }
     }
 }
 
@@ -84,6 +108,26 @@ pub struct SequenceInfo {
     pub account_sequence_number: u64,
 }
 
+/// Why a transaction left Mempool involuntarily, i.e. other than being committed or rejected.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EvictionReason {
+    // Evicted to make room for another transaction while Mempool is full.
+    CapacityFull,
+    // Garbage collected, by system TTL or by its own expiration time.
+    Expired,
+    // Replaced by a transaction with the same sequence number and a higher gas price.
+    Replaced,
+}
+
+/// Sent for every transaction evicted from Mempool, see `Mempool::with_eviction_sender`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvictionEvent {
+    pub hash: HashValue,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub reason: EvictionReason,
+}
+
 #[cfg(test)]
 mod test {
     use crate::core_mempool::{MempoolTransaction, TimelineState};
@@ -91,7 +135,10 @@ mod test {
     use aptos_types::{
         account_address::AccountAddress,
         chain_id::ChainId,
-        transaction::{RawTransaction, Script, SignedTransaction, TransactionPayload},
+        transaction::{
+            authenticator::AccountAuthenticator, RawTransaction, Script, SignedTransaction,
+            TransactionPayload,
+        },
     };
     use std::time::{Duration, SystemTime};
 
@@ -105,6 +152,32 @@ mod test {
         assert!(mempool_txn1.get_estimated_bytes() < mempool_txn2.get_estimated_bytes());
     }
 
+    #[test]
+    fn test_estimated_bytes_multi_agent() {
+        let txn = create_test_transaction(0, vec![0x1]);
+        let raw_txn = txn.clone().into_raw_transaction();
+        let single_agent = create_test_mempool_transaction(txn);
+
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let signer = AccountAuthenticator::ed25519(
+            private_key.public_key(),
+            private_key.sign(&raw_txn).unwrap(),
+        );
+        let multi_agent = create_test_mempool_transaction(SignedTransaction::new_multi_agent(
+            raw_txn,
+            signer.clone(),
+            vec![AccountAddress::random()],
+            vec![signer],
+        ));
+
+        // Same raw txn, but the secondary signer adds at least its address, public key and
+        // signature.
+        assert!(
+            multi_agent.get_estimated_bytes()
+                >= single_agent.get_estimated_bytes() + AccountAddress::LENGTH + 32 + 64
+        );
+    }
+
     fn create_test_mempool_transaction(signed_txn: SignedTransaction) -> MempoolTransaction {
         MempoolTransaction::new(
             signed_txn,
@@ -6,9 +6,9 @@ use crate::{
     core_mempool::{
         index::{
             AccountTransactions, MultiBucketTimelineIndex, ParkingLotIndex, PriorityIndex,
-            PriorityQueueIter, TTLIndex,
+            PriorityQueueIter, TTLIndex, TxnPointer,
         },
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{EvictionEvent, EvictionReason, MempoolTransaction, TimelineState},
     },
     counters,
     counters::{
@@ -28,11 +28,14 @@ use aptos_types::{
 };
 use std::{
     cmp::max,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     mem::size_of,
     ops::Bound,
     time::{Duration, SystemTime},
 };
+#[cfg(test)]
+use std::ops::Range;
+use tokio::sync::mpsc::UnboundedSender;
 
 /// Estimated per-txn overhead of indexes. Needs to be updated if additional indexes are added.
 pub const TXN_INDEX_ESTIMATED_BYTES: usize = size_of::<crate::core_mempool::index::OrderedQueueKey>() // priority_index
@@ -80,10 +83,42 @@ pub struct TransactionStore {
     // eager expiration
     eager_expire_threshold: Option<Duration>,
     eager_expire_time: Duration,
+
+    // transactions younger than this are not evicted when mempool is full
+    min_protected_age: Duration,
+    // max span of parked sequence numbers per account
+    max_parked_seq_span: Option<u64>,
+    // evict lower priority local txns for higher priority local ones when full
+    evict_lower_priority_local_txns: bool,
+    // lowers the eviction score of local txns per other txn of their sender
+    eviction_account_penalty_pct: u64,
+    // senders whose txns are never evicted when full
+    priority_senders: HashSet<AccountAddress>,
+
+    // gas price updates of the same txn within this window are applied to the indexes at once
+    update_batch_window: Duration,
+    // staged gas price updates, and the time the oldest one was staged at
+    pending_updates: HashMap<TxnPointer, MempoolTransaction>,
+    pending_updates_since: Option<SystemTime>,
+
+    // min ranking score bump in percent for a txn to replace one with the same sequence number
+    replace_by_fee_min_bump_pct: u64,
+    // park the txns following a rejected txn, so that only contiguous txns are broadcast
+    broadcast_contiguous_prefix_only: bool,
+    // reset the insertion time and system TTL of a txn when it is resubmitted as is
+    refresh_ttl_on_resubmission: bool,
+    // max number of txns per account, reported separately from `capacity_per_user`
+    max_txns_per_account: Option<usize>,
+
+    // notified of every txn evicted from mempool, if set
+    eviction_sender: Option<UnboundedSender<EvictionEvent>>,
 }
 
 impl TransactionStore {
-    pub(crate) fn new(config: &MempoolConfig) -> Self {
+    pub(crate) fn new(
+        config: &MempoolConfig,
+        eviction_sender: Option<UnboundedSender<EvictionEvent>>,
+    ) -> Self {
         Self {
             // main DS
             transactions: HashMap::new(),
@@ -94,10 +129,10 @@ impl TransactionStore {
             expiration_time_index: TTLIndex::new(Box::new(|t: &MempoolTransaction| {
                 Duration::from_secs(t.txn.expiration_timestamp_secs())
             })),
-            priority_index: PriorityIndex::new(),
+            priority_index: PriorityIndex::new(config.sequence_number_penalty_pct),
             timeline_index: MultiBucketTimelineIndex::new(config.broadcast_buckets.clone())
                 .unwrap(),
-            parking_lot_index: ParkingLotIndex::new(),
+            parking_lot_index: ParkingLotIndex::new(config.randomization_seed),
             hash_index: HashMap::new(),
 
             // estimated size in bytes
@@ -112,6 +147,23 @@ impl TransactionStore {
             // eager expiration
             eager_expire_threshold: config.eager_expire_threshold_ms.map(Duration::from_millis),
             eager_expire_time: Duration::from_millis(config.eager_expire_time_ms),
+
+            min_protected_age: Duration::from_millis(config.min_protected_age_ms),
+            max_parked_seq_span: config.max_parked_seq_span,
+            evict_lower_priority_local_txns: config.evict_lower_priority_local_txns,
+            eviction_account_penalty_pct: config.eviction_account_penalty_pct,
+            priority_senders: config.priority_senders.iter().copied().collect(),
+
+            update_batch_window: Duration::from_millis(config.update_batch_window_ms),
+            pending_updates: HashMap::new(),
+            pending_updates_since: None,
+
+            replace_by_fee_min_bump_pct: config.replace_by_fee_min_bump_pct,
+            broadcast_contiguous_prefix_only: config.broadcast_contiguous_prefix_only,
+            refresh_ttl_on_resubmission: config.refresh_ttl_on_resubmission,
+            max_txns_per_account: config.max_txns_per_account,
+
+            eviction_sender,
         }
     }
 
@@ -138,6 +190,23 @@ impl TransactionStore {
         None
     }
 
+    /// Returns the committed hash of the transaction of `address` with `sequence_number`.
+    pub(crate) fn get_committed_hash(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<HashValue> {
+        self.get_mempool_txn(address, sequence_number)
+            .map(MempoolTransaction::get_committed_hash)
+    }
+
+    /// Returns whether the transaction of `address` with `sequence_number` is in mempool, either
+    /// ready or parked.
+    #[cfg(test)]
+    pub(crate) fn contains(&self, address: &AccountAddress, sequence_number: u64) -> bool {
+        self.get_mempool_txn(address, sequence_number).is_some()
+    }
+
     /// Fetch transaction by account address + sequence_number, including ranking score
     pub(crate) fn get_with_ranking_score(
         &self,
@@ -157,6 +226,173 @@ impl TransactionStore {
         }
     }
 
+    /// Same as `get_by_hash`, but returns the transaction with its Mempool metadata.
+    #[cfg(test)]
+    pub(crate) fn get_mempool_txn_by_hash(&self, hash: HashValue) -> Option<&MempoolTransaction> {
+        let (address, sequence_number) = self.hash_index.get(&hash)?;
+        self.get_mempool_txn(address, *sequence_number)
+    }
+
+    /// Returns whether `txn` is ready, i.e. in the priority queue of the txns for consensus.
+    #[cfg(test)]
+    pub(crate) fn is_ready(&self, txn: &MempoolTransaction) -> bool {
+        self.priority_index.contains(txn)
+    }
+
+    /// Returns the first range of sequence numbers missing in mempool before the txn of `address`
+    /// with `sequence_number`, following the contiguous ready txns of `address`. None if there is
+    /// no gap, e.g. if the txn is ready.
+    #[cfg(test)]
+    pub(crate) fn blocking_gap(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<Range<u64>> {
+        let start = self.next_sequence_number(address)?;
+        let (end, _) = self
+            .transactions
+            .get(address)?
+            .range(start..=sequence_number)
+            .next()?;
+        if start < *end {
+            Some(start..*end)
+        } else {
+            None
+        }
+    }
+
+    /// Returns all transactions of `address`, both ready and parked, by sequence number.
+    #[cfg(test)]
+    pub(crate) fn get_by_sender(&self, address: &AccountAddress) -> Vec<SignedTransaction> {
+        self.transactions.get(address).map_or(vec![], |txns| {
+            txns.values().map(|txn| txn.txn.clone()).collect()
+        })
+    }
+
+    /// Returns a copy of all transactions, both ready and parked, see `Mempool::dump`.
+    #[cfg(test)]
+    pub(crate) fn dump(&self) -> Vec<MempoolTransaction> {
+        self.transactions
+            .values()
+            .flat_map(|txns| txns.values().cloned())
+            .collect()
+    }
+
+    /// Returns the transactions in the parking lot, with the time they were inserted at.
+    pub(crate) fn get_parked(&self) -> Vec<(SignedTransaction, SystemTime)> {
+        self.transactions
+            .iter()
+            .flat_map(|(address, txns)| {
+                txns.iter()
+                    .filter(move |(sequence_number, _)| {
+                        self.parking_lot_index.contains(address, sequence_number)
+                    })
+                    .map(|(_, txn)| (txn.txn.clone(), txn.insertion_time))
+            })
+            .collect()
+    }
+
+    /// Returns the (sender, sequence number) of the transactions in the parking lot.
+    #[cfg(test)]
+    pub(crate) fn get_parking_lot_transactions(&self) -> Vec<TxnPointer> {
+        self.parking_lot_index.get_all()
+    }
+
+    /// Returns the (sender, sequence number) of the ready and of the parked transactions, each
+    /// ordered by sender and sequence number.
+    #[cfg(test)]
+    pub(crate) fn classify_all(&self) -> (Vec<TxnPointer>, Vec<TxnPointer>) {
+        let mut ready = vec![];
+        let mut parked = vec![];
+        for (address, txns) in &self.transactions {
+            for sequence_number in txns.keys() {
+                if self.parking_lot_index.contains(address, sequence_number) {
+                    parked.push((*address, *sequence_number));
+                } else {
+                    ready.push((*address, *sequence_number));
+                }
+            }
+        }
+        ready.sort_unstable();
+        parked.sort_unstable();
+        (ready, parked)
+    }
+
+    /// Returns the (sender, sequence number, age) of all transactions inserted more than `age`
+    /// ago, oldest first.
+    #[cfg(test)]
+    pub(crate) fn transactions_older_than(
+        &self,
+        age: Duration,
+    ) -> Vec<(AccountAddress, u64, Duration)> {
+        let now = SystemTime::now();
+        let mut txns: Vec<_> = self
+            .transactions
+            .iter()
+            .flat_map(|(address, txns)| {
+                txns.iter().filter_map(move |(sequence_number, txn)| {
+                    now.duration_since(txn.insertion_time)
+                        .ok()
+                        .filter(|txn_age| *txn_age > age)
+                        .map(|txn_age| (*address, *sequence_number, txn_age))
+                })
+            })
+            .collect();
+        txns.sort_by(|a, b| b.2.cmp(&a.2));
+        txns
+    }
+
+    /// Returns the insertion time of the transaction that has been in Mempool the longest.
+    pub(crate) fn oldest_insertion_time(&self) -> Option<SystemTime> {
+        self.transactions
+            .values()
+            .flat_map(|txns| txns.values().map(|txn| txn.insertion_time))
+            .min()
+    }
+
+    /// Returns the number of ready transactions in each gas price bucket delimited by
+    /// `bucket_boundaries`, see `Mempool::gas_price_histogram`.
+    #[cfg(test)]
+    pub(crate) fn gas_price_histogram(&self, bucket_boundaries: &[u64]) -> Vec<usize> {
+        let mut histogram = vec![0; bucket_boundaries.len() + 1];
+        for key in self.priority_index.iter() {
+            if let Some(txn) = self.get_mempool_txn(
+                &key.address,
+                key.sequence_number.transaction_sequence_number,
+            ) {
+                let gas_price = txn.get_gas_price();
+                let bucket = bucket_boundaries.partition_point(|boundary| *boundary <= gas_price);
+                histogram[bucket] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Returns at most `limit` transactions ordered by (sender, sequence number), starting right
+    /// after `after` (exclusive), and whether there are more transactions following them.
+    #[cfg(test)]
+    pub(crate) fn list_transactions(
+        &self,
+        after: Option<TxnPointer>,
+        limit: usize,
+    ) -> (Vec<SignedTransaction>, bool) {
+        let mut pointers: Vec<TxnPointer> = self
+            .transactions
+            .iter()
+            .flat_map(|(account, txns)| txns.keys().map(move |seq_num| (*account, *seq_num)))
+            .filter(|pointer| after.map_or(true, |after| *pointer > after))
+            .collect();
+        pointers.sort_unstable();
+        let has_more = pointers.len() > limit;
+
+        let txns = pointers
+            .into_iter()
+            .take(limit)
+            .filter_map(|(account, seq_num)| self.get(&account, seq_num))
+            .collect();
+        (txns, has_more)
+    }
+
     /// Return (SystemTime, is the timestamp for end-to-end)
     pub(crate) fn get_insertion_time_and_bucket(
         &self,
@@ -193,12 +429,37 @@ impl TransactionStore {
         self.sequence_numbers.get(address)
     }
 
+    /// Returns whether the transaction of `address` with `sequence_number` is known to be
+    /// committed, i.e. it is below the sequence number of the account.
+    pub(crate) fn is_committed(&self, address: &AccountAddress, sequence_number: u64) -> bool {
+        self.get_sequence_number(address)
+            .map_or(false, |account_sequence_number| sequence_number < *account_sequence_number)
+    }
+
+    /// Returns the sequence number following the contiguous ready txns of `address`, i.e. its
+    /// committed sequence number if it has none. None if mempool doesn't know the account.
+    #[cfg(test)]
+    pub(crate) fn next_sequence_number(&self, address: &AccountAddress) -> Option<u64> {
+        let mut next = *self.sequence_numbers.get(address)?;
+        if let Some(txns) = self.transactions.get(address) {
+            for (seq, txn) in txns.range(next..) {
+                if *seq != next || !self.priority_index.contains(txn) {
+                    break;
+                }
+                next += 1;
+            }
+        }
+        Some(next)
+    }
+
     /// Insert transaction into TransactionStore. Performs validation checks and updates indexes.
     pub(crate) fn insert(&mut self, txn: MempoolTransaction) -> MempoolStatus {
         let address = txn.get_sender();
         let txn_seq_num = txn.sequence_info.transaction_sequence_number;
         let acc_seq_num = txn.sequence_info.account_sequence_number;
 
+        self.apply_pending_updates_if_due();
+
         // If the transaction is already in Mempool, we only allow the user to
         // increase the gas unit price to speed up a transaction, but not the max gas.
         //
@@ -223,9 +484,25 @@ impl TransactionStore {
                             .to_string(),
                     );
                 } else if current_version.txn.gas_unit_price() < txn.get_gas_price() {
+                    // Every replacement has to raise the ranking score by a min percentage, so
+                    // that senders can't churn Mempool with tiny gas price bumps.
+                    let min_ranking_score = current_version.ranking_score as u128
+                        * (100 + self.replace_by_fee_min_bump_pct as u128)
+                        / 100;
+                    if (txn.ranking_score as u128) < min_ranking_score {
+                        return MempoolStatus::new(MempoolStatusCode::ReplacementUnderpriced)
+                            .with_message(format!(
+                                "Replacement ranking score {} is below the required {}",
+                                txn.ranking_score, min_ranking_score,
+                            ));
+                    }
+                    if !self.update_batch_window.is_zero() {
+                        return self.stage_update(txn);
+                    }
                     // Update txn if gas unit price is a larger value than before
                     if let Some(txn) = txns.remove(&txn_seq_num) {
                         self.index_remove(&txn);
+                        self.notify_eviction(&txn, EvictionReason::Replaced);
                     };
                 } else if current_version.get_gas_price() > txn.get_gas_price() {
                     return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
@@ -235,13 +512,34 @@ impl TransactionStore {
                     // If the transaction is the same, it's an idempotent call
                     // Updating signers is not supported, the previous submission must fail
                     counters::CORE_MEMPOOL_IDEMPOTENT_TXNS.inc();
+                    if self.refresh_ttl_on_resubmission {
+                        // The system TTL is part of the priority key, re-index the txn.
+                        let is_ready = self.priority_index.contains(current_version);
+                        self.priority_index.remove(current_version);
+                        self.system_ttl_index.remove(current_version);
+                        current_version.insertion_time = txn.insertion_time;
+                        current_version.expiration_time = txn.expiration_time;
+                        self.system_ttl_index.insert(current_version);
+                        if is_ready {
+                            self.priority_index.insert(current_version);
+                        }
+                        return MempoolStatus::new(MempoolStatusCode::Accepted).with_message(
+                            "Transaction already in mempool, its TTL was refreshed".to_string(),
+                        );
+                    }
                     return MempoolStatus::new(MempoolStatusCode::Accepted);
                 }
             }
         }
 
         if self.check_is_full_after_eviction(&txn, acc_seq_num) {
-            return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
+            // Only ready txns make room by evicting from the parking lot.
+            let code = if self.check_txn_ready(&txn, acc_seq_num) {
+                MempoolStatusCode::MempoolIsFull
+            } else {
+                MempoolStatusCode::MempoolIsFullNoEvictableParking
+            };
+            return MempoolStatus::new(code).with_message(format!(
                 "Mempool is full. Mempool size: {}, Capacity: {}",
                 self.system_ttl_index.size(),
                 self.capacity,
@@ -250,12 +548,41 @@ impl TransactionStore {
 
         self.clean_committed_transactions(&address, acc_seq_num);
 
+        // parked sequence number span check
+        if let Some(max_parked_seq_span) = self.max_parked_seq_span {
+            if !self.check_txn_ready(&txn, acc_seq_num) {
+                let (lowest, highest) = self
+                    .parking_lot_index
+                    .get_seq_num_range(&address)
+                    .map_or((txn_seq_num, txn_seq_num), |(lowest, highest)| {
+                        (lowest.min(txn_seq_num), highest.max(txn_seq_num))
+                    });
+                if highest - lowest > max_parked_seq_span {
+                    return MempoolStatus::new(MempoolStatusCode::InvalidSeqNumber).with_message(
+                        format!(
+                            "Parked sequence numbers of account would span from {} to {}, max span: {}",
+                            lowest, highest, max_parked_seq_span,
+                        ),
+                    );
+                }
+            }
+        }
+
         self.transactions
             .entry(address)
             .or_insert_with(AccountTransactions::new);
 
         if let Some(txns) = self.transactions.get_mut(&address) {
             // capacity check
+            if let Some(max_txns_per_account) = self.max_txns_per_account {
+                if txns.len() >= max_txns_per_account {
+                    return MempoolStatus::new(MempoolStatusCode::TooManyTransactionsForAccount)
+                        .with_message(format!(
+                            "Account reached the max number of transactions in mempool: {}",
+                            max_txns_per_account,
+                        ));
+                }
+            }
             if txns.len() >= self.capacity_per_user {
                 return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
                     format!(
@@ -274,7 +601,7 @@ impl TransactionStore {
                 .insert(txn.get_committed_hash(), (sender, txn_seq_num));
             let txn_size_bytes = txn.get_estimated_bytes();
             txns.insert(txn_seq_num, txn);
-            self.sequence_numbers.insert(sender, acc_seq_num);
+            self.update_account_sequence_number(&sender, acc_seq_num);
             self.size_bytes += txn_size_bytes;
             self.track_indices();
         }
@@ -321,7 +648,15 @@ impl TransactionStore {
     ) -> bool {
         if self.is_full() && self.check_txn_ready(txn, curr_sequence_number) {
             // try to free some space in Mempool from ParkingLot by evicting a non-ready txn
-            if let Some((address, sequence_number)) = self.parking_lot_index.get_poppable() {
+            let mut evictable = self.get_evictable();
+            // otherwise make room for a local txn by evicting a lower priority local one
+            if evictable.is_none()
+                && self.evict_lower_priority_local_txns
+                && txn.timeline_state != TimelineState::NonQualified
+            {
+                evictable = self.get_lower_priority_local(txn);
+            }
+            if let Some((address, sequence_number)) = evictable {
                 if let Some(txn) = self
                     .transactions
                     .get_mut(&address)
@@ -334,12 +669,141 @@ impl TransactionStore {
                         ))
                     );
                     self.index_remove(&txn);
+                    self.notify_eviction(&txn, EvictionReason::CapacityFull);
                 }
             }
         }
         self.is_full()
     }
 
+    /// Stages a gas price update of a transaction already in Mempool. Updates of the same
+    /// transaction are applied to the indexes at once, by `apply_pending_updates`. Until then,
+    /// the current version stays in Mempool and the hash of the update can't be looked up, which
+    /// the message of the returned status tells.
+    fn stage_update(&mut self, txn: MempoolTransaction) -> MempoolStatus {
+        let pointer = (txn.get_sender(), txn.sequence_info.transaction_sequence_number);
+        if let Some(pending) = self.pending_updates.get(&pointer) {
+            if pending.get_gas_price() > txn.get_gas_price() {
+                return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
+                    "Transaction update already pending with a higher gas price".to_string(),
+                );
+            } else if pending.get_gas_price() == txn.get_gas_price() {
+                counters::CORE_MEMPOOL_IDEMPOTENT_TXNS.inc();
+                return MempoolStatus::new(MempoolStatusCode::Accepted);
+            }
+        }
+        self.pending_updates_since.get_or_insert_with(SystemTime::now);
+        self.pending_updates.insert(pointer, txn);
+        MempoolStatus::new(MempoolStatusCode::Accepted).with_message(format!(
+            "Transaction update staged, to be applied within {:?}",
+            self.update_batch_window,
+        ))
+    }
+
+    fn apply_pending_updates_if_due(&mut self) {
+        let is_due = self.pending_updates_since.map_or(false, |since| {
+            SystemTime::now()
+                .duration_since(since)
+                .map_or(false, |age| age >= self.update_batch_window)
+        });
+        if is_due {
+            self.apply_pending_updates();
+        }
+    }
+
+    /// Applies all the staged gas price updates, and returns the number of transactions updated.
+    pub(crate) fn apply_pending_updates(&mut self) -> usize {
+        self.pending_updates_since = None;
+        let mut num_updated = 0;
+        for ((address, sequence_number), txn) in std::mem::take(&mut self.pending_updates) {
+            // The transaction might have been committed or removed in the meantime.
+            let is_update = self
+                .get_mempool_txn(&address, sequence_number)
+                .map_or(false, |current| current.get_gas_price() < txn.get_gas_price());
+            if !is_update {
+                continue;
+            }
+            let current = match self
+                .transactions
+                .get_mut(&address)
+                .and_then(|txns| txns.remove(&sequence_number))
+            {
+                Some(current) => current,
+                None => continue,
+            };
+            self.index_remove(&current);
+            if self.insert(txn).code == MempoolStatusCode::Accepted {
+                self.notify_eviction(&current, EvictionReason::Replaced);
+                num_updated += 1;
+            } else {
+                // The update can still be rejected (e.g. by the per account limits), in which
+                // case the current version is kept.
+                self.insert(current);
+            }
+        }
+        num_updated
+    }
+
+    /// Picks a parked transaction to evict, skipping the ones added within `min_protected_age`
+    /// and the ones of `priority_senders`.
+    fn get_evictable(&mut self) -> Option<TxnPointer> {
+        if self.min_protected_age.is_zero() && self.priority_senders.is_empty() {
+            return self.parking_lot_index.get_poppable();
+        }
+
+        let protected_since = SystemTime::now()
+            .checked_sub(self.min_protected_age)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let transactions = &self.transactions;
+        let priority_senders = &self.priority_senders;
+        self.parking_lot_index.get_poppable_if(|(address, sequence_number)| {
+            !priority_senders.contains(address)
+                && transactions
+                    .get(address)
+                    .and_then(|txns| txns.get(sequence_number))
+                    .map_or(false, |txn| txn.insertion_time <= protected_since)
+        })
+    }
+
+    /// Returns the lowest priority ready local txn with a lower ranking score than `txn`. Only the
+    /// last txn of an account is considered, so that no gap is left in its sequence numbers.
+    /// With `eviction_account_penalty_pct`, priority is the `eviction_score` of the txn instead of
+    /// its ranking score.
+    fn get_lower_priority_local(&self, txn: &MempoolTransaction) -> Option<TxnPointer> {
+        let sender = txn.get_sender();
+        let ranking_score = self.priority_index.ranking_score(txn);
+        let mut candidates = self
+            .priority_index
+            .iter()
+            .rev()
+            .take_while(|key| key.gas_ranking_score < ranking_score)
+            .filter(|key| key.address != sender && !self.priority_senders.contains(&key.address))
+            .filter_map(|key| {
+                let pointer = (key.address, key.sequence_number.transaction_sequence_number);
+                let txns = self.transactions.get(&pointer.0)?;
+                let (last_sequence_number, last_txn) = txns.iter().next_back()?;
+                (*last_sequence_number == pointer.1
+                    && last_txn.timeline_state != TimelineState::NonQualified)
+                    .then(|| (pointer, key.gas_ranking_score, txns.len()))
+            });
+        if self.eviction_account_penalty_pct == 0 {
+            return candidates.next().map(|(pointer, _, _)| pointer);
+        }
+        candidates
+            .min_by_key(|(_, ranking_score, num_account_txns)| {
+                self.eviction_score(*ranking_score, *num_account_txns)
+            })
+            .map(|(pointer, _, _)| pointer)
+    }
+
+    /// Eviction score of a txn of ranking score `ranking_score` whose sender has
+    /// `num_account_txns` txns in Mempool: the ranking score lowered by
+    /// `eviction_account_penalty_pct` per other txn of the sender. The lowest score goes first.
+    fn eviction_score(&self, ranking_score: u64, num_account_txns: usize) -> u128 {
+        let others = num_account_txns.saturating_sub(1) as u128;
+        ranking_score as u128 * 100 / (100 + self.eviction_account_penalty_pct as u128 * others)
+    }
+
     fn is_full(&self) -> bool {
         self.system_ttl_index.size() >= self.capacity || self.size_bytes >= self.capacity_bytes
     }
@@ -416,6 +880,11 @@ impl TransactionStore {
     ///   should be included in both the PriorityIndex (ordering for Consensus) and
     ///   TimelineIndex (txns for SharedMempool).
     /// - Other txns are considered to be "non-ready" and should be added to ParkingLotIndex.
+    /// Promotes the txns of `address` that are sequential from `sequence_num` to ready, and parks
+    /// the ones following the first gap.
+    /// Txns are promoted in increasing sequence number order, whatever their ranking score, so
+    /// that a chain unblocked at once enters the timeline (and gets broadcast) in the order it
+    /// can be executed.
     fn process_ready_transactions(&mut self, address: &AccountAddress, sequence_num: u64) {
         if let Some(txns) = self.transactions.get_mut(address) {
             let mut min_seq = sequence_num;
@@ -499,13 +968,32 @@ impl TransactionStore {
         }
     }
 
+    /// Records `account_sequence_number` as the sequence number of `address`, and updates the
+    /// txns of `address` that were inserted when it was lower, so that their sequence number
+    /// penalty in the priority index reflects the current distance.
+    fn update_account_sequence_number(
+        &mut self,
+        address: &AccountAddress,
+        account_sequence_number: u64,
+    ) {
+        self.sequence_numbers.insert(*address, account_sequence_number);
+        if let Some(txns) = self.transactions.get_mut(address) {
+            for txn in txns.values_mut() {
+                if txn.sequence_info.account_sequence_number < account_sequence_number {
+                    self.priority_index
+                        .update_account_sequence_number(txn, account_sequence_number);
+                }
+            }
+        }
+    }
+
     /// Handles transaction commit.
     /// It includes deletion of all transactions with sequence number <= `account_sequence_number`
     /// and potential promotion of sequential txns to PriorityIndex/TimelineIndex.
     pub fn commit_transaction(&mut self, account: &AccountAddress, sequence_number: u64) {
         let current_seq_number = self.get_sequence_number(account).map_or(0, |v| *v);
         let new_seq_number = max(current_seq_number, sequence_number + 1);
-        self.sequence_numbers.insert(*account, new_seq_number);
+        self.update_account_sequence_number(account, new_seq_number);
         self.clean_committed_transactions(account, new_seq_number);
         self.process_ready_transactions(account, new_seq_number);
     }
@@ -527,6 +1015,9 @@ impl TransactionStore {
                 txns.remove(&sequence_number);
             }
             self.index_remove(&txn_to_remove);
+            if self.broadcast_contiguous_prefix_only {
+                self.park_transactions_after(account, sequence_number);
+            }
 
             if aptos_logger::enabled!(Level::Trace) {
                 let mut txns_log = TxnsLog::new();
@@ -539,6 +1030,75 @@ impl TransactionStore {
         }
     }
 
+    /// Moves the transactions of `account` following `sequence_number` to the parking lot, and
+    /// out of the timeline, until the gap at `sequence_number` is filled.
+    fn park_transactions_after(&mut self, account: &AccountAddress, sequence_number: u64) {
+        if let Some(txns) = self.transactions.get_mut(account) {
+            for (_, t) in txns.range_mut((Bound::Excluded(sequence_number), Bound::Unbounded)) {
+                self.parking_lot_index.insert(t);
+                t.was_parked = true;
+                self.priority_index.remove(t);
+                self.timeline_index.remove(t);
+                if let TimelineState::Ready(_) = t.timeline_state {
+                    t.timeline_state = TimelineState::NotReady;
+                }
+            }
+            self.track_indices();
+        }
+    }
+
+    /// Removes all transactions which are not ready (i.e. sitting in the parking lot).
+    pub(crate) fn remove_parked_transactions(&mut self) {
+        self.remove_transactions_if(|store, account, sequence_number| {
+            store.parking_lot_index.contains(account, &sequence_number)
+        });
+    }
+
+    /// Removes all transactions.
+    pub(crate) fn remove_all_transactions(&mut self) {
+        self.remove_transactions_if(|_, _, _| true);
+    }
+
+    fn remove_transactions_if<F>(&mut self, predicate: F)
+    where
+        F: Fn(&Self, &AccountAddress, u64) -> bool,
+    {
+        let to_remove: Vec<TxnPointer> = self
+            .transactions
+            .iter()
+            .flat_map(|(account, txns)| txns.keys().map(move |seq_num| (*account, *seq_num)))
+            .filter(|(account, seq_num)| predicate(self, account, *seq_num))
+            .collect();
+
+        let mut rm_txns = match aptos_logger::enabled!(Level::Trace) {
+            true => TxnsLog::new(),
+            false => TxnsLog::new_with_max(10),
+        };
+        for (account, sequence_number) in to_remove {
+            if let Some(txn) = self
+                .transactions
+                .get_mut(&account)
+                .and_then(|txns| txns.remove(&sequence_number))
+            {
+                rm_txns.add(account, sequence_number);
+                self.index_remove(&txn);
+            }
+        }
+        debug!(LogSchema::new(LogEntry::RemoveTxn).txns(rm_txns));
+    }
+
+    fn notify_eviction(&self, txn: &MempoolTransaction, reason: EvictionReason) {
+        if let Some(eviction_sender) = &self.eviction_sender {
+            // Nobody might be listening anymore, which is fine.
+            let _ = eviction_sender.send(EvictionEvent {
+                hash: txn.get_committed_hash(),
+                sender: txn.get_sender(),
+                sequence_number: txn.sequence_info.transaction_sequence_number,
+                reason,
+            });
+        }
+    }
+
     /// Removes transaction from all indexes. Only call after removing from main transactions DS.
     fn index_remove(&mut self, txn: &MempoolTransaction) {
         counters::CORE_MEMPOOL_REMOVED_TXNS.inc();
@@ -617,6 +1177,24 @@ impl TransactionStore {
         (batch, last_timeline_id.into())
     }
 
+    /// Same as `read_timeline`, but also returns the number of transactions in the timeline
+    /// since `timeline_id`, including the ones returned.
+    #[cfg(test)]
+    pub(crate) fn read_timeline_with_total(
+        &self,
+        timeline_id: &MultiBucketTimelineIndexIds,
+        count: usize,
+    ) -> (Vec<SignedTransaction>, MultiBucketTimelineIndexIds, usize) {
+        let (batch, last_timeline_id) = self.read_timeline(timeline_id, count);
+        let total = self.timeline_index.count_since(timeline_id);
+        (batch, last_timeline_id, total)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn current_timeline_index(&self) -> MultiBucketTimelineIndexIds {
+        self.timeline_index.current_timeline_index()
+    }
+
     pub(crate) fn timeline_range(
         &self,
         start_end_pairs: &Vec<(u64, u64)>,
@@ -664,17 +1242,18 @@ impl TransactionStore {
         gc_time
     }
 
-    /// Garbage collect old transactions.
-    pub(crate) fn gc_by_system_ttl(&mut self, gc_time: Duration) {
-        self.gc(gc_time, true);
+    /// Garbage collect old transactions. Returns the committed hashes of the removed ones.
+    pub(crate) fn gc_by_system_ttl(&mut self, gc_time: Duration) -> Vec<HashValue> {
+        self.gc(gc_time, true)
     }
 
-    /// Garbage collect old transactions based on client-specified expiration time.
-    pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) {
-        self.gc(self.eager_expire_time(block_time), false);
+    /// Garbage collect old transactions based on client-specified expiration time. Returns the
+    /// committed hashes of the removed ones.
+    pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) -> Vec<HashValue> {
+        self.gc(self.eager_expire_time(block_time), false)
     }
 
-    fn gc(&mut self, now: Duration, by_system_ttl: bool) {
+    fn gc(&mut self, now: Duration, by_system_ttl: bool) -> Vec<HashValue> {
         let (metric_label, index, log_event) = if by_system_ttl {
             (
                 counters::GC_SYSTEM_TTL_LABEL,
@@ -701,6 +1280,7 @@ impl TransactionStore {
             true => TxnsLog::new(),
             false => TxnsLog::new_with_max(10),
         };
+        let mut gc_hashes = vec![];
         while let Some(key) = gc_iter.next() {
             if let Some(txns) = self.transactions.get_mut(&key.address) {
                 let park_range_start = Bound::Excluded(key.sequence_number);
@@ -738,6 +1318,8 @@ impl TransactionStore {
 
                     // remove txn
                     self.index_remove(&txn);
+                    self.notify_eviction(&txn, EvictionReason::Expired);
+                    gc_hashes.push(txn.get_committed_hash());
                 }
             }
         }
@@ -748,6 +1330,7 @@ impl TransactionStore {
             trace!(LogSchema::event_log(LogEntry::GCRemoveTxns, log_event).txns(gc_txns_log));
         }
         self.track_indices();
+        gc_hashes
     }
 
     pub(crate) fn iter_queue(&self) -> PriorityQueueIter {
@@ -769,6 +1352,67 @@ impl TransactionStore {
         txns_log
     }
 
+    /// Panics if the indexes disagree with the main transactions DS on the set of transactions.
+    #[cfg(test)]
+    pub(crate) fn debug_assert_consistency(&self) {
+        let mut num_txns = 0;
+        let mut size_bytes = 0;
+        for (address, txns) in &self.transactions {
+            assert!(!txns.is_empty(), "Empty transactions map for {}", address);
+            assert!(
+                self.sequence_numbers.contains_key(address),
+                "Missing sequence number for {}",
+                address
+            );
+            for (sequence_number, txn) in txns.iter() {
+                let txn_pointer = (*address, *sequence_number);
+                assert_eq!(
+                    self.hash_index.get(&txn.get_committed_hash()),
+                    Some(&txn_pointer),
+                    "Hash index out of sync for {:?}",
+                    txn_pointer
+                );
+                assert!(
+                    !(self.priority_index.contains(txn)
+                        && self.parking_lot_index.contains(address, sequence_number)),
+                    "{:?} is both ready and parked",
+                    txn_pointer
+                );
+                num_txns += 1;
+                size_bytes += txn.get_estimated_bytes();
+            }
+        }
+        for key in self.priority_index.iter() {
+            assert!(
+                self.get_mempool_txn(&key.address, key.sequence_number.transaction_sequence_number)
+                    .is_some(),
+                "Priority index has unknown transaction {}:{}",
+                key.address,
+                key.sequence_number.transaction_sequence_number
+            );
+        }
+        for (index, size) in [
+            ("hash", self.hash_index.len()),
+            ("system TTL", self.system_ttl_index.size()),
+            ("expiration time", self.expiration_time_index.size()),
+        ] {
+            assert_eq!(size, num_txns, "Size of {} index out of sync", index);
+        }
+        assert!(self.priority_index.size() + self.parking_lot_index.size() <= num_txns);
+        assert!(self.timeline_index.size() <= self.priority_index.size());
+        assert_eq!(self.size_bytes, size_bytes, "Size in bytes out of sync");
+    }
+
+    #[cfg(test)]
+    pub(crate) fn size_bytes(&self) -> usize {
+        self.size_bytes
+    }
+
+    #[cfg(test)]
+    pub(crate) fn hash_index_mut(&mut self) -> &mut HashMap<HashValue, (AccountAddress, u64)> {
+        &mut self.hash_index
+    }
+
     #[cfg(test)]
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
@@ -5,8 +5,9 @@
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_metrics_core::{
     exponential_buckets, histogram_opts, op_counters::DurationHistogram, register_histogram,
-    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge_vec,
-    Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec,
 };
 use aptos_short_hex_str::AsShortHexStr;
 use once_cell::sync::Lazy;
@@ -157,6 +158,19 @@ pub fn core_mempool_timeline_index_size(bucket_min_size_pairs: &Vec<(&str, usize
     }
 }
 
+/// Gauge tracking how long the oldest txn in core mempool has been there, as of the last GC
+static CORE_MEMPOOL_OLDEST_TXN_AGE_MS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_core_mempool_oldest_txn_age_ms",
+        "Time the oldest txn in core mempool has been there, in milliseconds"
+    )
+    .unwrap()
+});
+
+pub fn core_mempool_oldest_txn_age(age: Duration) {
+    CORE_MEMPOOL_OLDEST_TXN_AGE_MS.set(age.as_millis() as i64)
+}
+
 /// Counter tracking number of txns removed from core mempool
 pub static CORE_MEMPOOL_REMOVED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
@@ -174,6 +174,7 @@ pub enum LogEntry {
     DBError,
     UnexpectedNetworkMsg,
     MempoolSnapshot,
+    ParkingLotPersistence,
 }
 
 #[derive(Clone, Copy, Serialize)]
@@ -16,6 +16,7 @@ use crate::{
     },
     MempoolEventsReceiver, QuorumStoreRequest,
 };
+use anyhow::Result;
 use aptos_bounded_executor::BoundedExecutor;
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_consensus_types::common::TransactionSummary;
@@ -27,7 +28,7 @@ use aptos_network::{
     application::interface::{NetworkClientInterface, NetworkServiceEvents},
     protocols::network::Event,
 };
-use aptos_types::on_chain_config::OnChainConfigPayload;
+use aptos_types::{on_chain_config::OnChainConfigPayload, transaction::SignedTransaction};
 use aptos_vm_validator::vm_validator::TransactionValidation;
 use futures::{
     channel::mpsc,
@@ -35,6 +36,8 @@ use futures::{
     StreamExt,
 };
 use std::{
+    fs,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant, SystemTime},
 };
@@ -235,6 +238,7 @@ async fn handle_mempool_reconfig_event<NetworkClient, TransactionValidator>(
     bounded_executor
         .spawn(tasks::process_config_update(
             config_update,
+            smp.mempool.clone(),
             smp.validator.clone(),
             smp.broadcast_within_validator_network.clone(),
         ))
@@ -381,3 +385,43 @@ pub(crate) async fn snapshot_job(mempool: Arc<Mutex<CoreMempool>>, snapshot_inte
         trace!(LogSchema::new(LogEntry::MempoolSnapshot).txns(snapshot));
     }
 }
+
+/// Periodically persists the parking lot of core mempool to `path`, so that the parked
+/// transactions survive a restart. They are imported back by `runtime::bootstrap`.
+pub(crate) async fn parking_lot_persistence_job(
+    mempool: Arc<Mutex<CoreMempool>>,
+    path: PathBuf,
+    persistence_interval_secs: u64,
+) {
+    let mut interval =
+        IntervalStream::new(interval(Duration::from_secs(persistence_interval_secs)));
+    while let Some(_interval) = interval.next().await {
+        let parked = mempool.lock().export_parking_lot();
+        if let Err(e) = write_parking_lot(&path, &parked) {
+            sample!(
+                SampleRate::Duration(Duration::from_secs(60)),
+                warn!(LogSchema::new(LogEntry::ParkingLotPersistence).error(&e))
+            );
+        }
+    }
+}
+
+/// Writes the parked transactions to `path`, through a temporary file so that a crash mid-write
+/// doesn't leave a truncated file behind.
+pub(crate) fn write_parking_lot(
+    path: &Path,
+    parked: &[(SignedTransaction, SystemTime)],
+) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, bcs::to_bytes(parked)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Reads the parked transactions written by `write_parking_lot`, none if `path` doesn't exist.
+pub(crate) fn read_parking_lot(path: &Path) -> Result<Vec<(SignedTransaction, SystemTime)>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    Ok(bcs::from_bytes(&fs::read(path)?)?)
+}
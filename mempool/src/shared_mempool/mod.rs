@@ -9,5 +9,5 @@ pub(crate) mod types;
 pub use runtime::bootstrap;
 #[cfg(any(test, feature = "fuzzing"))]
 pub(crate) use runtime::start_shared_mempool;
-mod coordinator;
+pub(crate) mod coordinator;
 pub(crate) mod tasks;
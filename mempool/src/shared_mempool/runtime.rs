@@ -3,10 +3,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::CoreMempool,
+    core_mempool::{CoreMempool, DbBalanceProvider},
+    logging::{LogEntry, LogSchema},
     network::MempoolSyncMsg,
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
+        coordinator::{
+            coordinator, gc_coordinator, parking_lot_persistence_job, read_parking_lot,
+            snapshot_job,
+        },
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     QuorumStoreRequest,
@@ -14,13 +18,16 @@ use crate::{
 use aptos_config::config::NodeConfig;
 use aptos_event_notifications::ReconfigNotificationListener;
 use aptos_infallible::{Mutex, RwLock};
-use aptos_logger::Level;
+use aptos_logger::{info, warn, Level};
 use aptos_mempool_notifications::MempoolNotificationListener;
 use aptos_network::application::interface::{NetworkClient, NetworkServiceEvents};
-use aptos_storage_interface::DbReader;
-use aptos_vm_validator::vm_validator::{TransactionValidation, VMValidator};
+use aptos_storage_interface::{state_view::LatestDbStateCheckpointView, DbReader};
+use aptos_types::mempool_status::MempoolStatusCode;
+use aptos_vm_validator::vm_validator::{
+    get_account_sequence_number, TransactionValidation, VMValidator,
+};
 use futures::channel::mpsc::{Receiver, UnboundedSender};
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 use tokio::runtime::{Handle, Runtime};
 
 /// Bootstrap of SharedMempool.
@@ -28,6 +35,7 @@ use tokio::runtime::{Handle, Runtime};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers).
 ///   - inbound_network_task (task that handles inbound mempool messages and network events).
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL).
+///   - parking_lot_persistence_job (task that periodically persists the parking lot, if enabled).
 pub(crate) fn start_shared_mempool<TransactionValidator>(
     executor: &Handle,
     config: &NodeConfig,
@@ -70,6 +78,14 @@ pub(crate) fn start_shared_mempool<TransactionValidator>(
         config.mempool.system_transaction_gc_interval_ms,
     ));
 
+    if let Some(path) = &config.mempool.parking_lot_persistence_path {
+        executor.spawn(parking_lot_persistence_job(
+            mempool.clone(),
+            path.clone(),
+            config.mempool.parking_lot_persistence_interval_secs,
+        ));
+    }
+
     if aptos_logger::enabled!(Level::Trace) {
         executor.spawn(snapshot_job(
             mempool,
@@ -89,7 +105,14 @@ pub fn bootstrap(
     mempool_reconfig_events: ReconfigNotificationListener,
 ) -> Runtime {
     let runtime = aptos_runtimes::spawn_named_runtime("shared-mem".into(), None);
-    let mempool = Arc::new(Mutex::new(CoreMempool::new(config)));
+    let mut mempool = CoreMempool::new(config);
+    if config.mempool.check_balance_at_admission {
+        mempool.set_balance_provider(Arc::new(DbBalanceProvider::new(Arc::clone(&db))));
+    }
+    if let Some(path) = &config.mempool.parking_lot_persistence_path {
+        import_persisted_parking_lot(&mut mempool, path, &db);
+    }
+    let mempool = Arc::new(Mutex::new(mempool));
     let vm_validator = Arc::new(RwLock::new(VMValidator::new(Arc::clone(&db))));
     start_shared_mempool(
         runtime.handle(),
@@ -107,3 +130,32 @@ pub fn bootstrap(
     );
     runtime
 }
+
+/// Imports the parking lot persisted by `parking_lot_persistence_job` before the restart. The
+/// transactions committed in the meantime are dropped, and a missing or unreadable file is
+/// logged and ignored, so that it never prevents the node from starting.
+fn import_persisted_parking_lot(mempool: &mut CoreMempool, path: &Path, db: &Arc<dyn DbReader>) {
+    let result = read_parking_lot(path).and_then(|parked| {
+        let state_view = db.latest_state_checkpoint_view()?;
+        Ok(parked
+            .into_iter()
+            .filter(|(txn, _)| {
+                get_account_sequence_number(&state_view, txn.sender())
+                    .map_or(false, |sequence_number| {
+                        txn.sequence_number() >= sequence_number
+                    })
+            })
+            .collect::<Vec<_>>())
+    });
+    match result {
+        Ok(parked) => {
+            let imported = mempool
+                .import_parking_lot(parked)
+                .iter()
+                .filter(|status| status.code == MempoolStatusCode::Accepted)
+                .count();
+            info!("Imported {} parked transactions from {:?}", imported, path);
+        },
+        Err(e) => warn!(LogSchema::new(LogEntry::ParkingLotPersistence).error(&e)),
+    }
+}
@@ -192,7 +192,7 @@ pub(crate) async fn process_transaction_broadcast<NetworkClient, TransactionVali
     notify_subscribers(SharedMempoolNotification::ACK, &smp.subscribers);
 }
 
-/// If `MempoolIsFull` on any of the transactions, provide backpressure to the downstream peer.
+/// If Mempool is full on any of the transactions, provide backpressure to the downstream peer.
 fn gen_ack_response(
     request_id: MultiBatchId,
     results: Vec<SubmissionStatusBundle>,
@@ -200,7 +200,12 @@ fn gen_ack_response(
 ) -> MempoolSyncMsg {
     let mut backoff_and_retry = false;
     for (_, (mempool_status, _)) in results.into_iter() {
-        if mempool_status.code == MempoolStatusCode::MempoolIsFull {
+        if matches!(
+            mempool_status.code,
+            MempoolStatusCode::MempoolIsFull
+                | MempoolStatusCode::MempoolIsFullNoEvictableParking
+                | MempoolStatusCode::RateLimited
+        ) {
             backoff_and_retry = true;
             break;
         }
@@ -477,13 +482,21 @@ pub(crate) fn process_quorum_store_request<NetworkClient, TransactionValidator>(
                     mempool.gc_by_expiration_time(curr_time);
                 }
 
+                // make sure consensus sees the latest gas prices
+                mempool.apply_pending_updates();
+
                 let max_txns = cmp::max(max_txns, 1);
                 let _get_batch_timer = counters::mempool_service_start_latency_timer(
                     counters::GET_BLOCK_GET_BATCH_LABEL,
                     counters::REQUEST_SUCCESS_LABEL,
                 );
-                txns =
-                    mempool.get_batch(max_txns, max_bytes, return_non_full, exclude_transactions);
+                txns = mempool.get_batch(
+                    max_txns,
+                    max_bytes,
+                    return_non_full,
+                    exclude_transactions,
+                    None,
+                );
             }
 
             // mempool_service_transactions is logged inside get_batch
@@ -527,11 +540,12 @@ pub(crate) fn process_committed_transactions(
     transactions: Vec<TransactionSummary>,
     block_timestamp_usecs: u64,
 ) {
+    let committed: Vec<_> = transactions
+        .iter()
+        .map(|transaction| (transaction.sender, transaction.sequence_number))
+        .collect();
     let mut pool = mempool.lock();
-
-    for transaction in transactions {
-        pool.commit_transaction(&transaction.sender, transaction.sequence_number);
-    }
+    pool.remove_transactions(&committed, false);
 
     if block_timestamp_usecs > 0 {
         pool.gc_by_expiration_time(Duration::from_micros(block_timestamp_usecs));
@@ -553,9 +567,11 @@ pub(crate) fn process_rejected_transactions(
     }
 }
 
-/// Processes on-chain reconfiguration notifications.  Restarts validator with the new info.
+/// Processes on-chain reconfiguration notifications.  Restarts validator with the new info, and
+/// applies the epoch change policy to mempool.
 pub(crate) async fn process_config_update<V>(
     config_update: OnChainConfigPayload,
+    mempool: Arc<Mutex<CoreMempool>>,
     validator: Arc<RwLock<V>>,
     broadcast_within_validator_network: Arc<RwLock<bool>>,
 ) where
@@ -566,6 +582,8 @@ pub(crate) async fn process_config_update<V>(
             .reconfig_update(config_update.clone())
     );
 
+    mempool.lock().on_epoch_change(config_update.epoch());
+
     if let Err(e) = validator.write().restart(config_update.clone()) {
         counters::VM_RECONFIG_UPDATE_FAIL_COUNT.inc();
         error!(LogSchema::event_log(LogEntry::ReconfigUpdate, LogEvent::VMUpdateFail).error(&e));
@@ -292,6 +292,22 @@ impl MultiBucketTimelineIndexIds {
     }
 }
 
+impl PartialOrd for MultiBucketTimelineIndexIds {
+    fn partial_cmp(&self, other: &MultiBucketTimelineIndexIds) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Note: like MultiBatchId, in rev order to check the most significant buckets first
+impl Ord for MultiBucketTimelineIndexIds {
+    fn cmp(&self, other: &MultiBucketTimelineIndexIds) -> std::cmp::Ordering {
+        self.id_per_bucket
+            .iter()
+            .rev()
+            .cmp(other.id_per_bucket.iter().rev())
+    }
+}
+
 impl From<Vec<u64>> for MultiBucketTimelineIndexIds {
     fn from(timeline_ids: Vec<u64>) -> Self {
         Self {
@@ -358,6 +374,15 @@ mod test {
 
         assert!(left > right);
     }
+
+    #[test]
+    fn test_multi_bucket_timeline_ids_ordering() {
+        let ids = |id_per_bucket: Vec<u64>| MultiBucketTimelineIndexIds { id_per_bucket };
+
+        assert!(ids(vec![0, 0, 1]) > ids(vec![5, 5, 0]));
+        assert!(ids(vec![1, 2, 3]) > ids(vec![0, 2, 3]));
+        assert_eq!(ids(vec![1, 2, 3]).cmp(&ids(vec![1, 2, 3])), std::cmp::Ordering::Equal);
+    }
 }
 
 /// Txn broadcast-related info for a given remote peer.
@@ -3,18 +3,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, MempoolTransaction, TimelineState},
+    core_mempool::{
+        BalanceProvider, CoreMempool, EvictionEvent, EvictionReason, MempoolTransaction,
+        TimelineState,
+    },
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, setup_mempool,
         setup_mempool_with_broadcast_buckets, TestTransaction,
     },
 };
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{EpochChangePolicy, NodeConfig, RankingStrategyType};
 use aptos_crypto::HashValue;
-use aptos_types::{mempool_status::MempoolStatusCode, transaction::SignedTransaction};
+use aptos_types::{
+    account_address::AccountAddress, mempool_status::MempoolStatusCode,
+    transaction::SignedTransaction,
+};
+use aptos_infallible::Mutex;
 use itertools::Itertools;
 use std::{
     collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
     time::{Duration, SystemTime},
 };
 
@@ -63,6 +75,44 @@ fn test_transaction_ordering_only_seqnos() {
     }
 }
 
+#[test]
+fn test_priority_inheritance() {
+    let txns = vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 100),
+        TestTransaction::new(1, 0, 10),
+    ];
+
+    // Without inheritance, the high gas txn waits for its low gas predecessor, which is only
+    // pulled after the txn of the other account.
+    let mut config = NodeConfig::random();
+    let mut pool = CoreMempool::new(&config);
+    let transactions = add_txns_to_mempool(&mut pool, txns.clone());
+    assert_eq!(pool.get_batch(2, 10240, true, HashSet::new(), None), vec![
+        transactions[2].clone(),
+        transactions[0].clone()
+    ]);
+
+    // With inheritance, the predecessor ranks above the txn of the other account, pulling the
+    // whole chain into the block.
+    config.mempool.priority_inheritance_pct = 50;
+    let mut pool = CoreMempool::new(&config);
+    let transactions = add_txns_to_mempool(&mut pool, txns.clone());
+    assert_eq!(pool.get_batch(2, 10240, true, HashSet::new(), None), vec![
+        transactions[0].clone(),
+        transactions[1].clone()
+    ]);
+
+    // Unless the inherited part of the score is still below the other txn.
+    config.mempool.priority_inheritance_pct = 5;
+    let mut pool = CoreMempool::new(&config);
+    let transactions = add_txns_to_mempool(&mut pool, txns);
+    assert_eq!(pool.get_batch(2, 10240, true, HashSet::new(), None), vec![
+        transactions[2].clone(),
+        transactions[0].clone()
+    ]);
+}
+
 #[test]
 fn test_transaction_metrics() {
     let (mut mempool, _) = setup_mempool();
@@ -115,6 +165,74 @@ fn test_update_transaction_in_mempool() {
     ]);
 }
 
+#[test]
+fn test_replace_by_fee_min_bump() {
+    let mut config = NodeConfig::random();
+    config.mempool.replace_by_fee_min_bump_pct = 10;
+    let mut pool = CoreMempool::new(&config);
+    let add = |pool: &mut CoreMempool, gas_price| {
+        let txn = TestTransaction::new(0, 0, gas_price).make_signed_transaction();
+        pool.add_txn(txn, gas_price, 0, TimelineState::NotReady).code
+    };
+
+    assert_eq!(add(&mut pool, 100), MempoolStatusCode::Accepted);
+    assert_eq!(add(&mut pool, 101), MempoolStatusCode::ReplacementUnderpriced);
+    assert_eq!(add(&mut pool, 109), MempoolStatusCode::ReplacementUnderpriced);
+    pool.get_transaction_store().debug_assert_consistency();
+    let batch = pool.get_batch(10, 1024, true, HashSet::new(), None);
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].gas_unit_price(), 100);
+
+    assert_eq!(add(&mut pool, 110), MempoolStatusCode::Accepted);
+    pool.get_transaction_store().debug_assert_consistency();
+    let batch = pool.get_batch(10, 1024, true, HashSet::new(), None);
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].gas_unit_price(), 110);
+
+    // A new sequence number isn't a replacement.
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 1, 1)).is_ok());
+}
+
+#[test]
+fn test_max_gas_unit_price() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_gas_unit_price = Some(1_000_000);
+    let mut pool = CoreMempool::new(&config);
+
+    let txn = TestTransaction::new(0, 0, u64::MAX).make_signed_transaction();
+    let status = pool.add_txn(txn, u64::MAX, 0, TimelineState::NotReady);
+    assert_eq!(status.code, MempoolStatusCode::GasUnitPriceTooHigh);
+    assert!(pool.get_transaction_store().get_transactions().is_empty());
+
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 0, 1_000_000)).is_ok());
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 0, 100)).is_ok());
+}
+
+struct FixedBalanceProvider {
+    balance: u64,
+}
+
+impl BalanceProvider for FixedBalanceProvider {
+    fn balance(&self, _address: &AccountAddress) -> Option<u64> {
+        Some(self.balance)
+    }
+}
+
+#[test]
+fn test_balance_provider() {
+    let mut pool = setup_mempool().0;
+    pool.set_balance_provider(Arc::new(FixedBalanceProvider { balance: 1_000 }));
+
+    // Max fee of 100 gas units at 20 per unit exceeds the balance.
+    let txn = TestTransaction::new(0, 0, 20).make_signed_transaction_with_max_gas_amount(100);
+    let status = pool.add_txn(txn, 20, 0, TimelineState::NotReady);
+    assert_eq!(status.code, MempoolStatusCode::InsufficientBalance);
+    assert!(pool.get_transaction_store().get_transactions().is_empty());
+
+    let txn = TestTransaction::new(0, 0, 10).make_signed_transaction_with_max_gas_amount(100);
+    assert!(add_signed_txn(&mut pool, txn).is_ok());
+}
+
 #[test]
 fn test_ignore_same_transaction_submitted_to_mempool() {
     let (mut mempool, _) = setup_mempool();
@@ -123,6 +241,27 @@ fn test_ignore_same_transaction_submitted_to_mempool() {
     assert!(ret.is_ok())
 }
 
+#[test]
+fn test_refresh_ttl_on_resubmission() {
+    let mut config = NodeConfig::random();
+    config.mempool.refresh_ttl_on_resubmission = true;
+    let mut pool = CoreMempool::new(&config);
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, txn.clone()).unwrap();
+    let expiration_time = pool.dump()[0].expiration_time;
+
+    std::thread::sleep(Duration::from_millis(10));
+    let status = pool.add_txn(txn.clone(), 1, 0, TimelineState::NotReady);
+    assert_eq!(status.code, MempoolStatusCode::Accepted);
+    assert!(!status.message.is_empty());
+    let refreshed = pool.dump();
+    assert_eq!(refreshed.len(), 1);
+    assert!(refreshed[0].expiration_time > expiration_time);
+
+    pool.get_transaction_store().debug_assert_consistency();
+    assert_eq!(pool.get_batch(10, 1024, true, HashSet::new(), None), vec![txn]);
+}
+
 #[test]
 fn test_fail_for_same_gas_amount_and_not_same_expiration_time() {
     let (mut mempool, _) = setup_mempool();
@@ -250,11 +389,44 @@ fn test_system_ttl() {
     add_txn(&mut mempool, transaction.clone()).unwrap();
 
     // GC routine should clear transaction from first insert but keep last one.
-    mempool.gc();
-    let batch = mempool.get_batch(1, 1024, true, HashSet::new());
+    let expired_hash = TestTransaction::new(0, 0, 10).make_signed_transaction().committed_hash();
+    assert_eq!(mempool.gc(), vec![expired_hash]);
+    let batch = mempool.get_batch(1, 1024, true, HashSet::new(), None);
     assert_eq!(vec![transaction.make_signed_transaction()], batch);
 }
 
+#[test]
+fn test_system_ttl_jitter() {
+    // With no system timeout, txns only live for their jitter offset.
+    let mut config = NodeConfig::random();
+    config.mempool.system_transaction_timeout_secs = 0;
+    config.mempool.system_transaction_timeout_jitter_secs = 10;
+    config.mempool.randomization_seed = Some(0);
+    let mut mempool = CoreMempool::new(&config);
+
+    for address in 0..4 {
+        for seq in 0..25 {
+            add_txn(&mut mempool, TestTransaction::new(address, seq, 1)).unwrap();
+        }
+    }
+    let expiration_times: HashSet<_> = mempool
+        .dump()
+        .iter()
+        .map(|txn| {
+            let insertion_time = aptos_infallible::duration_since_epoch_at(&txn.insertion_time);
+            assert!(txn.expiration_time >= insertion_time);
+            assert!(txn.expiration_time < insertion_time + Duration::from_secs(10));
+            txn.expiration_time
+        })
+        .collect();
+    assert!(expiration_times.len() > 1);
+
+    // The txns are not all gc-ed at the same boundary.
+    let num_expired = mempool.gc().len();
+    assert!(num_expired < 100);
+    assert_eq!(mempool.dump().len(), 100 - num_expired);
+}
+
 #[test]
 fn test_commit_callback() {
     // Consensus commit callback should unlock txns in parking lot.
@@ -263,11 +435,31 @@ fn test_commit_callback() {
     let txns = add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 6, 1)]);
 
     // Check that pool is empty.
-    assert!(pool.get_batch(1, 1024, true, HashSet::new()).is_empty());
+    assert!(pool.get_batch(1, 1024, true, HashSet::new(), None).is_empty());
     // Transaction 5 got back from consensus.
     pool.commit_transaction(&TestTransaction::get_address(1), 5);
     // Verify that we can execute transaction 6.
-    assert_eq!(pool.get_batch(1, 1024, true, HashSet::new())[0], txns[0]);
+    assert_eq!(pool.get_batch(1, 1024, true, HashSet::new(), None)[0], txns[0]);
+}
+
+#[test]
+fn test_commit_removes_parked_txns_below() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(1, 2, 1),
+        TestTransaction::new(1, 4, 1),
+        TestTransaction::new(1, 7, 1),
+    ]);
+    assert_eq!(pool.get_parking_lot_size(), 3);
+
+    // Committing 5 invalidates the parked txns 2 and 4 right away, and unblocks nothing as 6
+    // is missing.
+    pool.commit_transaction(&TestTransaction::get_address(1), 5);
+    let address = TestTransaction::get_address(1);
+    assert!(!pool.contains(&address, 2));
+    assert!(!pool.contains(&address, 4));
+    assert_eq!(pool.get_parking_lot_transactions(), vec![(address, 7)]);
+    pool.get_transaction_store().debug_assert_consistency();
 }
 
 #[test]
@@ -293,6 +485,86 @@ fn test_reset_sequence_number_on_failure() {
     assert!(add_txn(&mut pool, TestTransaction::new(1, 0, 1)).is_ok());
 }
 
+#[test]
+fn test_remove_transactions() {
+    let txns: Vec<_> = (0..7).map(|seq| TestTransaction::new(1, seq, 1)).collect();
+    let committed: Vec<_> = (0..5)
+        .map(|seq| (TestTransaction::get_address(1), seq))
+        .collect();
+
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(&mut pool, txns.clone());
+    let mut batch_pool = setup_mempool().0;
+    add_txns_to_mempool(&mut batch_pool, txns.clone());
+
+    for (sender, sequence_number) in &committed {
+        pool.commit_transaction(sender, *sequence_number);
+    }
+    batch_pool.remove_transactions(&committed, false);
+
+    batch_pool.get_transaction_store().debug_assert_consistency();
+    assert_eq!(
+        batch_pool.get_transaction_store().size_bytes(),
+        pool.get_transaction_store().size_bytes()
+    );
+    let batch = batch_pool.get_batch(10, 10240, true, HashSet::new(), None);
+    assert_eq!(view(batch.clone()), vec![5, 6]);
+    assert_eq!(batch, pool.get_batch(10, 10240, true, HashSet::new(), None));
+
+    // Rejections free the same space as single rejections.
+    let mut pool = setup_mempool().0;
+    let signed_txns = add_txns_to_mempool(&mut pool, txns.clone());
+    let mut batch_pool = setup_mempool().0;
+    add_txns_to_mempool(&mut batch_pool, txns);
+    for ((sender, sequence_number), txn) in committed.iter().zip(&signed_txns) {
+        pool.reject_transaction(sender, *sequence_number, &txn.committed_hash());
+    }
+    batch_pool.remove_transactions(&committed, true);
+
+    batch_pool.get_transaction_store().debug_assert_consistency();
+    assert_eq!(
+        batch_pool.get_transaction_store().size_bytes(),
+        pool.get_transaction_store().size_bytes()
+    );
+    assert_eq!(
+        batch_pool.get_batch(10, 10240, true, HashSet::new(), None),
+        pool.get_batch(10, 10240, true, HashSet::new(), None)
+    );
+}
+
+#[test]
+fn test_duplicate_commit_notification() {
+    let address = TestTransaction::get_address(1);
+    let txns = vec![
+        TestTransaction::new(1, 0, 1),
+        TestTransaction::new(1, 1, 1),
+        TestTransaction::new(1, 2, 1),
+        TestTransaction::new(1, 4, 1),
+    ];
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(&mut pool, txns.clone());
+    let mut duplicate_pool = setup_mempool().0;
+    add_txns_to_mempool(&mut duplicate_pool, txns);
+
+    pool.commit_transaction(&address, 1);
+    duplicate_pool.commit_transaction(&address, 1);
+    assert!(duplicate_pool.get_transaction_store().is_committed(&address, 1));
+    duplicate_pool.commit_transaction(&address, 1);
+    duplicate_pool.commit_transaction(&address, 0);
+    duplicate_pool.remove_transactions(&[(address, 1)], false);
+
+    duplicate_pool.get_transaction_store().debug_assert_consistency();
+    assert_eq!(
+        duplicate_pool.get_transaction_store().get_sequence_number(&address),
+        pool.get_transaction_store().get_sequence_number(&address)
+    );
+    assert_eq!(duplicate_pool.next_sequence_number(&address), Some(3));
+    assert_eq!(duplicate_pool.get_parking_lot_size(), pool.get_parking_lot_size());
+    let batch = duplicate_pool.get_batch(10, 10240, true, HashSet::new(), None);
+    assert_eq!(view(batch.clone()), vec![2]);
+    assert_eq!(batch, pool.get_batch(10, 10240, true, HashSet::new(), None));
+}
+
 fn view(txns: Vec<SignedTransaction>) -> Vec<u64> {
     txns.iter()
         .map(SignedTransaction::sequence_number)
@@ -314,6 +586,9 @@ fn test_timeline() {
     assert_eq!(view(timeline), vec![0, 1]);
     // Txns 3 and 5 should be in parking lot.
     assert_eq!(2, pool.get_parking_lot_size());
+    let parked: HashSet<_> = pool.get_parking_lot_transactions().into_iter().collect();
+    let address = TestTransaction::get_address(1);
+    assert_eq!(parked, [(address, 3), (address, 5)].into_iter().collect());
 
     // Add txn 2 to unblock txn3.
     add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 2, 1)]);
@@ -326,6 +601,14 @@ fn test_timeline() {
     let (timeline, _) = pool.read_timeline(&vec![2].into(), 10);
     assert_eq!(view(timeline), vec![2, 3]);
 
+    // The total counts all the ready txns since the start, even past `count`.
+    let (timeline, _, total) = pool.read_timeline_with_total(&vec![0].into(), 1);
+    assert_eq!(view(timeline), vec![0]);
+    assert_eq!(total, 4);
+    let (timeline, _, total) = pool.read_timeline_with_total(&vec![2].into(), 10);
+    assert_eq!(view(timeline), vec![2, 3]);
+    assert_eq!(total, 2);
+
     // Simulate callback from consensus to unblock txn 5.
     pool.commit_transaction(&TestTransaction::get_address(1), 4);
     let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
@@ -334,6 +617,117 @@ fn test_timeline() {
     assert_eq!(0, pool.get_parking_lot_size());
 }
 
+#[test]
+fn test_classify_all() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(1, 0, 1),
+        TestTransaction::new(1, 1, 1),
+        TestTransaction::new(1, 3, 1),
+        TestTransaction::new(1, 5, 1),
+        TestTransaction::new(2, 0, 1),
+        TestTransaction::new(3, 1, 1),
+    ]);
+
+    let (ready, parked) = pool.classify_all();
+    let sort = |mut pointers: Vec<(AccountAddress, u64)>| {
+        pointers.sort_unstable();
+        pointers
+    };
+    let (address_1, address_2, address_3) = (
+        TestTransaction::get_address(1),
+        TestTransaction::get_address(2),
+        TestTransaction::get_address(3),
+    );
+    assert_eq!(ready, sort(vec![(address_1, 0), (address_1, 1), (address_2, 0)]));
+    assert_eq!(parked, sort(vec![(address_1, 3), (address_1, 5), (address_3, 1)]));
+    // Same split as the timeline and the parking lot.
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(ready.len(), timeline.len());
+    assert_eq!(parked, sort(pool.get_parking_lot_transactions()));
+
+    // Filling the gap moves txn 3 to the ready ones.
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 2, 1)]);
+    let (ready, parked) = pool.classify_all();
+    assert_eq!(ready.len(), 5);
+    assert!(ready.contains(&(address_1, 3)));
+    assert_eq!(parked, sort(vec![(address_1, 5), (address_3, 1)]));
+}
+
+#[test]
+fn test_promotion_order() {
+    let mut config = NodeConfig::random();
+    config.mempool.broadcast_buckets = vec![0];
+    let mut pool = CoreMempool::new(&config);
+    // A chain behind a gap at 0, with ranking scores that don't follow the sequence numbers.
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(1, 1, 1),
+        TestTransaction::new(1, 3, 10),
+        TestTransaction::new(1, 2, 5),
+    ]);
+    assert_eq!(pool.get_parking_lot_size(), 3);
+
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 0, 1)]);
+    assert_eq!(pool.get_parking_lot_size(), 0);
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    let sequence_numbers: Vec<_> = timeline
+        .iter()
+        .map(SignedTransaction::sequence_number)
+        .collect();
+    assert_eq!(sequence_numbers, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_read_timeline_compact() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+        TestTransaction::new(1, 0, 3),
+        TestTransaction::new(1, 2, 1),
+    ]);
+
+    for (start, count) in [(0, 10), (0, 2), (2, 10), (10, 10)] {
+        let (timeline, timeline_id) = pool.read_timeline(&vec![start].into(), count);
+        let (bytes, compact_timeline_id) = pool.read_timeline_compact(&vec![start].into(), count);
+        let decoded: Vec<SignedTransaction> = bcs::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, timeline);
+        assert_eq!(compact_timeline_id, timeline_id);
+    }
+}
+
+#[test]
+fn test_current_timeline_index() {
+    let mut pool = setup_mempool().0;
+    assert_eq!(pool.current_timeline_index(), vec![0].into());
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+        TestTransaction::new(1, 0, 1),
+        TestTransaction::new(1, 2, 1),
+    ]);
+
+    // A window of the timeline ends at or before the current index.
+    let (timeline, window_end) = pool.read_timeline(&vec![0].into(), 2);
+    assert_eq!(timeline.len(), 2);
+    let current = pool.current_timeline_index();
+    assert!(window_end < current);
+    let (timeline, window_end) = pool.read_timeline(&window_end, 10);
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(window_end, current);
+
+    // Parked txns are not in the timeline.
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(2, 1, 1)]);
+    assert_eq!(pool.current_timeline_index(), current);
+    // Ready ones move the current index past the ones already read.
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 1, 1)]);
+    let new_current = pool.current_timeline_index();
+    assert!(new_current > current);
+    let (timeline, window_end) = pool.read_timeline(&current, 10);
+    assert_eq!(view(timeline), vec![1, 2]);
+    assert_eq!(window_end, new_current);
+}
+
 #[test]
 fn test_multi_bucket_timeline() {
     let mut pool = setup_mempool_with_broadcast_buckets(vec![0, 101, 201]).0;
@@ -469,6 +863,28 @@ fn test_capacity() {
     assert!(add_txn(&mut pool, TestTransaction::new(1, 2, 1)).is_ok());
 }
 
+#[test]
+fn test_max_txns_per_account() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_txns_per_account = Some(3);
+    let mut pool = CoreMempool::new(&config);
+
+    for seq in 0..3 {
+        assert!(add_txn(&mut pool, TestTransaction::new(1, seq, 1)).is_ok());
+    }
+    // The account is at the cap, way below the global capacity.
+    let txn = TestTransaction::new(1, 3, 1).make_signed_transaction();
+    let status = pool.add_txn(txn, 1, 0, TimelineState::NotReady);
+    assert_eq!(status.code, MempoolStatusCode::TooManyTransactionsForAccount);
+    // Other accounts and replacements are not affected.
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 0, 1)).is_ok());
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 2, 5)).is_ok());
+
+    // Committed transactions free capacity back.
+    pool.commit_transaction(&TestTransaction::get_address(1), 0);
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 3, 1)).is_ok());
+}
+
 #[test]
 fn test_capacity_bytes() {
     let capacity_bytes = 2_048;
@@ -556,7 +972,7 @@ fn test_parking_lot_eviction() {
     }
     // Make sure that we have correct txns in Mempool.
     let mut txns: Vec<_> = pool
-        .get_batch(5, 5120, true, HashSet::new())
+        .get_batch(5, 5120, true, HashSet::new(), None)
         .iter()
         .map(SignedTransaction::sequence_number)
         .collect();
@@ -567,6 +983,51 @@ fn test_parking_lot_eviction() {
     assert!(add_txn(&mut pool, TestTransaction::new(0, 2, 1)).is_err());
 }
 
+#[test]
+fn test_parking_lot_eviction_event() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 2;
+    let (eviction_sender, mut eviction_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let mut pool = CoreMempool::with_eviction_sender(&config, Some(eviction_sender));
+
+    let parked = TestTransaction::new(1, 5, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, parked.clone()).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert!(eviction_receiver.try_recv().is_err());
+
+    // Mempool is full, the parked txn makes room for a ready one.
+    add_txn(&mut pool, TestTransaction::new(2, 0, 1)).unwrap();
+    assert_eq!(eviction_receiver.try_recv().unwrap(), EvictionEvent {
+        hash: parked.committed_hash(),
+        sender: TestTransaction::get_address(1),
+        sequence_number: 5,
+        reason: EvictionReason::CapacityFull,
+    });
+    assert!(eviction_receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_parking_lot_eviction_skips_priority_senders() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.priority_senders = vec![TestTransaction::get_address(1)];
+    let mut pool = CoreMempool::new(&config);
+    for (address, seq) in [(1, 5), (1, 6), (2, 5)] {
+        add_txn(&mut pool, TestTransaction::new(address, seq, 1)).unwrap();
+    }
+
+    // Mempool is full, only the parked txn of the other sender makes room for a ready one.
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    let mut parked = pool.get_parking_lot_transactions();
+    parked.sort();
+    let priority_sender = TestTransaction::get_address(1);
+    assert_eq!(parked, vec![(priority_sender, 5), (priority_sender, 6)]);
+
+    // Only txns of priority senders are left in the parking lot, the insertion is rejected.
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 1, 1)).is_err());
+    assert_eq!(pool.get_parking_lot_transactions().len(), 2);
+}
+
 #[test]
 fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     let mut config = NodeConfig::random();
@@ -585,7 +1046,7 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
 
     // Make sure that we have correct txns in Mempool.
     let mut txns: Vec<_> = pool
-        .get_batch(5, 5120, true, HashSet::new())
+        .get_batch(5, 5120, true, HashSet::new(), None)
         .iter()
         .map(SignedTransaction::sequence_number)
         .collect();
@@ -597,72 +1058,910 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     for seq in not_ready_seq_nums {
         assert!(add_txn(&mut pool, TestTransaction::new(1, seq, 1)).is_err());
     }
+
+    // The failure is told apart from a full mempool with nothing to evict.
+    let status = pool.add_txn(
+        TestTransaction::new(1, 6, 1).make_signed_transaction(),
+        1,
+        0,
+        TimelineState::NotReady,
+    );
+    assert_eq!(status.code, MempoolStatusCode::MempoolIsFullNoEvictableParking);
 }
 
 #[test]
-fn test_gc_ready_transaction() {
-    let mut pool = setup_mempool().0;
-    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
-
-    // Insert in the middle transaction that's going to be expired.
-    let txn = TestTransaction::new(1, 1, 1).make_signed_transaction_with_expiration_time(0);
-    pool.add_txn(txn, 1, 0, TimelineState::NotReady);
-
-    // Insert few transactions after it.
-    // They are supposed to be ready because there's a sequential path from 0 to them.
-    add_txn(&mut pool, TestTransaction::new(1, 2, 1)).unwrap();
-    add_txn(&mut pool, TestTransaction::new(1, 3, 1)).unwrap();
+fn test_parking_lot_eviction_with_seed_is_reproducible() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 4;
+    config.mempool.randomization_seed = Some(42);
+
+    let run = |config: &NodeConfig| {
+        let mut pool = CoreMempool::new(config);
+        // Fill Mempool with non-ready txns, one per account. Distinct gas prices keep the
+        // priority order independent of insertion time.
+        for address in 0..4 {
+            add_txn(&mut pool, TestTransaction::new(address, 1, address as u64 + 1)).unwrap();
+        }
+        // Each ready txn evicts a randomly chosen txn from the parking lot.
+        for address in 0..4 {
+            let _ = add_txn(&mut pool, TestTransaction::new(address, 0, address as u64 + 1));
+        }
+        pool.get_batch(10, 10240, true, HashSet::new(), None)
+    };
 
-    // Check that all txns are ready.
-    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
-    assert_eq!(timeline.len(), 4);
+    let block = run(&config);
+    assert!(!block.is_empty());
+    assert_eq!(block, run(&config));
+}
 
-    // GC expired transaction.
-    pool.gc_by_expiration_time(Duration::from_secs(1));
+#[test]
+fn test_epoch_change_drop_parked() {
+    let mut config = NodeConfig::random();
+    config.mempool.epoch_change_policy = EpochChangePolicy::DropParked;
+    let mut pool = CoreMempool::new(&config);
+    for (address, seq) in [(0, 0), (0, 1), (0, 5), (1, 3)] {
+        add_txn(&mut pool, TestTransaction::new(address, seq, 1)).unwrap();
+    }
+    assert_eq!(pool.get_parking_lot_size(), 2);
 
-    // Make sure txns 2 and 3 became not ready and we can't read them from any API.
-    let block = pool.get_batch(1, 1024, true, HashSet::new());
-    assert_eq!(block.len(), 1);
-    assert_eq!(block[0].sequence_number(), 0);
+    pool.on_epoch_change(1);
+    assert_eq!(pool.get_parking_lot_size(), 0);
+    let transactions = pool.get_transaction_store().get_transactions();
+    assert!(!transactions.contains_key(&TestTransaction::get_address(1)));
+    let mut txns: Vec<_> = pool
+        .get_batch(10, 10240, true, HashSet::new(), None)
+        .iter()
+        .map(SignedTransaction::sequence_number)
+        .collect();
+    txns.sort_unstable();
+    assert_eq!(txns, vec![0, 1]);
+}
 
-    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
-    assert_eq!(timeline.len(), 1);
-    assert_eq!(timeline[0].sequence_number(), 0);
+#[test]
+fn test_epoch_change_drop_all() {
+    let mut config = NodeConfig::random();
+    config.mempool.epoch_change_policy = EpochChangePolicy::DropAll;
+    let mut pool = CoreMempool::new(&config);
+    pool.on_epoch_change(1);
+    for (address, seq) in [(0, 0), (0, 5), (1, 0)] {
+        add_txn(&mut pool, TestTransaction::new(address, seq, 1)).unwrap();
+    }
 
-    // Resubmit txn 1
-    add_txn(&mut pool, TestTransaction::new(1, 1, 1)).unwrap();
+    // Same epoch again, nothing should be dropped.
+    pool.on_epoch_change(1);
+    assert_eq!(pool.get_batch(10, 10240, true, HashSet::new(), None).len(), 2);
 
-    // Make sure txns 2 and 3 can be broadcast after txn 1 is resubmitted
-    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
-    assert_eq!(timeline.len(), 4);
+    pool.on_epoch_change(2);
+    assert!(pool.get_transaction_store().get_transactions().is_empty());
+    assert_eq!(pool.get_parking_lot_size(), 0);
+    assert!(pool.get_batch(10, 10240, true, HashSet::new(), None).is_empty());
 }
 
 #[test]
-fn test_clean_stuck_transactions() {
+fn test_list_transactions_pagination() {
     let mut pool = setup_mempool().0;
-    for seq in 0..5 {
-        add_txn(&mut pool, TestTransaction::new(0, seq, 1)).unwrap();
+    let mut expected = HashSet::new();
+    for address in 0..4 {
+        // Both ready and parked transactions.
+        for seq in [0, 1, 2, 5, 7] {
+            add_txn(&mut pool, TestTransaction::new(address, seq, 1)).unwrap();
+            expected.insert((TestTransaction::get_address(address), seq));
+        }
     }
-    let db_sequence_number = 10;
-    let txn = TestTransaction::new(0, db_sequence_number, 1).make_signed_transaction();
-    pool.add_txn(txn, 1, db_sequence_number, TimelineState::NotReady);
-    let block = pool.get_batch(1, 1024, true, HashSet::new());
-    assert_eq!(block.len(), 1);
-    assert_eq!(block[0].sequence_number(), 10);
+
+    let mut listed = vec![];
+    let mut cursor = None;
+    loop {
+        let (txns, next_cursor) = pool.list_transactions(cursor, 3);
+        assert!(txns.len() <= 3);
+        listed.extend(txns.iter().map(|txn| (txn.sender(), txn.sequence_number())));
+        if next_cursor.is_none() {
+            break;
+        }
+        cursor = next_cursor;
+    }
+
+    // Each transaction is listed exactly once, in a stable order.
+    assert_eq!(listed.len(), expected.len());
+    assert_eq!(listed.iter().cloned().collect::<HashSet<_>>(), expected);
+    assert!(listed.windows(2).all(|w| w[0] < w[1]));
 }
 
 #[test]
-fn test_get_transaction_by_hash() {
-    let mut pool = setup_mempool().0;
-    let db_sequence_number = 10;
-    let txn = TestTransaction::new(0, db_sequence_number, 1).make_signed_transaction();
-    pool.add_txn(txn.clone(), 1, db_sequence_number, TimelineState::NotReady);
-    let hash = txn.clone().committed_hash();
-    let ret = pool.get_by_hash(hash);
-    assert_eq!(ret, Some(txn));
+fn test_parking_lot_eviction_skips_protected_txns() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.min_protected_age_ms = 500;
+    let mut pool = CoreMempool::new(&config);
 
-    let ret = pool.get_by_hash(HashValue::random());
-    assert!(ret.is_none());
+    // Old non-ready txn, past its protection period by the time mempool fills up.
+    add_txn(&mut pool, TestTransaction::new(0, 5, 1)).unwrap();
+    std::thread::sleep(Duration::from_millis(600));
+    // Fresh non-ready txn, still protected, and a ready one to fill up Mempool.
+    add_txn(&mut pool, TestTransaction::new(1, 5, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(2, 0, 1)).unwrap();
+
+    // Inserting a ready txn into a full Mempool evicts the old parked txn.
+    add_txn(&mut pool, TestTransaction::new(3, 0, 1)).unwrap();
+    let transactions = pool.get_transaction_store().get_transactions();
+    assert!(!transactions.contains_key(&TestTransaction::get_address(0)));
+    assert!(transactions.contains_key(&TestTransaction::get_address(1)));
+    assert_eq!(pool.get_parking_lot_size(), 1);
+
+    // Only protected txns are left in the parking lot, so nothing can be evicted.
+    assert!(add_txn(&mut pool, TestTransaction::new(4, 0, 1)).is_err());
+}
+
+#[test]
+fn test_max_parked_seq_span() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_parked_seq_span = Some(10);
+    let mut pool = CoreMempool::new(&config);
+
+    add_txn(&mut pool, TestTransaction::new(0, 5, 1)).unwrap();
+    // Too far beyond the parked txn.
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 1_000, 1)).is_err());
+    // Within the span.
+    add_txn(&mut pool, TestTransaction::new(0, 15, 1)).unwrap();
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 16, 1)).is_err());
+    assert_eq!(pool.get_parking_lot_size(), 2);
+
+    // Ready txns don't count towards the span.
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 1_000, 1)).unwrap();
+    assert_eq!(pool.get_parking_lot_size(), 3);
+}
+
+#[test]
+fn test_evict_lower_priority_local_txn() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    config.mempool.evict_lower_priority_local_txns = true;
+    let mut pool = CoreMempool::new(&config);
+    // Fill Mempool with ready local txns, nothing is parked.
+    for (address, gas_price) in [(0, 2), (1, 1), (2, 3)] {
+        add_txn(&mut pool, TestTransaction::new(address, 0, gas_price)).unwrap();
+    }
+    assert_eq!(pool.get_parking_lot_size(), 0);
+
+    // A lower priority txn is still rejected.
+    assert!(add_txn(&mut pool, TestTransaction::new(3, 0, 1)).is_err());
+    // A higher priority one evicts the lowest priority txn.
+    add_txn(&mut pool, TestTransaction::new(3, 0, 5)).unwrap();
+    let transactions = pool.get_transaction_store().get_transactions();
+    assert!(!transactions.contains_key(&TestTransaction::get_address(1)));
+
+    let mut gas_prices: Vec<_> = pool
+        .get_batch(10, 10240, true, HashSet::new(), None)
+        .iter()
+        .map(SignedTransaction::gas_unit_price)
+        .collect();
+    gas_prices.sort_unstable();
+    assert_eq!(gas_prices, vec![2, 3, 5]);
+}
+
+#[test]
+fn test_evict_lower_priority_local_txn_account_fairness() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 5;
+    config.mempool.evict_lower_priority_local_txns = true;
+    // A dominant account holding 4 txns of gas 3, and a sparse one holding a txn of gas 2.
+    let txns = vec![
+        TestTransaction::new(0, 0, 3),
+        TestTransaction::new(0, 1, 3),
+        TestTransaction::new(0, 2, 3),
+        TestTransaction::new(0, 3, 3),
+        TestTransaction::new(1, 0, 2),
+    ];
+    let dominant = TestTransaction::get_address(0);
+    let sparse = TestTransaction::get_address(1);
+
+    // By default, the globally lowest priority txn is evicted.
+    let mut pool = CoreMempool::new(&config);
+    add_txns_to_mempool(&mut pool, txns.clone());
+    add_txn(&mut pool, TestTransaction::new(2, 0, 5)).unwrap();
+    let transactions = pool.get_transaction_store().get_transactions();
+    assert!(!transactions.contains_key(&sparse));
+    assert_eq!(transactions[&dominant].len(), 4);
+
+    // With the penalty, the last txn of the dominant account goes first, even at a higher gas.
+    config.mempool.eviction_account_penalty_pct = 50;
+    let mut pool = CoreMempool::new(&config);
+    add_txns_to_mempool(&mut pool, txns);
+    add_txn(&mut pool, TestTransaction::new(2, 0, 5)).unwrap();
+    let transactions = pool.get_transaction_store().get_transactions();
+    assert!(transactions.contains_key(&sparse));
+    assert_eq!(
+        transactions[&dominant].keys().copied().collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    // Txns with a ranking score above the incoming one are still never evicted.
+    assert!(add_txn(&mut pool, TestTransaction::new(3, 0, 2)).is_err());
+}
+
+#[test]
+fn test_gas_price_histogram() {
+    let mut pool = setup_mempool().0;
+    for (address, gas_price) in [(0, 1), (1, 5), (2, 10), (3, 10), (4, 20), (5, 100)] {
+        add_txn(&mut pool, TestTransaction::new(address, 0, gas_price)).unwrap();
+    }
+    // Parked txns are not counted.
+    add_txn(&mut pool, TestTransaction::new(0, 2, 10)).unwrap();
+
+    assert_eq!(pool.gas_price_histogram(&[5, 10, 50]), vec![1, 1, 3, 1]);
+    assert_eq!(pool.gas_price_histogram(&[]), vec![6]);
+    assert_eq!(pool.gas_price_histogram(&[1_000]), vec![6, 0]);
+}
+
+#[test]
+fn test_gas_price_histogram_bucket_boundaries() {
+    let mut pool = setup_mempool().0;
+    for (address, gas_price) in [(0, 1), (1, 3), (2, 3), (3, 5)] {
+        add_txn(&mut pool, TestTransaction::new(address, 0, gas_price)).unwrap();
+    }
+    add_txn(&mut pool, TestTransaction::new(1, 2, 3)).unwrap();
+
+    // Buckets are [0, 3), [3, 5) and [5, inf), a boundary belongs to the bucket it starts.
+    assert_eq!(pool.gas_price_histogram(&[3, 5]), vec![1, 2, 1]);
+    assert_eq!(pool.gas_price_histogram(&[1, 4]), vec![0, 3, 1]);
+    assert_eq!(pool.gas_price_histogram(&[6]), vec![4, 0]);
+}
+
+#[test]
+fn test_batched_gas_price_updates() {
+    let mut config = NodeConfig::random();
+    config.mempool.update_batch_window_ms = 60_000;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 3)).unwrap();
+
+    // Rapid updates of the same txn are staged, without touching the indexes.
+    for gas_price in 2..=5 {
+        add_txn(&mut pool, TestTransaction::new(0, 0, gas_price)).unwrap();
+    }
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 0, 4)).is_err());
+    let gas_prices = |pool: &CoreMempool| {
+        let mut gas_prices: Vec<_> = pool
+            .get_batch(10, 10240, true, HashSet::new(), None)
+            .iter()
+            .map(SignedTransaction::gas_unit_price)
+            .collect();
+        gas_prices.sort_unstable();
+        gas_prices
+    };
+    assert_eq!(gas_prices(&pool), vec![1, 3]);
+
+    // All of them are applied with a single index update.
+    assert_eq!(pool.apply_pending_updates(), 1);
+    assert_eq!(gas_prices(&pool), vec![3, 5]);
+    assert_eq!(pool.apply_pending_updates(), 0);
+}
+
+#[test]
+fn test_transactions_older_than() {
+    let mut pool = setup_mempool().0;
+
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    add_txn(&mut pool, TestTransaction::new(2, 0, 1)).unwrap();
+
+    let old_txns = pool.transactions_older_than(Duration::from_millis(200));
+    let txns: Vec<_> = old_txns
+        .iter()
+        .map(|(address, sequence_number, _)| (*address, *sequence_number))
+        .collect();
+    assert_eq!(txns.len(), 3);
+    assert!(txns.contains(&(TestTransaction::get_address(0), 0)));
+    assert!(txns.contains(&(TestTransaction::get_address(0), 1)));
+    assert_eq!(txns[2], (TestTransaction::get_address(1), 0));
+    assert!(old_txns[0].2 >= Duration::from_millis(600));
+    assert!(old_txns[2].2 >= Duration::from_millis(300));
+
+    assert!(pool.transactions_older_than(Duration::from_secs(60)).is_empty());
+}
+
+#[test]
+fn test_oldest_transaction_age() {
+    let mut pool = setup_mempool().0;
+    assert_eq!(pool.oldest_transaction_age(SystemTime::now()), None);
+
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    let insertion_time = pool.dump()[0].insertion_time;
+    std::thread::sleep(Duration::from_millis(10));
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+
+    // The age is measured against the given time, from the oldest insertion.
+    let now = insertion_time + Duration::from_secs(30);
+    assert_eq!(pool.oldest_transaction_age(now), Some(Duration::from_secs(30)));
+    assert_eq!(pool.oldest_transaction_age(insertion_time), Some(Duration::ZERO));
+    // A clock behind the insertion time reports no age rather than failing.
+    let before = insertion_time - Duration::from_secs(1);
+    assert_eq!(pool.oldest_transaction_age(before), Some(Duration::ZERO));
+}
+
+#[test]
+fn test_index_consistency() {
+    let mut pool = setup_mempool().0;
+
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 2, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(2, 0, 3)).unwrap();
+    pool.get_transaction_store().debug_assert_consistency();
+
+    pool.commit_transaction(&TestTransaction::get_address(0), 0);
+    let hash = TestTransaction::new(2, 0, 3)
+        .make_signed_transaction()
+        .committed_hash();
+    pool.reject_transaction(&TestTransaction::get_address(2), 0, &hash);
+    pool.get_transaction_store().debug_assert_consistency();
+}
+
+#[test]
+#[should_panic(expected = "Hash index out of sync")]
+fn test_index_consistency_detects_corruption() {
+    let mut pool = setup_mempool().0;
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+
+    // Drop a transaction from the hash index only.
+    let hash = TestTransaction::new(0, 1, 1)
+        .make_signed_transaction()
+        .committed_hash();
+    pool.get_transaction_store_mut().hash_index_mut().remove(&hash);
+    pool.get_transaction_store().debug_assert_consistency();
+}
+
+#[test]
+fn test_get_transactions_by_sender() {
+    let mut pool = setup_mempool().0;
+    let address = TestTransaction::get_address(1);
+
+    // Txns 3 and 5 are parked.
+    let txns = vec![
+        TestTransaction::new(1, 0, 1),
+        TestTransaction::new(1, 1, 1),
+        TestTransaction::new(1, 3, 1),
+        TestTransaction::new(1, 5, 1),
+    ];
+    let txns = add_txns_to_mempool(&mut pool, txns);
+    add_txn(&mut pool, TestTransaction::new(2, 0, 1)).unwrap();
+    assert_eq!(pool.get_parking_lot_size(), 2);
+    assert_eq!(pool.get_transactions_by_sender(&address), txns);
+
+    pool.commit_transaction(&address, 0);
+    assert_eq!(pool.get_transactions_by_sender(&address), txns[1..]);
+    pool.reject_transaction(&address, 3, &txns[2].clone().committed_hash());
+    assert_eq!(pool.get_transactions_by_sender(&address), vec![
+        txns[1].clone(),
+        txns[3].clone(),
+    ]);
+    assert!(pool.get_transactions_by_sender(&TestTransaction::get_address(3)).is_empty());
+}
+
+#[test]
+fn test_peek_next() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 5),
+        TestTransaction::new(1, 0, 3),
+        TestTransaction::new(2, 1, 10),
+    ]);
+    let mut exclude = HashSet::new();
+
+    // Txn 1 of account 0 has a higher gas price, but it can't go before txn 0.
+    assert_eq!(pool.peek_next(&exclude), Some(txns[2].clone()));
+    exclude.insert(txns[2].committed_hash());
+    assert_eq!(pool.peek_next(&exclude), Some(txns[0].clone()));
+    exclude.insert(txns[0].committed_hash());
+    assert_eq!(pool.peek_next(&exclude), Some(txns[1].clone()));
+    exclude.insert(txns[1].committed_hash());
+    // The txn of account 2 isn't ready.
+    assert_eq!(pool.peek_next(&exclude), None);
+
+    // Peeking has no side effects.
+    assert_eq!(pool.get_batch(10, 1024, true, HashSet::new(), None).len(), 3);
+}
+
+#[test]
+fn test_peek_batch() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 5),
+        TestTransaction::new(1, 0, 3),
+        TestTransaction::new(2, 0, 2),
+    ]);
+    let exclude: HashSet<_> = [(TestTransaction::get_address(2), 0)].into_iter().collect();
+
+    let batch = pool.peek_batch(10, 1024, exclude.clone());
+    assert_eq!(batch, vec![txns[2].clone(), txns[0].clone(), txns[1].clone()]);
+    assert_eq!(pool.peek_batch(10, 1024, exclude.clone()), batch);
+    assert_eq!(pool.get_batch(10, 1024, true, exclude, None), batch);
+
+    assert_eq!(pool.peek_batch(2, 1024, HashSet::new()), batch[..2]);
+}
+
+#[test]
+fn test_get_batch_excluding_senders() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 5),
+        TestTransaction::new(1, 0, 3),
+        TestTransaction::new(1, 1, 2),
+    ]);
+    let exclude_senders: HashSet<_> = [TestTransaction::get_address(1)].into_iter().collect();
+
+    let batch = pool.get_batch_excluding_senders(10, 1024, true, HashSet::new(), &exclude_senders);
+    assert_eq!(batch, vec![txns[0].clone(), txns[1].clone()]);
+
+    // The transactions of the excluded sender stay in mempool.
+    let batch = pool.get_batch(10, 1024, true, HashSet::new(), None);
+    assert_eq!(batch.len(), 4);
+    assert_eq!(batch[0], txns[2]);
+}
+
+#[test]
+fn test_get_batch_snapshot() {
+    let mempool = Arc::new(Mutex::new(setup_mempool().0));
+    add_txns_to_mempool(&mut mempool.lock(), vec![
+        TestTransaction::new(0, 1, 1),
+        TestTransaction::new(1, 0, 1),
+    ]);
+    let address_0 = TestTransaction::get_address(0);
+    let address_1 = TestTransaction::get_address(1);
+
+    let inserted = Arc::new(AtomicBool::new(false));
+    let batch = {
+        let pool = mempool.lock();
+        // Txn 0 promotes the parked txn 1 of the same sender, while the batch is pulled.
+        let inserter = {
+            let mempool = mempool.clone();
+            let inserted = inserted.clone();
+            thread::spawn(move || {
+                add_txn(&mut mempool.lock(), TestTransaction::new(0, 0, 1)).unwrap();
+                inserted.store(true, Ordering::SeqCst);
+            })
+        };
+        // Simulate a long get_batch call.
+        thread::sleep(Duration::from_millis(100));
+        let batch = pool.get_batch(10, 10240, true, HashSet::new(), None);
+        assert!(!inserted.load(Ordering::SeqCst));
+        drop(pool);
+        inserter.join().unwrap();
+        batch
+    };
+    assert!(inserted.load(Ordering::SeqCst));
+    let pointers = |txns: Vec<SignedTransaction>| -> Vec<_> {
+        txns.iter()
+            .map(|txn| (txn.sender(), txn.sequence_number()))
+            .sorted()
+            .collect()
+    };
+    assert_eq!(pointers(batch), vec![(address_1, 0)]);
+
+    // The next call reflects the insertion and the promotion.
+    let batch = mempool
+        .lock()
+        .get_batch(10, 10240, true, HashSet::new(), None);
+    let mut expected = vec![(address_0, 0), (address_0, 1), (address_1, 0)];
+    expected.sort();
+    assert_eq!(pointers(batch), expected);
+}
+
+#[test]
+fn test_get_batch_including_parking() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 6, 1),
+        TestTransaction::new(1, 0, 2),
+    ]);
+
+    // The parked txn is never part of a normal batch.
+    let batch = pool.get_batch(10, 10240, true, HashSet::new(), None);
+    assert_eq!(batch, vec![txns[2].clone(), txns[0].clone()]);
+
+    // It follows the ready ones when the parking lot is included.
+    let batch = pool.get_batch_including_parking(10, 10240, HashSet::new());
+    assert_eq!(batch, vec![txns[2].clone(), txns[0].clone(), txns[1].clone()]);
+    assert_eq!(pool.get_batch_including_parking(2, 10240, HashSet::new()).len(), 2);
+    let seen = [(TestTransaction::get_address(0), 6)].into_iter().collect();
+    assert_eq!(pool.get_batch_including_parking(10, 10240, seen).len(), 2);
+}
+
+#[test]
+fn test_get_batch_by_gas() {
+    let mut pool = setup_mempool().0;
+    // Highest gas price first, with increasing max gas amounts.
+    let txns = vec![
+        TestTransaction::new(0, 0, 3).make_signed_transaction_with_max_gas_amount(100),
+        TestTransaction::new(1, 0, 2).make_signed_transaction_with_max_gas_amount(200),
+        TestTransaction::new(2, 0, 1).make_signed_transaction_with_max_gas_amount(300),
+    ];
+    for txn in &txns {
+        add_signed_txn(&mut pool, txn.clone()).unwrap();
+    }
+
+    assert_eq!(pool.get_batch_by_gas(10, 600, HashSet::new()), txns);
+    // A txn that would exceed the limit ends the batch.
+    assert_eq!(pool.get_batch_by_gas(10, 599, HashSet::new()), txns[..2].to_vec());
+    assert_eq!(pool.get_batch_by_gas(10, 300, HashSet::new()), txns[..2].to_vec());
+    assert_eq!(pool.get_batch_by_gas(10, 299, HashSet::new()), txns[..1].to_vec());
+    assert!(pool.get_batch_by_gas(10, 99, HashSet::new()).is_empty());
+    assert_eq!(pool.get_batch_by_gas(1, 600, HashSet::new()), txns[..1].to_vec());
+
+    // Seen txns don't count towards the limit.
+    let seen = [(TestTransaction::get_address(0), 0)].into_iter().collect();
+    assert_eq!(pool.get_batch_by_gas(10, 500, seen), txns[1..].to_vec());
+}
+
+#[test]
+fn test_fee_per_byte_ranking() {
+    let mut config = NodeConfig::random();
+    config.mempool.broadcast_buckets = vec![0];
+    config.mempool.ranking_strategy = RankingStrategyType::FeePerByte;
+    let mut pool = CoreMempool::new(&config);
+
+    // Same size, but the cheaper txn may pay a higher total fee.
+    let expensive = TestTransaction::new(0, 0, 2).make_signed_transaction_with_max_gas_amount(100);
+    let cheap = TestTransaction::new(1, 0, 1).make_signed_transaction_with_max_gas_amount(1000);
+    add_signed_txn(&mut pool, expensive.clone()).unwrap();
+    add_signed_txn(&mut pool, cheap.clone()).unwrap();
+
+    assert_eq!(pool.get_batch(10, 1024, true, HashSet::new(), None), vec![cheap, expensive]);
+}
+
+#[test]
+fn test_sequence_number_penalty_ranking() {
+    let mut config = NodeConfig::random();
+    config.mempool.broadcast_buckets = vec![0];
+    let long_run: Vec<_> = (0..10).map(|seq| TestTransaction::new(0, seq, 12)).collect();
+    let fresh = TestTransaction::new(1, 0, 5);
+
+    // Without penalty, the whole run outranks the cheaper txn of the other account.
+    let mut pool = CoreMempool::new(&config);
+    add_txns_to_mempool(&mut pool, long_run.clone());
+    add_txn(&mut pool, fresh.clone()).unwrap();
+    let sender = TestTransaction::get_address(0);
+    let fresh_position = pool.estimate_position(&TestTransaction::get_address(1), 0).unwrap();
+    assert_eq!(fresh_position, 10);
+
+    // With a 50% penalty per sequence number, the score of the run drops below 5 from sequence
+    // number 3 on (12 / (1 + 0.5 * 3) < 5).
+    config.mempool.sequence_number_penalty_pct = 50;
+    let mut pool = CoreMempool::new(&config);
+    add_txns_to_mempool(&mut pool, long_run);
+    add_txn(&mut pool, fresh).unwrap();
+    let fresh_position = pool.estimate_position(&TestTransaction::get_address(1), 0).unwrap();
+    for seq in 0..10 {
+        let position = pool.estimate_position(&sender, seq).unwrap();
+        if seq < 3 {
+            assert!(position < fresh_position);
+        } else {
+            assert!(position > fresh_position);
+        }
+    }
+
+    // Once sequence numbers 0 to 2 are committed, the distances of the rest of the run shrink,
+    // and their scores are raised accordingly.
+    pool.commit_transaction(&sender, 2);
+    let fresh_position = pool.estimate_position(&TestTransaction::get_address(1), 0).unwrap();
+    for seq in 3..10 {
+        let position = pool.estimate_position(&sender, seq).unwrap();
+        if seq < 6 {
+            assert!(position < fresh_position);
+        } else {
+            assert!(position > fresh_position);
+        }
+    }
+}
+
+#[test]
+fn test_export_import_parking_lot() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 2, 1),
+        TestTransaction::new(1, 1, 1),
+    ]);
+    let parked = |pool: &CoreMempool| -> HashSet<_> {
+        pool.export_parking_lot()
+            .into_iter()
+            .map(|(txn, insertion_time)| (txn.committed_hash(), insertion_time))
+            .collect()
+    };
+    let parked_hashes: HashSet<_> = parked(&pool).into_iter().map(|(hash, _)| hash).collect();
+    assert_eq!(
+        parked_hashes,
+        [txns[1].committed_hash(), txns[2].committed_hash()]
+            .into_iter()
+            .collect()
+    );
+
+    let mut imported_pool = setup_mempool().0;
+    let statuses = imported_pool.import_parking_lot(pool.export_parking_lot());
+    assert!(statuses
+        .iter()
+        .all(|status| status.code == MempoolStatusCode::Accepted));
+    assert_eq!(imported_pool.get_parking_lot_size(), 2);
+    assert_eq!(parked(&imported_pool), parked(&pool));
+    assert!(imported_pool
+        .get_batch(10, 1024, true, HashSet::new(), None)
+        .is_empty());
+}
+
+#[test]
+fn test_gas_price_tie_broken_by_system_expiration() {
+    let mut pool = setup_mempool().0;
+
+    // Ties are broken by the system expiration time, i.e. the time the txns entered mempool,
+    // and not by the expiration time of the txns themselves.
+    let later = TestTransaction::new(0, 0, 1).make_signed_transaction_with_expiration_time(2_000);
+    let earlier = TestTransaction::new(1, 0, 1).make_signed_transaction_with_expiration_time(1_000);
+    add_signed_txn(&mut pool, later.clone()).unwrap();
+    std::thread::sleep(Duration::from_millis(10));
+    add_signed_txn(&mut pool, earlier.clone()).unwrap();
+
+    assert_eq!(pool.get_batch(10, 1024, true, HashSet::new(), None), vec![later, earlier]);
+}
+
+#[test]
+fn test_random_expirations_pulled_in_arrival_order() {
+    let mut pool = setup_mempool().0;
+
+    // Like the txns of the random expiration transfer generator of the emitter: same gas price,
+    // expirations in no particular order. They are still pulled in the order they arrived.
+    let txns: Vec<_> = [3_000, 1_000, 4_000, 2_000]
+        .into_iter()
+        .enumerate()
+        .map(|(sender, expiration_secs)| {
+            TestTransaction::new(sender, 0, 1)
+                .make_signed_transaction_with_expiration_time(expiration_secs)
+        })
+        .collect();
+    for txn in &txns {
+        add_signed_txn(&mut pool, txn.clone()).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(pool.get_batch(10, 1024, true, HashSet::new(), None), txns);
+}
+
+#[test]
+fn test_get_batch_tie_break_seed() {
+    let mut config = NodeConfig::random();
+    config.mempool.broadcast_buckets = vec![0];
+    let mut pool = CoreMempool::new(&config);
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(1, 0, 1),
+        TestTransaction::new(2, 0, 1),
+    ]);
+    // Same gas price and system expiration time, so the order of the txns is only decided by
+    // the tie-break.
+    let mut txns = pool.dump();
+    let expiration_time = txns.iter().map(|txn| txn.expiration_time).max().unwrap();
+    for txn in &mut txns {
+        txn.expiration_time = expiration_time;
+    }
+    let pool = CoreMempool::load(&config, txns);
+
+    let senders = |batch: Vec<SignedTransaction>| -> Vec<_> {
+        batch.iter().map(SignedTransaction::sender).collect()
+    };
+    // Without a seed, ties are broken by sender.
+    let mut by_sender = senders(pool.get_batch(10, 1024, true, HashSet::new(), None));
+    assert_eq!(by_sender.len(), 3);
+    by_sender.sort_by(|a, b| b.cmp(a));
+    assert_eq!(senders(pool.get_batch(10, 1024, true, HashSet::new(), None)), by_sender);
+
+    let mut orders = HashSet::new();
+    for seed in 0..16 {
+        let order = senders(pool.get_batch(10, 1024, true, HashSet::new(), Some(seed)));
+        // The same seed always gives the same order.
+        assert_eq!(senders(pool.get_batch(10, 1024, true, HashSet::new(), Some(seed))), order);
+        orders.insert(order);
+    }
+    assert!(orders.len() > 1);
+}
+
+#[test]
+fn test_dump_and_load() {
+    let mut config = NodeConfig::random();
+    config.mempool.broadcast_buckets = vec![0];
+    let mut pool = CoreMempool::new(&config);
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 2),
+        TestTransaction::new(0, 3, 1),
+        TestTransaction::new(1, 2, 3),
+    ]);
+    let expired = TestTransaction::new(2, 0, 1).make_signed_transaction_with_expiration_time(0);
+    add_signed_txn(&mut pool, expired).unwrap();
+
+    let mut loaded_pool = CoreMempool::load(&config, pool.dump());
+    loaded_pool.get_transaction_store().debug_assert_consistency();
+    assert_eq!(loaded_pool.get_parking_lot_size(), 2);
+    let (timeline, _) = loaded_pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(timeline, vec![txns[0].clone(), txns[1].clone()]);
+    assert_eq!(loaded_pool.get_batch(10, 1024, true, HashSet::new(), None), vec![
+        txns[0].clone(),
+        txns[1].clone()
+    ]);
+
+    // Filling the gap makes the parked txn of account 0 ready.
+    add_txns_to_mempool(&mut loaded_pool, vec![TestTransaction::new(0, 2, 1)]);
+    assert_eq!(loaded_pool.get_parking_lot_size(), 1);
+}
+
+#[test]
+fn test_contains() {
+    let mut pool = setup_mempool().0;
+    let address = TestTransaction::get_address(0);
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 0, 1)]);
+    // Last executed sequence number is 0, so sequence number 6 is in the parking lot.
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 6, 1)]);
+    assert_eq!(pool.get_parking_lot_size(), 1);
+
+    assert!(pool.contains(&address, 0));
+    assert!(pool.contains(&address, 6));
+    assert!(!pool.contains(&address, 5));
+    assert!(!pool.contains(&TestTransaction::get_address(1), 0));
+}
+
+#[test]
+fn test_next_sequence_number() {
+    let mut pool = setup_mempool().0;
+    let address = TestTransaction::get_address(0);
+    assert_eq!(pool.next_sequence_number(&address), None);
+
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+        TestTransaction::new(0, 2, 1),
+    ]);
+    assert_eq!(pool.next_sequence_number(&address), Some(3));
+
+    // Parked transactions past a gap don't move it.
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 5, 1)]);
+    assert_eq!(pool.next_sequence_number(&address), Some(3));
+
+    // Without ready transactions, it is the committed sequence number.
+    pool.commit_transaction(&address, 3);
+    assert_eq!(pool.next_sequence_number(&address), Some(4));
+}
+
+#[test]
+fn test_estimate_position() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 2),
+        TestTransaction::new(1, 0, 5),
+        TestTransaction::new(2, 0, 1),
+        TestTransaction::new(2, 2, 10),
+    ]);
+
+    assert_eq!(pool.estimate_position(&TestTransaction::get_address(1), 0), Some(0));
+    assert_eq!(pool.estimate_position(&TestTransaction::get_address(0), 0), Some(1));
+    assert_eq!(pool.estimate_position(&TestTransaction::get_address(2), 0), Some(2));
+    // Parked, despite its gas price.
+    assert_eq!(pool.estimate_position(&TestTransaction::get_address(2), 2), None);
+    assert_eq!(pool.estimate_position(&TestTransaction::get_address(3), 0), None);
+}
+
+#[test]
+fn test_broadcast_contiguous_prefix_only() {
+    let mut config = NodeConfig::random();
+    config.mempool.broadcast_buckets = vec![0];
+    config.mempool.broadcast_contiguous_prefix_only = true;
+    let mut pool = CoreMempool::new(&config);
+
+    // Gapped submission: 5 stays parked until 1 to 4 are in.
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(1, 0, 1),
+        TestTransaction::new(1, 1, 1),
+        TestTransaction::new(1, 2, 1),
+        TestTransaction::new(1, 5, 1),
+    ]);
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(view(timeline), vec![0, 1, 2]);
+    assert_eq!(pool.get_parking_lot_size(), 1);
+
+    // Rejecting 1 leaves a gap, so 2 is no longer broadcast.
+    pool.reject_transaction(
+        &TestTransaction::get_address(1),
+        1,
+        &txns[1].clone().committed_hash(),
+    );
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(view(timeline), vec![0]);
+    assert_eq!(pool.get_parking_lot_size(), 2);
+
+    // Filling the gap makes the contiguous txns broadcastable again.
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(1, 1, 1),
+        TestTransaction::new(1, 3, 1),
+        TestTransaction::new(1, 4, 1),
+    ]);
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(view(timeline), vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(pool.get_parking_lot_size(), 0);
+}
+
+#[test]
+fn test_gc_ready_transaction() {
+    let mut pool = setup_mempool().0;
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+
+    // Insert in the middle transaction that's going to be expired.
+    let txn = TestTransaction::new(1, 1, 1).make_signed_transaction_with_expiration_time(0);
+    let expired_hash = txn.clone().committed_hash();
+    pool.add_txn(txn, 1, 0, TimelineState::NotReady);
+
+    // Insert few transactions after it.
+    // They are supposed to be ready because there's a sequential path from 0 to them.
+    add_txn(&mut pool, TestTransaction::new(1, 2, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 3, 1)).unwrap();
+
+    // Check that all txns are ready.
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(timeline.len(), 4);
+
+    // GC expired transaction. Txns 2 and 3 are parked, but not evicted.
+    let gc_hashes = pool.gc_by_expiration_time(Duration::from_secs(1));
+    assert_eq!(gc_hashes, vec![expired_hash]);
+    assert_eq!(pool.get_parking_lot_size(), 2);
+
+    // Make sure txns 2 and 3 became not ready and we can't read them from any API.
+    let block = pool.get_batch(1, 1024, true, HashSet::new(), None);
+    assert_eq!(block.len(), 1);
+    assert_eq!(block[0].sequence_number(), 0);
+
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(timeline.len(), 1);
+    assert_eq!(timeline[0].sequence_number(), 0);
+
+    // Resubmit txn 1
+    add_txn(&mut pool, TestTransaction::new(1, 1, 1)).unwrap();
+
+    // Make sure txns 2 and 3 can be broadcast after txn 1 is resubmitted
+    let (timeline, _) = pool.read_timeline(&vec![0].into(), 10);
+    assert_eq!(timeline.len(), 4);
+}
+
+#[test]
+fn test_clean_stuck_transactions() {
+    let mut pool = setup_mempool().0;
+    for seq in 0..5 {
+        add_txn(&mut pool, TestTransaction::new(0, seq, 1)).unwrap();
+    }
+    let db_sequence_number = 10;
+    let txn = TestTransaction::new(0, db_sequence_number, 1).make_signed_transaction();
+    pool.add_txn(txn, 1, db_sequence_number, TimelineState::NotReady);
+    let block = pool.get_batch(1, 1024, true, HashSet::new(), None);
+    assert_eq!(block.len(), 1);
+    assert_eq!(block[0].sequence_number(), 10);
+}
+
+#[test]
+fn test_get_transaction_by_hash() {
+    let mut pool = setup_mempool().0;
+    let db_sequence_number = 10;
+    let txn = TestTransaction::new(0, db_sequence_number, 1).make_signed_transaction();
+    pool.add_txn(txn.clone(), 1, db_sequence_number, TimelineState::NotReady);
+    let hash = txn.clone().committed_hash();
+    let ret = pool.get_by_hash(hash);
+    assert_eq!(ret, Some(txn));
+
+    let ret = pool.get_by_hash(HashValue::random());
+    assert!(ret.is_none());
 }
 
 #[test]
@@ -690,6 +1989,141 @@ fn test_get_transaction_by_hash_after_the_txn_is_updated() {
     assert_eq!(txn_by_new_hash, Some(new_txn));
 }
 
+#[test]
+fn test_get_status_by_hash() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+        TestTransaction::new(0, 4, 1),
+        TestTransaction::new(1, 0, 5),
+    ]);
+
+    // Txn 4 is parked behind the missing txns 2 and 3.
+    let status = pool.get_status_by_hash(txns[2].committed_hash()).unwrap();
+    assert!(!status.ready);
+    assert_eq!(status.rank, None);
+    assert_eq!(status.blocking_gap, Some(2..4));
+    assert_eq!(status.ranking_score, 1);
+
+    // Ready txns have a rank and no gap.
+    let status = pool.get_status_by_hash(txns[3].committed_hash()).unwrap();
+    assert!(status.ready);
+    assert_eq!(status.rank, Some(0));
+    assert_eq!(status.blocking_gap, None);
+    let status = pool.get_status_by_hash(txns[1].committed_hash()).unwrap();
+    assert!(status.ready);
+    assert!(status.rank.is_some());
+    assert!(status.age < Duration::from_secs(60));
+
+    // Filling part of the gap narrows it.
+    add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 2, 1)]);
+    let status = pool.get_status_by_hash(txns[2].committed_hash()).unwrap();
+    assert_eq!(status.blocking_gap, Some(3..4));
+
+    assert!(pool.get_status_by_hash(HashValue::random()).is_none());
+}
+
+#[test]
+fn test_get_transaction_by_hash_in_full_pool() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 5;
+    let mut pool = CoreMempool::new(&config);
+    let parked = TestTransaction::new(1, 5, 1).make_signed_transaction();
+    add_signed_txn(&mut pool, parked.clone()).unwrap();
+    for seq in 0..4 {
+        add_txn(&mut pool, TestTransaction::new(0, seq, 1)).unwrap();
+    }
+
+    // The pool is full, the parked txn makes room for a ready one.
+    add_txn(&mut pool, TestTransaction::new(0, 4, 1)).unwrap();
+    assert!(add_txn(&mut pool, TestTransaction::new(2, 0, 1)).is_err());
+    let txns = pool.dump();
+    assert_eq!(txns.len(), 5);
+    for txn in txns {
+        assert_eq!(pool.get_by_hash(txn.get_committed_hash()), Some(txn.txn));
+    }
+    assert!(pool.get_by_hash(parked.committed_hash()).is_none());
+}
+
+#[test]
+fn test_get_transaction_by_hash_at_scale() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity_per_user = 10_000;
+    let mut pool = CoreMempool::new(&config);
+
+    let mut txns = vec![];
+    for seq in 0..2_500 {
+        for address in 0..4 {
+            let txn = TestTransaction::new(address, seq, 1).make_signed_transaction();
+            add_signed_txn(&mut pool, txn.clone()).unwrap();
+            txns.push(txn);
+        }
+    }
+    assert_eq!(pool.dump().len(), 10_000);
+
+    // Every txn has an entry in the hash index, and resolves to itself.
+    pool.get_transaction_store().debug_assert_consistency();
+    for txn in &txns {
+        assert_eq!(pool.get_by_hash(txn.committed_hash()).as_ref(), Some(txn));
+    }
+
+    // Lookups only go through the hash index, never through a scan of the pool: a txn missing
+    // from the index isn't found by hash, although it is still in the pool.
+    let txn = &txns[5_000];
+    pool.get_transaction_store_mut()
+        .hash_index_mut()
+        .remove(&txn.committed_hash());
+    assert_eq!(pool.get_by_hash(txn.committed_hash()), None);
+    assert_eq!(
+        pool.get_transaction_store()
+            .get(&txn.sender(), txn.sequence_number())
+            .as_ref(),
+        Some(txn)
+    );
+}
+
+#[test]
+fn test_admission_rate_limit() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_admissions_per_sec = Some(5);
+    let mut pool = CoreMempool::new(&config);
+
+    let statuses: Vec<_> = (0..10)
+        .map(|seq| {
+            let txn = TestTransaction::new(0, seq, 1).make_signed_transaction();
+            pool.add_txn(txn, 1, 0, TimelineState::NotReady).code
+        })
+        .collect();
+    assert_eq!(statuses[..5], [MempoolStatusCode::Accepted; 5]);
+    assert_eq!(statuses[5..], [MempoolStatusCode::RateLimited; 5]);
+    assert_eq!(pool.get_transactions_by_sender(&TestTransaction::get_address(0)).len(), 5);
+}
+
+#[test]
+fn test_per_sender_rate_limit() {
+    let mut config = NodeConfig::random();
+    config.mempool.per_sender_rate_limit = Some(3);
+    let mut pool = CoreMempool::new(&config);
+    let add = |pool: &mut CoreMempool, address: usize, seq: u64| {
+        let txn = TestTransaction::new(address, seq, 1).make_signed_transaction();
+        pool.add_txn(txn, 1, 0, TimelineState::NotReady).code
+    };
+
+    let statuses: Vec<_> = (0..5).map(|seq| add(&mut pool, 0, seq)).collect();
+    assert_eq!(statuses[..3], [MempoolStatusCode::Accepted; 3]);
+    assert_eq!(statuses[3..], [MempoolStatusCode::RateLimited; 2]);
+    // Other senders have their own budget.
+    for seq in 0..3 {
+        assert_eq!(add(&mut pool, 1, seq), MempoolStatusCode::Accepted);
+    }
+
+    // The budget of a sender refills over time, also once its idle rate limiter is dropped.
+    std::thread::sleep(Duration::from_millis(1_100));
+    pool.gc();
+    assert_eq!(add(&mut pool, 0, 3), MempoolStatusCode::Accepted);
+}
+
 #[test]
 fn test_bytes_limit() {
     let mut config = NodeConfig::random();
@@ -699,11 +2133,11 @@ fn test_bytes_limit() {
     for seq in 0..100 {
         add_txn(&mut pool, TestTransaction::new(1, seq, 1)).unwrap();
     }
-    let get_all = pool.get_batch(100, 100 * 1024, true, HashSet::new());
+    let get_all = pool.get_batch(100, 100 * 1024, true, HashSet::new(), None);
     assert_eq!(get_all.len(), 100);
     let txn_size = get_all[0].raw_txn_bytes_len() as u64;
     let limit = 10;
-    let hit_limit = pool.get_batch(100, txn_size * limit, true, HashSet::new());
+    let hit_limit = pool.get_batch(100, txn_size * limit, true, HashSet::new(), None);
     assert_eq!(hit_limit.len(), limit as usize);
 }
 
@@ -746,7 +2180,7 @@ fn test_sequence_number_behavior_at_capacity() {
     add_txn(&mut pool, TestTransaction::new(2, 0, 1)).unwrap();
     pool.commit_transaction(&TestTransaction::get_address(2), 0);
 
-    let batch = pool.get_batch(10, 10240, true, HashSet::new());
+    let batch = pool.get_batch(10, 10240, true, HashSet::new(), None);
     assert_eq!(batch.len(), 1);
 }
 
@@ -757,12 +2191,12 @@ fn test_not_return_non_full() {
     let mut pool = CoreMempool::new(&config);
     add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
 
-    let batch = pool.get_batch(10, 10240, true, HashSet::new());
+    let batch = pool.get_batch(10, 10240, true, HashSet::new(), None);
     assert_eq!(batch.len(), 1);
 
-    let batch = pool.get_batch(10, 10240, false, HashSet::new());
+    let batch = pool.get_batch(10, 10240, false, HashSet::new(), None);
     assert_eq!(batch.len(), 0);
 
-    let batch = pool.get_batch(1, 10240, false, HashSet::new());
+    let batch = pool.get_batch(1, 10240, false, HashSet::new(), None);
     assert_eq!(batch.len(), 1);
 }
@@ -183,7 +183,7 @@ impl MockSharedMempool {
     pub fn get_txns(&self, size: u64) -> Vec<SignedTransaction> {
         let pool = self.mempool.lock();
         // assume txn size is less than 100kb
-        pool.get_batch(size, size * 102400, true, HashSet::new())
+        pool.get_batch(size, size * 102400, true, HashSet::new(), None)
     }
 
     pub fn remove_txn(&self, txn: &SignedTransaction) {
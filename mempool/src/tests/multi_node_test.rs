@@ -369,6 +369,7 @@ impl TestHarness {
                                 102400,
                                 true,
                                 HashSet::new(),
+                                None,
                             );
                             for txn in transactions.iter() {
                                 assert!(block.contains(txn));
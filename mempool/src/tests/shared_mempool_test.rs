@@ -4,11 +4,13 @@
 
 use crate::{
     mocks::MockSharedMempool,
-    tests::common::{batch_add_signed_txn, TestTransaction},
+    shared_mempool::coordinator::{read_parking_lot, write_parking_lot},
+    tests::common::{add_txns_to_mempool, batch_add_signed_txn, setup_mempool, TestTransaction},
     QuorumStoreRequest,
 };
 use aptos_consensus_types::common::RejectedTransactionSummary;
 use aptos_mempool_notifications::MempoolNotificationSender;
+use aptos_temppath::TempPath;
 use aptos_types::transaction::Transaction;
 use futures::{channel::oneshot, executor::block_on, sink::SinkExt};
 
@@ -95,3 +97,25 @@ fn test_mempool_notify_committed_txns() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline.first().unwrap(), &kept_txn);
 }
+
+#[test]
+fn test_parking_lot_persistence() {
+    let mut pool = setup_mempool().0;
+    add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 2, 1),
+        TestTransaction::new(1, 1, 1),
+    ]);
+    let path = TempPath::new();
+
+    // Nothing persisted yet
+    assert!(read_parking_lot(path.path()).unwrap().is_empty());
+
+    let parked = pool.export_parking_lot();
+    write_parking_lot(path.path(), &parked).unwrap();
+    assert_eq!(read_parking_lot(path.path()).unwrap(), parked);
+
+    let mut restarted_pool = setup_mempool().0;
+    restarted_pool.import_parking_lot(read_parking_lot(path.path()).unwrap());
+    assert_eq!(restarted_pool.get_parking_lot_size(), 2);
+}
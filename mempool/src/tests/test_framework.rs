@@ -172,7 +172,7 @@ impl MempoolNode {
             let block = self
                 .mempool
                 .lock()
-                .get_batch(100, 102400, true, HashSet::new());
+                .get_batch(100, 102400, true, HashSet::new(), None);
 
             if block_contains_all_transactions(&block, txns) {
                 break;
@@ -225,7 +225,7 @@ impl MempoolNode {
         let block = self
             .mempool
             .lock()
-            .get_batch(100, 102400, true, HashSet::new());
+            .get_batch(100, 102400, true, HashSet::new(), None);
         if !condition(&block, txns) {
             let actual: Vec<_> = block
                 .iter()
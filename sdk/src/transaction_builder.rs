@@ -131,6 +131,17 @@ impl TransactionFactory {
         self.transaction_builder(payload)
     }
 
+    /// Same as `payload`, but expiring `jitter_secs` later than the factory's expiration time.
+    pub fn payload_with_expiration_jitter(
+        &self,
+        payload: TransactionPayload,
+        jitter_secs: u64,
+    ) -> TransactionBuilder {
+        let expiration_timestamp_secs = self.expiration_timestamp() + jitter_secs;
+        self.payload(payload)
+            .expiration_timestamp_secs(expiration_timestamp_secs)
+    }
+
     pub fn module(&self, code: Vec<u8>) -> TransactionBuilder {
         self.payload(TransactionPayload::ModuleBundle(ModuleBundle::singleton(
             code,
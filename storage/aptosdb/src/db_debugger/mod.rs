@@ -4,6 +4,7 @@
 pub mod checkpoint;
 mod common;
 pub mod ledger;
+pub mod state_kv;
 pub mod state_tree;
 pub mod truncate;
 
@@ -20,6 +21,9 @@ pub enum Cmd {
     #[clap(subcommand)]
     Ledger(ledger::Cmd),
 
+    #[clap(subcommand)]
+    StateKv(state_kv::Cmd),
+
     Truncate(truncate::Cmd),
 }
 
@@ -29,6 +33,7 @@ impl Cmd {
             Cmd::StateTree(cmd) => cmd.run(),
             Cmd::Checkpoint(cmd) => cmd.run(),
             Cmd::Ledger(cmd) => cmd.run(),
+            Cmd::StateKv(cmd) => cmd.run(),
             Cmd::Truncate(cmd) => cmd.run(),
         }
     }
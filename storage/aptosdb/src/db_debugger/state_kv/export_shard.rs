@@ -0,0 +1,50 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::AptosDB;
+use anyhow::{ensure, Result};
+use aptos_config::config::RocksdbConfigs;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(about = "Export a state kv db shard as a standalone DB, for offline analysis.")]
+pub struct Cmd {
+    #[clap(long, parse(from_os_str))]
+    db_dir: PathBuf,
+
+    #[clap(long)]
+    shard_id: u8,
+
+    #[clap(long, parse(from_os_str))]
+    output_dir: PathBuf,
+
+    #[clap(long)]
+    use_state_kv_db: bool,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        ensure!(!self.output_dir.exists(), "Output dir already exists.");
+
+        let rocksdb_config = RocksdbConfigs {
+            use_state_kv_db: self.use_state_kv_db,
+            ..Default::default()
+        };
+        let (_ledger_db, _state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &self.db_dir,
+            rocksdb_config,
+            /*readonly=*/ true,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )?;
+
+        println!(
+            "Exporting shard {} to: {:?}",
+            self.shard_id, &self.output_dir
+        );
+        state_kv_db.export_shard(self.shard_id, &self.output_dir)?;
+        println!("Done!");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod export_shard;
+
+use anyhow::Result;
+
+#[derive(clap::Subcommand)]
+#[clap(about = "Inspect the state kv db.")]
+pub enum Cmd {
+    ExportShard(export_shard::Cmd),
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        match self {
+            Self::ExportShard(cmd) => cmd.run(),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::schema::*;
+use crate::{schema::*, NUM_STATE_SHARDS};
 use aptos_config::config::RocksdbConfig;
+use aptos_rocksdb_options::set_sharded_memtable_options;
 use aptos_schemadb::{
     BlockBasedOptions, Cache, ColumnFamilyDescriptor, ColumnFamilyName, DBCompressionType, Options,
     SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
@@ -22,6 +23,7 @@ pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
         LEDGER_INFO_CF_NAME,
         STALE_STATE_VALUE_INDEX_CF_NAME,
         STATE_VALUE_CF_NAME,
+        STATE_VALUE_CHANGELOG_CF_NAME,
         TRANSACTION_CF_NAME,
         TRANSACTION_ACCUMULATOR_CF_NAME,
         TRANSACTION_BY_ACCOUNT_CF_NAME,
@@ -49,6 +51,7 @@ pub(super) fn state_kv_db_column_families() -> Vec<ColumnFamilyName> {
         DB_METADATA_CF_NAME,
         STALE_STATE_VALUE_INDEX_CF_NAME,
         STATE_VALUE_CF_NAME,
+        STATE_VALUE_CHANGELOG_CF_NAME,
     ]
 }
 
@@ -62,6 +65,9 @@ where
 {
     let mut table_options = BlockBasedOptions::default();
     table_options.set_cache_index_and_filter_blocks(rocksdb_config.cache_index_and_filter_blocks);
+    table_options.set_cache_index_and_filter_blocks_with_high_priority(
+        rocksdb_config.cache_index_and_filter_blocks_with_high_priority,
+    );
     table_options.set_block_size(rocksdb_config.block_size as usize);
     let cache = Cache::new_lru_cache(rocksdb_config.block_cache_size as usize)
         .expect("Create Rocksdb block cache failed.");
@@ -97,7 +103,29 @@ pub(super) fn gen_state_merkle_cfds(rocksdb_config: &RocksdbConfig) -> Vec<Colum
 
 pub(super) fn gen_state_kv_cfds(rocksdb_config: &RocksdbConfig) -> Vec<ColumnFamilyDescriptor> {
     let cfs = state_kv_db_column_families();
-    gen_cfds(rocksdb_config, cfs, with_state_key_extractor_processor)
+    let num_cfs = cfs.len();
+    gen_cfds(rocksdb_config, cfs, |cf_name, cf_opts| {
+        set_sharded_memtable_options(rocksdb_config, NUM_STATE_SHARDS, num_cfs, cf_opts);
+        if rocksdb_config.bottommost_zstd_compression {
+            cf_opts.set_bottommost_compression_type(DBCompressionType::Zstd);
+        }
+        if let Some(trigger) = rocksdb_config.level0_file_num_compaction_trigger {
+            cf_opts.set_level_zero_file_num_compaction_trigger(trigger);
+        }
+        if let Some(trigger) = rocksdb_config.level0_slowdown_writes_trigger {
+            cf_opts.set_level_zero_slowdown_writes_trigger(trigger);
+        }
+        if let Some(trigger) = rocksdb_config.level0_stop_writes_trigger {
+            cf_opts.set_level_zero_stop_writes_trigger(trigger);
+        }
+        if let Some(size) = rocksdb_config.target_file_size_base {
+            cf_opts.set_target_file_size_base(size);
+        }
+        if let Some(multiplier) = rocksdb_config.target_file_size_multiplier {
+            cf_opts.set_target_file_size_multiplier(multiplier);
+        }
+        with_state_key_extractor_processor(cf_name, cf_opts);
+    })
 }
 
 fn state_key_extractor(state_value_raw_key: &[u8]) -> &[u8] {
@@ -64,7 +64,7 @@ use crate::{
     schema::*,
     stale_node_index::StaleNodeIndexSchema,
     stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
-    state_kv_db::StateKvDb,
+    state_kv_db::{StateKvDb, StateKvStatsDumper},
     state_merkle_db::StateMerkleDb,
     state_store::StateStore,
     transaction_store::TransactionStore,
@@ -284,6 +284,7 @@ pub struct AptosDB {
     transaction_store: Arc<TransactionStore>,
     ledger_pruner: LedgerPrunerManager,
     _rocksdb_property_reporter: RocksdbPropertyReporter,
+    _state_kv_stats_dumper: Option<StateKvStatsDumper>,
     ledger_commit_lock: std::sync::Mutex<()>,
     indexer: Option<Indexer>,
 }
@@ -339,6 +340,7 @@ impl AptosDB {
                 Arc::clone(&ledger_rocksdb),
                 Arc::clone(&state_merkle_db),
             ),
+            _state_kv_stats_dumper: None,
             ledger_commit_lock: std::sync::Mutex::new(()),
             indexer: None,
         }
@@ -358,6 +360,11 @@ impl AptosDB {
             "Do not set prune_window when opening readonly.",
         );
 
+        let index_db_config = rocksdb_configs.index_db_config;
+        let stats_dump_interval = rocksdb_configs
+            .state_kv_db_stats_dump_interval_secs
+            .filter(|_| rocksdb_configs.use_state_kv_db)
+            .map(Duration::from_secs);
         let (ledger_db, state_merkle_db, state_kv_db) = Self::open_dbs(
             db_root_path.as_ref(),
             rocksdb_configs,
@@ -375,8 +382,10 @@ impl AptosDB {
         );
 
         if !readonly && enable_indexer {
-            myself.open_indexer(db_root_path, rocksdb_configs.index_db_config)?;
+            myself.open_indexer(db_root_path, index_db_config)?;
         }
+        myself._state_kv_stats_dumper = stats_dump_interval
+            .map(|interval| myself.state_kv_db.start_periodic_stats_dump(interval));
 
         Ok(myself)
     }
@@ -410,7 +419,7 @@ impl AptosDB {
         let ledger_db = Arc::new(ledger_db);
         let state_kv_db = StateKvDb::new(
             db_root_path.as_ref(),
-            rocksdb_configs,
+            rocksdb_configs.clone(),
             readonly,
             Arc::clone(&ledger_db),
         )?;
@@ -5,8 +5,12 @@ use crate::{
     db_metadata::DbMetadataSchema,
     metrics::PRUNER_LEAST_READABLE_VERSION,
     pruner::{
-        db_pruner::DBPruner, db_sub_pruner::DBSubPruner,
-        state_store::state_value_pruner::StateValuePruner,
+        db_pruner::DBPruner,
+        db_sub_pruner::DBSubPruner,
+        state_store::{
+            state_value_changelog_pruner::StateValueChangelogPruner,
+            state_value_pruner::StateValuePruner,
+        },
     },
     pruner_utils,
     schema::db_metadata::{DbMetadataKey, DbMetadataValue},
@@ -25,6 +29,7 @@ pub(crate) struct StateKvPruner {
     target_version: AtomicVersion,
     min_readable_version: AtomicVersion,
     state_value_pruner: Arc<dyn DBSubPruner + Send + Sync>,
+    state_value_changelog_pruner: Arc<dyn DBSubPruner + Send + Sync>,
 }
 
 impl DBPruner for StateKvPruner {
@@ -97,7 +102,8 @@ impl StateKvPruner {
             state_kv_db: Arc::clone(&state_kv_db),
             target_version: AtomicVersion::new(0),
             min_readable_version: AtomicVersion::new(0),
-            state_value_pruner: Arc::new(StateValuePruner::new(state_kv_db)),
+            state_value_pruner: Arc::new(StateValuePruner::new(Arc::clone(&state_kv_db))),
+            state_value_changelog_pruner: Arc::new(StateValueChangelogPruner::new(state_kv_db)),
         };
         pruner.initialize();
         pruner
@@ -132,6 +138,11 @@ impl StateKvPruner {
 
         self.state_value_pruner
             .prune(db_batch, min_readable_version, current_target_version)?;
+        self.state_value_changelog_pruner.prune(
+            db_batch,
+            min_readable_version,
+            current_target_version,
+        )?;
 
         Ok(current_target_version)
     }
@@ -20,6 +20,7 @@ use aptos_types::transaction::{AtomicVersion, Version};
 use std::sync::{atomic::Ordering, Arc};
 
 pub mod generics;
+pub(crate) mod state_value_changelog_pruner;
 pub(crate) mod state_value_pruner;
 
 #[cfg(test)]
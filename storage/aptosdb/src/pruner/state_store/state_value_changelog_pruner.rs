@@ -0,0 +1,65 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    pruner::db_sub_pruner::DBSubPruner, schema::state_value_changelog::StateValueChangelogSchema,
+    state_kv_db::StateKvDb,
+};
+use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
+use aptos_types::transaction::Version;
+use std::sync::Arc;
+
+/// Prunes the changelog of state values, whose entries are kept in the same shards as the state
+/// values they record.
+pub struct StateValueChangelogPruner {
+    state_kv_db: Arc<StateKvDb>,
+}
+
+impl DBSubPruner for StateValueChangelogPruner {
+    fn prune(
+        &self,
+        db_batch: &mut SchemaBatch,
+        min_readable_version: u64,
+        target_version: u64,
+    ) -> anyhow::Result<()> {
+        // Pruned even if the changelog is disabled, as it may hold entries written before.
+        let metadata_db = self.state_kv_db.metadata_db_handle();
+        for (_, db) in self.state_kv_db.distinct_db_shards() {
+            if Arc::ptr_eq(db, &metadata_db) {
+                Self::delete_range(db, db_batch, min_readable_version, target_version)?;
+            } else {
+                // Written ahead of the progress of the pruner, which is fine as pruning is
+                // idempotent.
+                let shard_batch = SchemaBatch::new();
+                Self::delete_range(db, &shard_batch, min_readable_version, target_version)?;
+                db.write_schemas(shard_batch)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StateValueChangelogPruner {
+    pub(in crate::pruner) fn new(state_kv_db: Arc<StateKvDb>) -> Self {
+        StateValueChangelogPruner { state_kv_db }
+    }
+
+    /// Adds to `batch` the deletion of the changelog entries of `db` in [`begin`, `end`).
+    fn delete_range(
+        db: &DB,
+        batch: &SchemaBatch,
+        begin: Version,
+        end: Version,
+    ) -> anyhow::Result<()> {
+        let mut iter = db.iter::<StateValueChangelogSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for item in iter {
+            let (key, _) = item?;
+            if key.0 >= end {
+                break;
+            }
+            batch.delete::<StateValueChangelogSchema>(&key)?;
+        }
+        Ok(())
+    }
+}
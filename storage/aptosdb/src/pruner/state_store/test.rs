@@ -3,15 +3,19 @@
 
 use crate::{
     new_sharded_schema_batch,
-    pruner::{state_merkle_pruner_worker::StateMerklePrunerWorker, *},
+    pruner::{
+        db_pruner::DBPruner, state_kv_pruner::StateKvPruner,
+        state_merkle_pruner_worker::StateMerklePrunerWorker, *,
+    },
     stale_node_index::StaleNodeIndexSchema,
     stale_state_value_index::StaleStateValueIndexSchema,
+    state_kv_db::{DefaultShardingStrategy, StateKvDb},
     state_merkle_db::StateMerkleDb,
     state_store::StateStore,
     test_helper::{arb_state_kv_sets, update_store},
     AptosDB, PrunerManager, StateKvPrunerManager, StateMerklePrunerManager,
 };
-use aptos_config::config::{LedgerPrunerConfig, StateMerklePrunerConfig};
+use aptos_config::config::{LedgerPrunerConfig, RocksdbConfig, StateMerklePrunerConfig};
 use aptos_crypto::HashValue;
 use aptos_schemadb::{ReadOptions, SchemaBatch};
 use aptos_storage_interface::{jmt_update_refs, jmt_updates, DbReader};
@@ -436,3 +440,41 @@ fn verify_state_value<'a, I: Iterator<Item = (&'a StateKey, &'a (Version, Option
         }
     }
 }
+
+#[test]
+fn test_state_value_changelog_pruner() {
+    let tmp_dir = TempPath::new();
+    let state_kv_db = Arc::new(
+        StateKvDb::open(
+            &tmp_dir,
+            RocksdbConfig::default(),
+            true,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap(),
+    );
+
+    // One update per version, of keys spread over the shards.
+    let value_state_sets: Vec<_> = (0..4u8)
+        .map(|i| HashMap::from([(StateKey::raw(vec![i]), Some(StateValue::from(vec![i])))]))
+        .collect();
+    let batches = new_sharded_schema_batch();
+    state_kv_db
+        .put_changelog(&value_state_sets.iter().collect::<Vec<_>>(), 0, &batches)
+        .unwrap();
+    state_kv_db.commit(3, batches).unwrap();
+
+    let pruner = StateKvPruner::new(Arc::clone(&state_kv_db));
+    pruner.set_target_version(2);
+    assert_eq!(pruner.prune(10).unwrap(), 2);
+
+    let versions: Vec<_> = state_kv_db
+        .read_changelog(0, 100)
+        .unwrap()
+        .into_iter()
+        .map(|(version, _, _)| version)
+        .collect();
+    assert_eq!(versions, vec![2, 3]);
+}
@@ -25,6 +25,7 @@ type ShardId = usize;
 pub(crate) enum DbMetadataValue {
     Version(Version),
     StateSnapshotProgress(StateSnapshotProgress),
+    ShardingStrategy(String),
 }
 
 impl DbMetadataValue {
@@ -55,6 +56,7 @@ pub enum DbMetadataKey {
     StateKvCommitProgress,
     OverallCommitProgress,
     StateKvShardCommitProgress(ShardId),
+    StateKvShardingStrategy,
 }
 
 define_schema!(
@@ -0,0 +1,77 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines the physical storage schema for the changelog of state values, which
+//! records every state update in version order for downstream consumers (e.g. indexers).
+//!
+//! An entry in this data set has 2 pieces of information in the key:
+//!     1. The version at which the state key was updated
+//!     2. The state key
+//! The value associated with the key is the serialized new State Value (`None` for deletion).
+//!
+//! ```text
+//! |<-------- key -------->|<--- value --->|
+//! | version |  state key  |  state value  |
+//! ```
+//!
+//! `version` is serialized in big endian so that records in RocksDB will be in order of its
+//! numeric value.
+
+use crate::schema::{ensure_slice_len_gt, STATE_VALUE_CHANGELOG_CF_NAME};
+use anyhow::Result;
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, SeekKeyCodec, ValueCodec},
+};
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{io::Write, mem::size_of};
+
+type Key = (Version, StateKey);
+
+define_schema!(
+    StateValueChangelogSchema,
+    Key,
+    Option<StateValue>,
+    STATE_VALUE_CHANGELOG_CF_NAME
+);
+
+impl KeyCodec<StateValueChangelogSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = vec![];
+        encoded.write_u64::<BigEndian>(self.0)?;
+        encoded.write_all(&self.1.encode()?)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        const VERSION_SIZE: usize = size_of::<Version>();
+
+        ensure_slice_len_gt(data, VERSION_SIZE)?;
+        let version = (&data[..VERSION_SIZE]).read_u64::<BigEndian>()?;
+        let state_key = StateKey::decode(&data[VERSION_SIZE..])?;
+        Ok((version, state_key))
+    }
+}
+
+impl ValueCodec<StateValueChangelogSchema> for Option<StateValue> {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+impl SeekKeyCodec<StateValueChangelogSchema> for Version {
+    fn encode_seek_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test;
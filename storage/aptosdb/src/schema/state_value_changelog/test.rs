@@ -0,0 +1,19 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        version in any::<Version>(),
+        state_key in any::<StateKey>(),
+        v in any::<Option<StateValue>>(),
+    ) {
+        assert_encode_decode::<StateValueChangelogSchema>(&(version, state_key), &v);
+    }
+}
+
+test_no_panic_decoding!(StateValueChangelogSchema);
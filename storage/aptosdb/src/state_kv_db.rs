@@ -6,27 +6,65 @@
 use crate::{
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     db_options::{gen_state_kv_cfds, state_kv_db_column_families},
+    schema::{
+        stale_state_value_index::StaleStateValueIndexSchema, state_value::StateValueSchema,
+        state_value_changelog::StateValueChangelogSchema, STATE_VALUE_CF_NAME,
+    },
     utils::truncation_helper::{get_state_kv_commit_progress, truncate_state_kv_db_shards},
-    COMMIT_POOL, NUM_STATE_SHARDS,
+    ShardedStateKvSchemaBatch, COMMIT_POOL, NUM_STATE_SHARDS,
 };
-use anyhow::Result;
+use anyhow::{bail, ensure, Result};
 use aptos_config::config::{RocksdbConfig, RocksdbConfigs};
-use aptos_logger::prelude::info;
-use aptos_rocksdb_options::gen_rocksdb_options;
-use aptos_schemadb::{SchemaBatch, DB};
-use aptos_types::transaction::Version;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::{info, warn};
+use aptos_rocksdb_options::gen_sharded_rocksdb_options;
+use aptos_schemadb::{schema::Schema, ReadOptions, SchemaBatch, DB};
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
 use arr_macro::arr;
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    thread::JoinHandle,
+    time::Duration,
 };
 
 pub const STATE_KV_DB_FOLDER_NAME: &str = "state_kv_db";
 pub const STATE_KV_METADATA_DB_NAME: &str = "state_kv_metadata_db";
 
+/// Decides which shard a state key is stored in. The name of the strategy is recorded in the
+/// metadata db when it's created, and the same strategy needs to be used on later opens.
+pub(crate) trait ShardingStrategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn shard_id(&self, state_key: &StateKey) -> u8;
+}
+
+/// Routes keys by the first nibble of their hash.
+pub(crate) struct DefaultShardingStrategy;
+
+impl ShardingStrategy for DefaultShardingStrategy {
+    fn name(&self) -> &str {
+        "default"
+    }
+
+    fn shard_id(&self, state_key: &StateKey) -> u8 {
+        state_key.get_shard_id()
+    }
+}
+
 pub struct StateKvDb {
     state_kv_metadata_db: Arc<DB>,
     state_kv_db_shards: [Arc<DB>; NUM_STATE_SHARDS],
+    enable_changelog: bool,
+    sharding_strategy: Arc<dyn ShardingStrategy>,
 }
 
 impl StateKvDb {
@@ -43,21 +81,139 @@ impl StateKvDb {
             return Ok(Self {
                 state_kv_metadata_db: Arc::clone(&ledger_db),
                 state_kv_db_shards: arr![Arc::clone(&ledger_db); 16],
+                enable_changelog: rocksdb_configs.enable_state_kv_changelog,
+                sharding_strategy: Arc::new(DefaultShardingStrategy),
             });
         }
 
-        Self::open(db_root_path, rocksdb_configs.state_kv_db_config, readonly)
+        Self::validate_open_config(db_root_path.as_ref(), &rocksdb_configs)?;
+        Self::retry_on_lock_error(
+            rocksdb_configs.state_kv_db_open_retries,
+            Duration::from_millis(rocksdb_configs.state_kv_db_open_retry_delay_ms),
+            || {
+                Self::open(
+                    db_root_path.as_ref(),
+                    rocksdb_configs.state_kv_db_config,
+                    rocksdb_configs.enable_state_kv_changelog,
+                    rocksdb_configs.wal_dir.as_deref(),
+                    Arc::new(DefaultShardingStrategy),
+                    readonly,
+                )
+            },
+        )
+    }
+
+    /// Calls `open`, retrying up to `retries` times while it fails to lock the db. The delay
+    /// before the first retry is `delay`, and doubles with every retry.
+    fn retry_on_lock_error<T>(
+        retries: usize,
+        mut delay: Duration,
+        mut open: impl FnMut() -> Result<T>,
+    ) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            match open() {
+                Err(err) if attempt < retries && Self::is_lock_error(&err) => {
+                    attempt += 1;
+                    warn!(
+                        attempt = attempt,
+                        retries = retries,
+                        delay_ms = delay.as_millis() as u64,
+                        error = ?err,
+                        "Failed to lock state kv db, retrying."
+                    );
+                    thread::sleep(delay);
+                    delay *= 2;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Whether `err` is RocksDB failing to lock the db because another process, or another
+    /// instance in this process, has it open.
+    fn is_lock_error(err: &anyhow::Error) -> bool {
+        let message = err.to_string();
+        message.contains("While lock file") || message.contains("lock hold by current process")
+    }
+
+    /// Checks that the state kv db can be opened under `db_root_path` with `rocksdb_configs`,
+    /// without opening or creating any db (or any file).
+    pub(crate) fn validate_open_config<P: AsRef<Path>>(
+        db_root_path: P,
+        rocksdb_configs: &RocksdbConfigs,
+    ) -> Result<()> {
+        let state_kv_db_path = db_root_path.as_ref().join(STATE_KV_DB_FOLDER_NAME);
+        for path in [db_root_path.as_ref(), state_kv_db_path.as_path()]
+            .into_iter()
+            .chain(rocksdb_configs.wal_dir.as_deref())
+        {
+            ensure!(
+                !path.exists() || path.is_dir(),
+                "{:?} exists and is not a directory.",
+                path
+            );
+        }
+
+        Self::validate_num_shards(NUM_STATE_SHARDS)?;
+
+        let config = &rocksdb_configs.state_kv_db_config;
+        ensure!(
+            config.max_open_files == -1 || config.max_open_files > 0,
+            "max_open_files must be positive or -1 (unlimited), got {}.",
+            config.max_open_files
+        );
+        ensure!(
+            config.max_background_jobs > 0,
+            "max_background_jobs must be positive, got {}.",
+            config.max_background_jobs
+        );
+        ensure!(
+            config.max_write_buffer_number > 0,
+            "max_write_buffer_number must be positive, got {}.",
+            config.max_write_buffer_number
+        );
+        ensure!(config.block_size > 0, "block_size must be positive.");
+        if let Some(budget) = config.memtable_memory_budget {
+            let num_memtables = NUM_STATE_SHARDS as u64
+                * state_kv_db_column_families().len() as u64
+                * config.max_write_buffer_number as u64;
+            ensure!(
+                budget >= num_memtables,
+                "memtable_memory_budget {} is too small for {} memtables.",
+                budget,
+                num_memtables
+            );
+        }
+
+        Ok(())
     }
 
+    /// Shard ids are `u8`s, and shards are picked from the bits of the key hashes.
+    fn validate_num_shards(num_shards: usize) -> Result<()> {
+        ensure!(
+            num_shards.is_power_of_two() && num_shards <= u8::MAX as usize + 1,
+            "Number of state kv db shards must be a power of two of at most 256, got {}.",
+            num_shards
+        );
+        Ok(())
+    }
+
+    /// Opens the state kv db under `db_root_path`. If `wal_dir` is set, the WAL of the dbs is
+    /// kept under it instead, with the same layout as the data.
     pub(crate) fn open<P: AsRef<Path>>(
         db_root_path: P,
         state_kv_db_config: RocksdbConfig,
+        enable_changelog: bool,
+        wal_dir: Option<&Path>,
+        sharding_strategy: Arc<dyn ShardingStrategy>,
         readonly: bool,
     ) -> Result<Self> {
         let state_kv_metadata_db_path = Self::metadata_db_path(db_root_path.as_ref());
 
         let state_kv_metadata_db = Arc::new(Self::open_db(
             state_kv_metadata_db_path.clone(),
+            wal_dir.map(Self::metadata_db_path),
             STATE_KV_METADATA_DB_NAME,
             &state_kv_db_config,
             readonly,
@@ -68,13 +224,15 @@ impl StateKvDb {
             "Opened state kv metadata db!"
         );
 
+        Self::check_sharding_strategy(&state_kv_metadata_db, sharding_strategy.as_ref(), readonly)?;
+
         // TODO(grao): Support sharding here.
         let sharding = false;
         let state_kv_db_shards = {
             if sharding {
                 let mut shard_id: usize = 0;
                 arr![{
-                    let db = Self::open_shard(db_root_path.as_ref(), shard_id as u8, &state_kv_db_config, readonly)?;
+                    let db = Self::open_shard(db_root_path.as_ref(), wal_dir, shard_id as u8, &state_kv_db_config, readonly)?;
                     shard_id += 1;
                     Arc::new(db)
                 }; 16]
@@ -86,6 +244,8 @@ impl StateKvDb {
         let state_kv_db = Self {
             state_kv_metadata_db,
             state_kv_db_shards,
+            enable_changelog,
+            sharding_strategy,
         };
 
         if let Some(overall_kv_commit_progress) = get_state_kv_commit_progress(&state_kv_db)? {
@@ -145,7 +305,14 @@ impl StateKvDb {
         db_root_path: impl AsRef<Path>,
         cp_root_path: impl AsRef<Path>,
     ) -> Result<()> {
-        let state_kv_db = Self::open(db_root_path, RocksdbConfig::default(), false)?;
+        let state_kv_db = Self::open(
+            db_root_path,
+            RocksdbConfig::default(),
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )?;
         let cp_state_kv_db_path = cp_root_path.as_ref().join(STATE_KV_DB_FOLDER_NAME);
 
         info!("Creating state_kv_db checkpoint at: {cp_state_kv_db_path:?}");
@@ -172,6 +339,193 @@ impl StateKvDb {
         Ok(())
     }
 
+    /// Exports shard `shard_id` to `output_path` as a standalone RocksDB, which can be opened on
+    /// its own with the state kv column families. Only the rows of the keys routed to the shard
+    /// are copied, so the export is the same whether or not sharding is enabled, and works on a
+    /// readonly db.
+    pub(crate) fn export_shard(&self, shard_id: u8, output_path: impl AsRef<Path>) -> Result<()> {
+        ensure!(
+            (shard_id as usize) < NUM_STATE_SHARDS,
+            "Invalid shard id: {}",
+            shard_id
+        );
+        ensure!(
+            !output_path.as_ref().exists(),
+            "{:?} already exists.",
+            output_path.as_ref()
+        );
+        info!(
+            "Exporting state kv db shard {shard_id} to: {:?}",
+            output_path.as_ref()
+        );
+
+        let output_db = Self::open_db(
+            output_path.as_ref().to_path_buf(),
+            None,
+            "exported_state_kv_db_shard",
+            &RocksdbConfig::default(),
+            /*readonly=*/ false,
+        )?;
+        let db = self.db_shard(shard_id);
+        let in_shard = |state_key: &StateKey| self.shard_id(state_key) == shard_id;
+        Self::copy_rows::<StateValueSchema>(db, &output_db, |(key, _)| in_shard(key))?;
+        Self::copy_rows::<StaleStateValueIndexSchema>(db, &output_db, |index| {
+            in_shard(&index.state_key)
+        })?;
+        Self::copy_rows::<StateValueChangelogSchema>(db, &output_db, |(_, key)| in_shard(key))?;
+        Self::copy_rows::<DbMetadataSchema>(db, &output_db, |_| true)
+    }
+
+    /// Copies the rows of schema `S` in `from` that pass `filter` to `to`.
+    fn copy_rows<S: Schema>(from: &DB, to: &DB, filter: impl Fn(&S::Key) -> bool) -> Result<()> {
+        const BATCH_SIZE: usize = 10_000;
+
+        let mut iter = from.iter::<S>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut batch = SchemaBatch::new();
+        let mut batch_len = 0;
+        for item in iter {
+            let (key, value) = item?;
+            if !filter(&key) {
+                continue;
+            }
+            batch.put::<S>(&key, &value)?;
+            batch_len += 1;
+            if batch_len == BATCH_SIZE {
+                to.write_schemas(std::mem::replace(&mut batch, SchemaBatch::new()))?;
+                batch_len = 0;
+            }
+        }
+        to.write_schemas(batch)
+    }
+
+    /// Starts a thread logging the estimated number of keys and size of every shard every
+    /// `interval`, until the returned dumper is dropped.
+    pub(crate) fn start_periodic_stats_dump(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> StateKvStatsDumper {
+        let state_kv_db = Arc::clone(self);
+        let num_dumps = Arc::new(AtomicUsize::new(0));
+        let num_dumps_clone = Arc::clone(&num_dumps);
+        let (send, recv) = mpsc::channel();
+        let join_handle = Some(thread::spawn(move || {
+            let mut ack = None;
+            loop {
+                match state_kv_db.dump_stats() {
+                    Ok(_) => {
+                        num_dumps_clone.fetch_add(1, Ordering::Relaxed);
+                    },
+                    Err(e) => warn!(
+                        error = ?e,
+                        "Dumping state kv db stats failed."
+                    ),
+                }
+                if let Some(ack) = ack.take() {
+                    // The requester may have given up waiting.
+                    let _ = ack.send(());
+                }
+                match recv.recv_timeout(interval) {
+                    Ok(StatsDumperCommand::Dump(sender)) => ack = Some(sender),
+                    Ok(StatsDumperCommand::Stop) => break,
+                    Err(mpsc::RecvTimeoutError::Timeout) => (),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        }));
+        StateKvStatsDumper {
+            sender: Mutex::new(send),
+            join_handle,
+            num_dumps,
+        }
+    }
+
+    /// Logs the stats of every shard, and returns them. Shards sharing the db of a previous one
+    /// are skipped, as their stats would be the same.
+    fn dump_stats(&self) -> Result<Vec<StateKvShardStats>> {
+        self.distinct_db_shards()
+            .map(|(shard_id, db)| {
+                let stats = StateKvShardStats {
+                    shard_id,
+                    num_keys: db.get_property(STATE_VALUE_CF_NAME, "rocksdb.estimate-num-keys")?,
+                    sst_bytes: db
+                        .get_property(STATE_VALUE_CF_NAME, "rocksdb.total-sst-files-size")?,
+                    mem_table_bytes: db
+                        .get_property(STATE_VALUE_CF_NAME, "rocksdb.cur-size-all-mem-tables")?,
+                };
+                info!(
+                    shard_id = stats.shard_id,
+                    num_keys = stats.num_keys,
+                    sst_bytes = stats.sst_bytes,
+                    mem_table_bytes = stats.mem_table_bytes,
+                    "State kv db shard stats."
+                );
+                Ok(stats)
+            })
+            .collect()
+    }
+
+    pub(crate) fn changelog_enabled(&self) -> bool {
+        self.enable_changelog
+    }
+
+    /// Records the updates in `value_state_sets` (the first one being at `first_version`) into
+    /// the changelog, in the same shard as the corresponding state values. No-op if the changelog
+    /// is not enabled.
+    pub(crate) fn put_changelog(
+        &self,
+        value_state_sets: &[&HashMap<StateKey, Option<StateValue>>],
+        first_version: Version,
+        sharded_state_kv_batches: &ShardedStateKvSchemaBatch,
+    ) -> Result<()> {
+        if !self.enable_changelog {
+            return Ok(());
+        }
+
+        value_state_sets
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, kvs)| {
+                let version = first_version + i as Version;
+                kvs.iter().try_for_each(|(k, v)| {
+                    sharded_state_kv_batches[self.shard_id(k) as usize]
+                        .put::<StateValueChangelogSchema>(&(version, k.clone()), v)
+                })
+            })
+    }
+
+    /// Returns the state value changes in [`from_version`, `to_version`), ordered by version.
+    #[cfg(test)]
+    pub(crate) fn read_changelog(
+        &self,
+        from_version: Version,
+        to_version: Version,
+    ) -> Result<Vec<(Version, StateKey, Option<StateValue>)>> {
+        ensure!(self.enable_changelog, "State kv changelog is not enabled.");
+
+        let mut changes = Vec::new();
+        for (_, db) in self.distinct_db_shards() {
+            let mut iter = db.iter::<StateValueChangelogSchema>(ReadOptions::default())?;
+            iter.seek(&from_version)?;
+            for item in iter {
+                let ((version, state_key), value) = item?;
+                if version >= to_version {
+                    break;
+                }
+                changes.push((version, state_key, value));
+            }
+        }
+        // Stable sort, changes within the same version stay in the order of the shards.
+        changes.sort_by_key(|(version, _, _)| *version);
+
+        Ok(changes)
+    }
+
+    /// Returns the id of the shard `state_key` is stored in.
+    pub(crate) fn shard_id(&self, state_key: &StateKey) -> u8 {
+        self.sharding_strategy.shard_id(state_key)
+    }
+
     pub(crate) fn metadata_db(&self) -> &DB {
         &self.state_kv_metadata_db
     }
@@ -180,6 +534,32 @@ impl StateKvDb {
         &self.state_kv_db_shards[shard_id as usize]
     }
 
+    /// Returns the dbs of the shards with the id of the first shard of each, skipping the shards
+    /// that share the db of a previous one (e.g. when sharding is disabled).
+    pub(crate) fn distinct_db_shards(&self) -> impl Iterator<Item = (usize, &Arc<DB>)> {
+        self.state_kv_db_shards
+            .iter()
+            .enumerate()
+            .filter(|(shard_id, db)| {
+                !self.state_kv_db_shards[..*shard_id]
+                    .iter()
+                    .any(|prev| Arc::ptr_eq(prev, db))
+            })
+    }
+
+    /// Advanced: returns a handle to the db of shard `shard_id`, or None if there is no such
+    /// shard, for tooling that needs operations not wrapped by `StateKvDb`. Writes through it
+    /// bypass the commit progress bookkeeping and can leave the db inconsistent, so it should be
+    /// used for reads only.
+    pub fn shard_db(&self, shard_id: usize) -> Option<Arc<DB>> {
+        self.state_kv_db_shards.get(shard_id).cloned()
+    }
+
+    /// Advanced: returns a handle to the metadata db, with the same caveats as `shard_db`.
+    pub fn metadata_db_handle(&self) -> Arc<DB> {
+        Arc::clone(&self.state_kv_metadata_db)
+    }
+
     pub(crate) fn commit_single_shard(
         &self,
         version: Version,
@@ -193,8 +573,47 @@ impl StateKvDb {
         self.state_kv_db_shards[shard_id as usize].write_schemas(batch)
     }
 
+    /// Makes sure `sharding_strategy` is the one the db was created with, recording it if the db
+    /// doesn't have one yet.
+    fn check_sharding_strategy(
+        state_kv_metadata_db: &DB,
+        sharding_strategy: &dyn ShardingStrategy,
+        readonly: bool,
+    ) -> Result<()> {
+        let recorded = state_kv_metadata_db
+            .get::<DbMetadataSchema>(&DbMetadataKey::StateKvShardingStrategy)?;
+        match recorded {
+            Some(DbMetadataValue::ShardingStrategy(name)) => ensure!(
+                name == sharding_strategy.name(),
+                "State kv db was created with sharding strategy {}, but opened with {}.",
+                name,
+                sharding_strategy.name()
+            ),
+            Some(value) => bail!("Unexpected sharding strategy metadata: {:?}", value),
+            None => {
+                // Dbs created before the strategy was recorded use the default one.
+                let has_data = state_kv_metadata_db
+                    .get::<DbMetadataSchema>(&DbMetadataKey::StateKvCommitProgress)?
+                    .is_some();
+                ensure!(
+                    !has_data || sharding_strategy.name() == DefaultShardingStrategy.name(),
+                    "Existing state kv db uses the default sharding strategy, opened with {}.",
+                    sharding_strategy.name()
+                );
+                if !readonly {
+                    state_kv_metadata_db.put::<DbMetadataSchema>(
+                        &DbMetadataKey::StateKvShardingStrategy,
+                        &DbMetadataValue::ShardingStrategy(sharding_strategy.name().to_string()),
+                    )?;
+                }
+            },
+        }
+        Ok(())
+    }
+
     fn open_shard<P: AsRef<Path>>(
         db_root_path: P,
+        wal_dir: Option<&Path>,
         shard_id: u8,
         state_kv_db_config: &RocksdbConfig,
         readonly: bool,
@@ -202,6 +621,7 @@ impl StateKvDb {
         let db_name = format!("state_kv_db_shard_{}", shard_id);
         Self::open_db(
             Self::db_shard_path(db_root_path, shard_id),
+            wal_dir.map(|wal_dir| Self::db_shard_path(wal_dir, shard_id)),
             &db_name,
             state_kv_db_config,
             readonly,
@@ -210,24 +630,20 @@ impl StateKvDb {
 
     fn open_db(
         path: PathBuf,
+        wal_path: Option<PathBuf>,
         name: &str,
         state_kv_db_config: &RocksdbConfig,
         readonly: bool,
     ) -> Result<DB> {
+        let mut db_opts =
+            gen_sharded_rocksdb_options(state_kv_db_config, NUM_STATE_SHARDS, readonly);
+        if let Some(wal_path) = wal_path {
+            db_opts.set_wal_dir(wal_path);
+        }
         Ok(if readonly {
-            DB::open_cf_readonly(
-                &gen_rocksdb_options(state_kv_db_config, true),
-                path,
-                name,
-                state_kv_db_column_families(),
-            )?
+            DB::open_cf_readonly(&db_opts, path, name, state_kv_db_column_families())?
         } else {
-            DB::open_cf(
-                &gen_rocksdb_options(state_kv_db_config, false),
-                path,
-                name,
-                gen_state_kv_cfds(state_kv_db_config),
-            )?
+            DB::open_cf(&db_opts, path, name, gen_state_kv_cfds(state_kv_db_config))?
         })
     }
 
@@ -246,3 +662,584 @@ impl StateKvDb {
             .join("metadata")
     }
 }
+
+/// Stats of a shard, as dumped by `StateKvDb::start_periodic_stats_dump`.
+#[derive(Debug)]
+struct StateKvShardStats {
+    shard_id: usize,
+    num_keys: u64,
+    sst_bytes: u64,
+    mem_table_bytes: u64,
+}
+
+/// Commands sent to the thread started by `StateKvDb::start_periodic_stats_dump`.
+#[derive(Debug)]
+enum StatsDumperCommand {
+    /// Dumps the stats without waiting for the interval, and acks on the sender once done.
+    Dump(mpsc::Sender<()>),
+    Stop,
+}
+
+/// Handle of the thread started by `StateKvDb::start_periodic_stats_dump`, which stops it on drop.
+#[derive(Debug)]
+pub(crate) struct StateKvStatsDumper {
+    sender: Mutex<mpsc::Sender<StatsDumperCommand>>,
+    join_handle: Option<JoinHandle<()>>,
+    /// Number of times the stats were dumped successfully.
+    num_dumps: Arc<AtomicUsize>,
+}
+
+impl StateKvStatsDumper {
+    /// Dumps the stats right away, returning once they are dumped, e.g. to check the dumps in
+    /// tests without waiting for the interval.
+    #[cfg(test)]
+    fn dump_now(&self) {
+        let (ack_sender, ack_receiver) = mpsc::channel();
+        self.sender
+            .lock()
+            .send(StatsDumperCommand::Dump(ack_sender))
+            .unwrap();
+        ack_receiver.recv().unwrap();
+    }
+}
+
+impl Drop for StateKvStatsDumper {
+    fn drop(&mut self) {
+        // Notify the stats dumping thread to exit
+        self.sender.lock().send(StatsDumperCommand::Stop).unwrap();
+        self.join_handle
+            .take()
+            .expect("State kv db stats dumping thread must exist.")
+            .join()
+            .expect("State kv db stats dumping thread should join peacefully.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DefaultShardingStrategy, ShardingStrategy, StateKvDb};
+    use crate::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        db_options::state_kv_db_column_families,
+        new_sharded_schema_batch,
+        schema::{state_value::StateValueSchema, DB_METADATA_CF_NAME},
+        NUM_STATE_SHARDS,
+    };
+    use aptos_config::config::{RocksdbConfig, RocksdbConfigs, RocksdbStatisticsLevel};
+    use aptos_rocksdb_options::gen_sharded_rocksdb_options;
+    use aptos_schemadb::{Options, ReadOptions, DB};
+    use aptos_temppath::TempPath;
+    use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
+    use std::{
+        collections::HashMap,
+        path::Path,
+        sync::{atomic::Ordering, Arc},
+        time::Duration,
+    };
+
+    /// Returns the content of the latest OPTIONS file RocksDB persisted under `db_path`.
+    fn read_latest_options_file(db_path: &Path) -> String {
+        let mut options_files: Vec<_> = std::fs::read_dir(db_path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| name.starts_with("OPTIONS-"))
+            })
+            .collect();
+        options_files.sort();
+        std::fs::read_to_string(options_files.last().expect("No OPTIONS file found.")).unwrap()
+    }
+
+    fn num_files_with_extension(dir: &Path, extension: &str) -> usize {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(extension))
+            .count()
+    }
+
+    #[test]
+    fn test_cache_index_and_filter_blocks_with_high_priority() {
+        let tmp_dir = TempPath::new();
+        let config = RocksdbConfig {
+            cache_index_and_filter_blocks: true,
+            cache_index_and_filter_blocks_with_high_priority: true,
+            ..Default::default()
+        };
+        let _state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            config,
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        let options = read_latest_options_file(&StateKvDb::metadata_db_path(&tmp_dir));
+        assert!(options.contains("cache_index_and_filter_blocks=true"));
+        assert!(options.contains("cache_index_and_filter_blocks_with_high_priority=true"));
+    }
+
+    #[test]
+    fn test_memtable_memory_budget() {
+        let tmp_dir = TempPath::new();
+        let num_cfs = state_kv_db_column_families().len() as u64;
+        let config = RocksdbConfig {
+            max_write_buffer_number: 4,
+            memtable_memory_budget: Some(NUM_STATE_SHARDS as u64 * num_cfs * 4 * (8 << 20)),
+            ..Default::default()
+        };
+        let _state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            config,
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        // Each shard gets its even part of the budget, split across the memtables of all its
+        // column families.
+        let options = read_latest_options_file(&StateKvDb::metadata_db_path(&tmp_dir));
+        assert!(options.contains(&format!("db_write_buffer_size={}", num_cfs * 4 * (8 << 20))));
+        assert!(options.contains(&format!("write_buffer_size={}", 8 << 20)));
+        assert!(options.contains("max_write_buffer_number=4"));
+    }
+
+    #[test]
+    fn test_bottommost_zstd_compression() {
+        let tmp_dir = TempPath::new();
+        let config = RocksdbConfig {
+            bottommost_zstd_compression: true,
+            ..Default::default()
+        };
+        let _state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            config,
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        // Upper levels keep the fast compression.
+        let options = read_latest_options_file(&StateKvDb::metadata_db_path(&tmp_dir));
+        assert!(options.contains("bottommost_compression=kZSTD"));
+        assert!(options.contains("compression=kLZ4Compression"));
+    }
+
+    #[test]
+    fn test_level0_triggers() {
+        let tmp_dir = TempPath::new();
+        let config = RocksdbConfig {
+            level0_file_num_compaction_trigger: Some(8),
+            level0_slowdown_writes_trigger: Some(24),
+            level0_stop_writes_trigger: Some(40),
+            ..Default::default()
+        };
+        let _state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            config,
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        let options = read_latest_options_file(&StateKvDb::metadata_db_path(&tmp_dir));
+        assert!(options.contains("level0_file_num_compaction_trigger=8"));
+        assert!(options.contains("level0_slowdown_writes_trigger=24"));
+        assert!(options.contains("level0_stop_writes_trigger=40"));
+    }
+
+    #[test]
+    fn test_target_file_size() {
+        let tmp_dir = TempPath::new();
+        let config = RocksdbConfig {
+            target_file_size_base: Some(32 << 20),
+            target_file_size_multiplier: Some(2),
+            ..Default::default()
+        };
+        let _state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            config,
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        let options = read_latest_options_file(&StateKvDb::metadata_db_path(&tmp_dir));
+        assert!(options.contains(&format!("target_file_size_base={}", 32 << 20)));
+        assert!(options.contains("target_file_size_multiplier=2"));
+    }
+
+    #[test]
+    fn test_statistics_level() {
+        for (statistics_level, statistics, report_bg_io_stats) in [
+            (RocksdbStatisticsLevel::Disabled, false, false),
+            (RocksdbStatisticsLevel::Minimal, true, false),
+            (RocksdbStatisticsLevel::Full, true, true),
+        ] {
+            let tmp_dir = TempPath::new();
+            let config = RocksdbConfig {
+                statistics_level,
+                ..Default::default()
+            };
+            let db_opts = gen_sharded_rocksdb_options(&config, NUM_STATE_SHARDS, false);
+            assert_eq!(db_opts.get_statistics().is_some(), statistics);
+
+            let _state_kv_db = StateKvDb::open(
+                &tmp_dir,
+                config,
+                false,
+                None,
+                Arc::new(DefaultShardingStrategy),
+                false,
+            )
+            .unwrap();
+            let options = read_latest_options_file(&StateKvDb::metadata_db_path(&tmp_dir));
+            assert!(options.contains(&format!("report_bg_io_stats={}", report_bg_io_stats)));
+        }
+    }
+
+    #[test]
+    fn test_wal_dir() {
+        let tmp_dir = TempPath::new();
+        let wal_dir = TempPath::new();
+        let state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            RocksdbConfig::default(),
+            false,
+            Some(wal_dir.path()),
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+        state_kv_db.write_progress(1).unwrap();
+        state_kv_db
+            .state_kv_metadata_db
+            .flush_cf(DB_METADATA_CF_NAME)
+            .unwrap();
+
+        let db_path = StateKvDb::metadata_db_path(&tmp_dir);
+        let wal_path = StateKvDb::metadata_db_path(&wal_dir);
+        assert!(num_files_with_extension(&wal_path, "log") > 0);
+        assert_eq!(num_files_with_extension(&db_path, "log"), 0);
+        assert!(num_files_with_extension(&db_path, "sst") > 0);
+        assert_eq!(num_files_with_extension(&wal_path, "sst"), 0);
+    }
+
+    #[test]
+    fn test_changelog() {
+        let tmp_dir = TempPath::new();
+        let state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            RocksdbConfig::default(),
+            true,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        let key = |k: &[u8]| StateKey::raw(k.to_vec());
+        let value = |v: &[u8]| Some(StateValue::from(v.to_vec()));
+        let value_state_sets = vec![
+            HashMap::from([(key(b"a"), value(b"a0")), (key(b"b"), value(b"b0"))]),
+            HashMap::from([(key(b"a"), value(b"a1"))]),
+            HashMap::from([(key(b"b"), None), (key(b"c"), value(b"c2"))]),
+            HashMap::from([(key(b"c"), value(b"c3"))]),
+        ];
+
+        // Commit the first 2 versions, then the rest.
+        for (first_version, sets) in [(0, &value_state_sets[..2]), (2, &value_state_sets[2..])] {
+            let batches = new_sharded_schema_batch();
+            state_kv_db
+                .put_changelog(&sets.iter().collect::<Vec<_>>(), first_version, &batches)
+                .unwrap();
+            state_kv_db
+                .commit(first_version + sets.len() as u64 - 1, batches)
+                .unwrap();
+        }
+
+        let changes = state_kv_db.read_changelog(1, 3).unwrap();
+        let mut expected = vec![
+            (1, key(b"a"), value(b"a1")),
+            (2, key(b"b"), None),
+            (2, key(b"c"), value(b"c2")),
+        ];
+        let mut sorted_changes = changes.clone();
+        sorted_changes.sort_by(|x, y| (x.0, &x.1).cmp(&(y.0, &y.1)));
+        expected.sort_by(|x, y| (x.0, &x.1).cmp(&(y.0, &y.1)));
+        assert_eq!(sorted_changes, expected);
+        assert!(changes.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        assert_eq!(state_kv_db.read_changelog(0, 100).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_changelog_disabled() {
+        let tmp_dir = TempPath::new();
+        let state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            RocksdbConfig::default(),
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        let batches = new_sharded_schema_batch();
+        let sets = [HashMap::from([(
+            StateKey::raw(b"a".to_vec()),
+            Some(StateValue::from(b"a0".to_vec())),
+        )])];
+        state_kv_db
+            .put_changelog(&sets.iter().collect::<Vec<_>>(), 0, &batches)
+            .unwrap();
+        state_kv_db.commit(0, batches).unwrap();
+
+        assert!(state_kv_db.read_changelog(0, 1).is_err());
+    }
+
+    /// Routes keys by their last byte.
+    struct LastByteShardingStrategy;
+
+    impl ShardingStrategy for LastByteShardingStrategy {
+        fn name(&self) -> &str {
+            "last_byte"
+        }
+
+        fn shard_id(&self, state_key: &StateKey) -> u8 {
+            let bytes = state_key.encode().unwrap();
+            bytes.last().map_or(0, |b| b % NUM_STATE_SHARDS as u8)
+        }
+    }
+
+    #[test]
+    fn test_sharding_strategy_mismatch() {
+        let tmp_dir = TempPath::new();
+        let open = |sharding_strategy: Arc<dyn ShardingStrategy>| {
+            StateKvDb::open(
+                &tmp_dir,
+                RocksdbConfig::default(),
+                false,
+                None,
+                sharding_strategy,
+                false,
+            )
+        };
+
+        let state_kv_db = open(Arc::new(LastByteShardingStrategy)).unwrap();
+        state_kv_db.commit(0, new_sharded_schema_batch()).unwrap();
+        drop(state_kv_db);
+
+        // Reopening with a different strategy is rejected, with the same one accepted.
+        assert!(open(Arc::new(DefaultShardingStrategy)).is_err());
+        open(Arc::new(LastByteShardingStrategy)).unwrap();
+    }
+
+    #[test]
+    fn test_export_shard() {
+        let tmp_dir = TempPath::new();
+        let state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            RocksdbConfig::default(),
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        let batches = new_sharded_schema_batch();
+        for i in 0..32u8 {
+            let key = StateKey::raw(vec![i]);
+            batches[state_kv_db.shard_id(&key) as usize]
+                .put::<StateValueSchema>(&(key, 0), &Some(StateValue::from(vec![i])))
+                .unwrap();
+        }
+        state_kv_db.commit(0, batches).unwrap();
+
+        let shard_id = state_kv_db.shard_id(&StateKey::raw(vec![0]));
+        let export_dir = TempPath::new();
+        state_kv_db.export_shard(shard_id, &export_dir).unwrap();
+        assert!(state_kv_db.export_shard(shard_id, &export_dir).is_err());
+        assert!(state_kv_db
+            .export_shard(NUM_STATE_SHARDS as u8, TempPath::new())
+            .is_err());
+
+        let read_all = |db: &DB| {
+            let mut iter = db.iter::<StateValueSchema>(ReadOptions::default()).unwrap();
+            iter.seek_to_first();
+            iter.map(|item| item.unwrap()).collect::<Vec<_>>()
+        };
+        let exported_db = DB::open_cf_readonly(
+            &Options::default(),
+            &export_dir,
+            "exported_shard",
+            state_kv_db_column_families(),
+        )
+        .unwrap();
+        let exported = read_all(&exported_db);
+        assert!(!exported.is_empty());
+        let expected: Vec<_> = read_all(state_kv_db.db_shard(shard_id))
+            .into_iter()
+            .filter(|((key, _), _)| state_kv_db.shard_id(key) == shard_id)
+            .collect();
+        assert_eq!(exported, expected);
+        assert_eq!(
+            exported_db
+                .get::<DbMetadataSchema>(&DbMetadataKey::StateKvCommitProgress)
+                .unwrap(),
+            Some(DbMetadataValue::Version(0))
+        );
+    }
+
+    #[test]
+    fn test_shard_db_handle() {
+        let tmp_dir = TempPath::new();
+        let state_kv_db = StateKvDb::open(
+            &tmp_dir,
+            RocksdbConfig::default(),
+            false,
+            None,
+            Arc::new(DefaultShardingStrategy),
+            false,
+        )
+        .unwrap();
+
+        let key = StateKey::raw(b"key".to_vec());
+        let value = Some(StateValue::from(b"value".to_vec()));
+        let shard_id = state_kv_db.shard_id(&key) as usize;
+        let batches = new_sharded_schema_batch();
+        batches[shard_id]
+            .put::<StateValueSchema>(&(key.clone(), 0), &value)
+            .unwrap();
+        state_kv_db.commit(0, batches).unwrap();
+
+        let shard_db = state_kv_db.shard_db(shard_id).unwrap();
+        assert_eq!(
+            shard_db.get::<StateValueSchema>(&(key.clone(), 0)).unwrap(),
+            Some(value)
+        );
+        // Sharding is not enabled yet, so every shard handle is the metadata db.
+        assert!(Arc::ptr_eq(&shard_db, &state_kv_db.metadata_db_handle()));
+        assert!(state_kv_db.shard_db(NUM_STATE_SHARDS).is_none());
+
+        assert_eq!(
+            state_kv_db
+                .metadata_db_handle()
+                .get::<DbMetadataSchema>(&DbMetadataKey::StateKvCommitProgress)
+                .unwrap(),
+            Some(DbMetadataValue::Version(0))
+        );
+    }
+
+    #[test]
+    fn test_validate_open_config() {
+        let tmp_dir = TempPath::new();
+        tmp_dir.create_as_dir().unwrap();
+        let configs = RocksdbConfigs {
+            use_state_kv_db: true,
+            ..Default::default()
+        };
+        StateKvDb::validate_open_config(&tmp_dir, &configs).unwrap();
+        // Nothing is created.
+        assert_eq!(std::fs::read_dir(tmp_dir.path()).unwrap().count(), 0);
+
+        let mut invalid_configs = configs.clone();
+        invalid_configs.state_kv_db_config.max_write_buffer_number = 0;
+        assert!(StateKvDb::validate_open_config(&tmp_dir, &invalid_configs).is_err());
+
+        let file = TempPath::new();
+        file.create_as_file().unwrap();
+        assert!(StateKvDb::validate_open_config(&file, &configs).is_err());
+
+        StateKvDb::validate_num_shards(NUM_STATE_SHARDS).unwrap();
+        assert!(StateKvDb::validate_num_shards(12).is_err());
+    }
+
+    #[test]
+    fn test_retry_on_lock_error() {
+        let lock_error = || {
+            anyhow::anyhow!(
+                "IO error: While lock file: /tmp/state_kv_db/metadata/LOCK: Resource temporarily \
+                 unavailable"
+            )
+        };
+
+        // A transient lock error is retried.
+        let mut attempts = 0;
+        let result = StateKvDb::retry_on_lock_error(3, Duration::from_millis(1), || {
+            attempts += 1;
+            if attempts == 1 {
+                Err(lock_error())
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result.unwrap(), 2);
+
+        // Until the retries run out.
+        let mut attempts = 0;
+        let result = StateKvDb::retry_on_lock_error(3, Duration::from_millis(1), || {
+            attempts += 1;
+            Err::<(), _>(lock_error())
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 4);
+
+        // Other errors are not retried.
+        let mut attempts = 0;
+        let result = StateKvDb::retry_on_lock_error(3, Duration::from_millis(1), || {
+            attempts += 1;
+            Err::<(), _>(anyhow::anyhow!("Corruption: bad block"))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_periodic_stats_dump() {
+        let tmp_dir = TempPath::new();
+        let state_kv_db = Arc::new(
+            StateKvDb::open(
+                &tmp_dir,
+                RocksdbConfig::default(),
+                false,
+                None,
+                Arc::new(DefaultShardingStrategy),
+                false,
+            )
+            .unwrap(),
+        );
+
+        // Sharding is not enabled yet, so every shard shares the same db, which is dumped once.
+        let stats = state_kv_db.dump_stats().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].shard_id, 0);
+
+        // The interval never elapses during the test, so only the initial dump and the explicit
+        // ones happen.
+        let dumper = state_kv_db.start_periodic_stats_dump(Duration::from_secs(3600));
+        let num_dumps = Arc::clone(&dumper.num_dumps);
+        dumper.dump_now();
+        assert_eq!(num_dumps.load(Ordering::Relaxed), 2);
+        dumper.dump_now();
+        assert_eq!(num_dumps.load(Ordering::Relaxed), 3);
+
+        // Dropping the dumper stops and joins its thread, so nothing is dumped afterwards.
+        drop(dumper);
+        assert_eq!(num_dumps.load(Ordering::Relaxed), 3);
+    }
+}
@@ -191,7 +191,7 @@ impl StateDb {
         read_opts.set_prefix_same_as_start(true);
         let mut iter = self
             .state_kv_db
-            .db_shard(state_key.get_shard_id())
+            .db_shard(self.state_kv_db.shard_id(state_key))
             .iter::<StateValueSchema>(read_opts)?;
         iter.seek(&(state_key.clone(), version))?;
         Ok(iter
@@ -595,13 +595,16 @@ impl StateStore {
             .with_label_values(&["add_state_kv_batch"])
             .start_timer();
 
+        self.state_kv_db
+            .put_changelog(&value_state_sets, first_version, sharded_state_kv_batches)?;
+
         value_state_sets
             .par_iter()
             .enumerate()
             .flat_map_iter(|(i, kvs)| {
                 let version = first_version + i as Version;
                 kvs.iter().map(move |(k, v)| {
-                    sharded_state_kv_batches[k.get_shard_id() as usize]
+                    sharded_state_kv_batches[self.state_kv_db.shard_id(k) as usize]
                         .put::<StateValueSchema>(&(k.clone(), version), v)
                 })
             })
@@ -683,7 +686,7 @@ impl StateStore {
                     usage.add_item(key.size() + value.size());
                 } else {
                     // stale index of the tombstone at current version.
-                    sharded_state_kv_batches[key.get_shard_id() as usize]
+                    sharded_state_kv_batches[self.state_kv_db.shard_id(key) as usize]
                         .put::<StaleStateValueIndexSchema>(
                         &StaleStateValueIndex {
                             stale_since_version: version,
@@ -705,7 +708,7 @@ impl StateStore {
                 if let Some((old_version, old_value)) = old_version_and_value_opt {
                     usage.remove_item(key.size() + old_value.size());
                     // stale index of the old value at its version.
-                    sharded_state_kv_batches[key.get_shard_id() as usize]
+                    sharded_state_kv_batches[self.state_kv_db.shard_id(key) as usize]
                         .put::<StaleStateValueIndexSchema>(
                         &StaleStateValueIndex {
                             stale_since_version: version,
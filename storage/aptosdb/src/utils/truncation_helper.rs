@@ -10,7 +10,8 @@ use crate::{
         ledger_info::LedgerInfoSchema, stale_node_index::StaleNodeIndexSchema,
         stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
         stale_state_value_index::StaleStateValueIndexSchema, state_value::StateValueSchema,
-        transaction::TransactionSchema, transaction_accumulator::TransactionAccumulatorSchema,
+        state_value_changelog::StateValueChangelogSchema, transaction::TransactionSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
         transaction_info::TransactionInfoSchema, version_data::VersionDataSchema,
         write_set::WriteSetSchema,
     },
@@ -147,6 +148,9 @@ pub(crate) fn truncate_state_kv_db_single_shard(
         expected_current_version,
         &batch,
     )?;
+    if state_kv_db.changelog_enabled() {
+        delete_state_value_changelog(state_kv_db.db_shard(shard_id), target_version + 1, &batch)?;
+    }
     state_kv_db.commit_single_shard(target_version, shard_id, batch)
 }
 
@@ -347,6 +351,22 @@ fn delete_state_value_and_index(
     Ok(())
 }
 
+fn delete_state_value_changelog(
+    state_kv_db_shard: &DB,
+    start_version: Version,
+    batch: &SchemaBatch,
+) -> Result<()> {
+    let mut iter = state_kv_db_shard.iter::<StateValueChangelogSchema>(ReadOptions::default())?;
+    iter.seek(&start_version)?;
+
+    for item in iter {
+        let (key, _) = item?;
+        batch.delete::<StateValueChangelogSchema>(&key)?;
+    }
+
+    Ok(())
+}
+
 fn delete_stale_node_index_at_version<S>(
     state_merkle_db: &StateMerkleDb,
     version: Version,
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_config::config::RocksdbConfig;
+use aptos_config::config::{RocksdbConfig, RocksdbStatisticsLevel};
 use rocksdb::Options;
 
 // TODO: Clean this up. It is currently separated into its own crate
@@ -20,3 +20,49 @@ pub fn gen_rocksdb_options(config: &RocksdbConfig, readonly: bool) -> Options {
 
     db_opts
 }
+
+/// Options of each of the `num_shards` dbs of a sharded db, where the memtables of a shard fit in
+/// its even part of `config.memtable_memory_budget` (if set), collecting the statistics of
+/// `config.statistics_level`.
+pub fn gen_sharded_rocksdb_options(
+    config: &RocksdbConfig,
+    num_shards: usize,
+    readonly: bool,
+) -> Options {
+    let mut db_opts = gen_rocksdb_options(config, readonly);
+    if let Some(budget) = memtable_budget_per_shard(config, num_shards) {
+        db_opts.set_db_write_buffer_size(budget);
+    }
+    match config.statistics_level {
+        RocksdbStatisticsLevel::Disabled => (),
+        RocksdbStatisticsLevel::Minimal => db_opts.enable_statistics(),
+        RocksdbStatisticsLevel::Full => {
+            db_opts.enable_statistics();
+            db_opts.set_report_bg_io_stats(true);
+        },
+    }
+
+    db_opts
+}
+
+/// Sets the memtable sizes of one of the `num_cfs` column families of one of the `num_shards` dbs
+/// of a sharded db, so that the memtables of all the column families of the shard fit in its even
+/// part of `config.memtable_memory_budget` (if set).
+pub fn set_sharded_memtable_options(
+    config: &RocksdbConfig,
+    num_shards: usize,
+    num_cfs: usize,
+    cf_opts: &mut Options,
+) {
+    cf_opts.set_max_write_buffer_number(config.max_write_buffer_number);
+    if let Some(budget) = memtable_budget_per_shard(config, num_shards) {
+        let num_memtables = num_cfs.max(1) * config.max_write_buffer_number.max(1) as usize;
+        cf_opts.set_write_buffer_size(budget / num_memtables);
+    }
+}
+
+fn memtable_budget_per_shard(config: &RocksdbConfig, num_shards: usize) -> Option<usize> {
+    config
+        .memtable_memory_budget
+        .map(|budget| budget as usize / num_shards.max(1))
+}
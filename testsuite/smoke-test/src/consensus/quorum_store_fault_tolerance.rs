@@ -40,6 +40,7 @@ async fn generate_traffic_and_assert_committed(
             TransactionType::CoinTransfer {
                 invalid_transaction_ratio: 0,
                 sender_use_account_pool: false,
+                max_expiration_jitter_secs: 0,
             },
             70,
         ),
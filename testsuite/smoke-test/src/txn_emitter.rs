@@ -67,7 +67,7 @@ async fn test_txn_emmitter() {
                 (TransactionType::default_coin_transfer(), 20),
                 // // commenting this out given it consistently fails smoke test
                 // // and it seems to be called only from `test_txn_emmitter`
-                // (TransactionType::NftMintAndTransfer, 20),
+                // (TransactionType::default_nft_mint_and_transfer(), 20),
                 (
                     TransactionType::PublishPackage {
                         use_account_pool: false,
@@ -127,6 +127,7 @@ impl TransactinWorkload {
                     TransactionType::CoinTransfer {
                         invalid_transaction_ratio: 0,
                         sender_use_account_pool: true,
+                        max_expiration_jitter_secs: 0,
                     }
                 } else {
                     TransactionType::CallCustomModules {
@@ -144,7 +145,9 @@ impl TransactinWorkload {
                     vec![(write_type, 1)],
                 ])
             },
-            Self::NftMint => request.transaction_type(TransactionType::NftMintAndTransfer),
+            Self::NftMint => {
+                request.transaction_type(TransactionType::default_nft_mint_and_transfer())
+            },
         }
     }
 }
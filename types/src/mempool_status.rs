@@ -64,6 +64,19 @@ pub enum MempoolStatusCode {
     // transaction didn't pass vm_validation
     VmError = 5,
     UnknownStatus = 6,
+    // Replacement of a transaction with the same sequence number doesn't bump the gas price enough
+    ReplacementUnderpriced = 7,
+    // Gas unit price is above the sanity cap of Mempool
+    GasUnitPriceTooHigh = 8,
+    // Account reached the configured max number of transactions per account
+    TooManyTransactionsForAccount = 9,
+    // The max fee of the transaction exceeds the balance of its sender
+    InsufficientBalance = 10,
+    // Mempool is full and the transaction isn't ready, so it can't make room by evicting from
+    // the parking lot
+    MempoolIsFullNoEvictableParking = 11,
+    // Mempool admits transactions faster than its configured rate
+    RateLimited = 12,
 }
 
 impl TryFrom<u64> for MempoolStatusCode {
@@ -78,6 +91,12 @@ impl TryFrom<u64> for MempoolStatusCode {
             4 => Ok(MempoolStatusCode::InvalidUpdate),
             5 => Ok(MempoolStatusCode::VmError),
             6 => Ok(MempoolStatusCode::UnknownStatus),
+            7 => Ok(MempoolStatusCode::ReplacementUnderpriced),
+            8 => Ok(MempoolStatusCode::GasUnitPriceTooHigh),
+            9 => Ok(MempoolStatusCode::TooManyTransactionsForAccount),
+            10 => Ok(MempoolStatusCode::InsufficientBalance),
+            11 => Ok(MempoolStatusCode::MempoolIsFullNoEvictableParking),
+            12 => Ok(MempoolStatusCode::RateLimited),
             _ => Err("invalid StatusCode"),
         }
     }
@@ -111,3 +111,15 @@ pub fn get_account_sequence_number(
         None => Ok(0),
     }
 }
+
+/// returns the balance of the account's AptosCoin store from storage, None if it has none
+pub fn get_account_balance(
+    state_view: &DbStateView,
+    address: AccountAddress,
+) -> Result<Option<u64>> {
+    let account_state_view = state_view.as_account_with_state_view(&address);
+
+    Ok(account_state_view
+        .get_coin_store_resource()?
+        .map(|coin_store| coin_store.coin()))
+}